@@ -0,0 +1,245 @@
+use std::{env, ffi::OsString, path::PathBuf};
+
+use clap::{
+    builder::{PossibleValue, PossibleValuesParser},
+    Args, CommandFactory, Parser, Subcommand, ValueEnum,
+};
+use threat_dragon_generator::reports::report_generator;
+
+/// Generate OWASP Threat Dragon models and reports from docs-as-code YAML
+/// files. Without a command, the reports of the project are generated.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+impl Cli {
+    /// Parse the command line, `generate` being the default command so that
+    /// the tool keeps working with the environment variables only.
+    pub fn parse_with_default_command() -> Self {
+        let mut args: Vec<OsString> = env::args_os().collect();
+        let command = Self::command();
+        let first = args.get(1).and_then(|arg| arg.to_str());
+        let explicit = match first {
+            Some("help" | "-h" | "--help" | "-V" | "--version") => true,
+            Some(first) => command
+                .get_subcommands()
+                .any(|subcommand| subcommand.get_name() == first),
+            None => false,
+        };
+        if !explicit {
+            args.insert(1, OsString::from("generate"));
+        }
+        Self::parse_from(args)
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Generate the Threat Dragon model, the reports and the exports (default)
+    Generate(GenerateArgs),
+    /// Generate the outputs, then regenerate them when the project files change
+    Watch(GenerateArgs),
+    /// Create a project from a built-in template
+    Init(InitArgs),
+    /// Write the JSON Schemas of the YAML files, for editor completion
+    Schema(SchemaArgs),
+    /// Language server of the project YAML files, over stdio
+    Lsp(LspArgs),
+    /// Convert a model of another tool to docs-as-code YAML files
+    Import(ImportArgs),
+    /// Check the threat counts of each diagram against thresholds, for CI
+    Gate(GateArgs),
+    /// Compare two versions of the threat model by element name
+    Diff(DiffArgs),
+    /// Changelog and time to mitigate of the threats from the git history
+    History(HistoryArgs),
+}
+
+/// Location of the project files.
+#[derive(Args, Debug, Clone)]
+pub struct ProjectArgs {
+    /// Project configuration file
+    #[arg(long = "config", env = "CONFIG_PATH")]
+    pub config_path: PathBuf,
+    /// Threats file
+    #[arg(long = "threats", env = "THREAT_PATH")]
+    pub threat_path: PathBuf,
+    /// Directory of the diagram files
+    #[arg(long = "diagrams", env = "DIAGRAM_PATH")]
+    pub diagram_path: PathBuf,
+    /// Threat templates added to the built-in library
+    #[arg(long = "templates", env = "TEMPLATE_PATH")]
+    pub template_path: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct GenerateArgs {
+    #[command(flatten)]
+    pub project: ProjectArgs,
+    /// Directory of the generated files
+    #[arg(long = "output", env = "OUTPUT_PATH")]
+    pub output_path: PathBuf,
+    /// Formats to generate, separated by commas, all of them by default
+    #[arg(
+        long = "format",
+        env = "OUTPUT_FORMATS",
+        value_delimiter = ',',
+        value_parser = format_parser()
+    )]
+    pub formats: Vec<String>,
+}
+
+/// Formats of the report generator registry, with their description.
+fn format_parser() -> PossibleValuesParser {
+    PossibleValuesParser::new(
+        report_generator::generators()
+            .iter()
+            .map(|generator| PossibleValue::new(generator.name()).help(generator.description())),
+    )
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ImportArgs {
+    /// Format of the model
+    pub format: ImportFormat,
+    /// Model file
+    pub file: PathBuf,
+    /// Directory of the YAML files, existing files are never overwritten
+    #[arg(long = "output", short = 'o')]
+    pub output_path: PathBuf,
+    /// YAML table of the draw.io shape styles and their node type
+    #[arg(long = "mapping")]
+    pub mapping_path: Option<PathBuf>,
+}
+
+/// Thresholds are checked on each diagram file, a threat on several nodes
+/// counting once, the exit code is 1 when one of them is exceeded.
+#[derive(Args, Debug, Clone)]
+pub struct GateArgs {
+    #[command(flatten)]
+    pub project: ProjectArgs,
+    /// JUnit XML file of the results, one test case per diagram per threshold
+    #[arg(long = "junit", env = "JUNIT_PATH")]
+    pub junit_path: Option<PathBuf>,
+    /// Maximum number of Open High threats
+    #[arg(long, default_value_t = 0)]
+    pub max_open_high: usize,
+    /// Maximum number of Open Medium threats
+    #[arg(long)]
+    pub max_open_medium: Option<usize>,
+    /// Maximum number of Open Low threats
+    #[arg(long)]
+    pub max_open_low: Option<usize>,
+    /// Maximum number of Open threats on flows through a public network
+    #[arg(long)]
+    pub max_unmitigated_public_flows: Option<usize>,
+}
+
+/// Each version is a project folder laid out as `config.yaml`, `threats.yaml`
+/// and `diagrams`, or a git revision of the `--project` folder.
+#[derive(Args, Debug, Clone)]
+pub struct DiffArgs {
+    /// Old version, folder or git revision
+    pub old: String,
+    /// New version, folder or git revision
+    pub new: String,
+    /// Project folder read at the git revisions
+    #[arg(long = "project", default_value = ".")]
+    pub project_path: PathBuf,
+    /// Threat templates added to the built-in library
+    #[arg(long = "templates", env = "TEMPLATE_PATH")]
+    pub template_path: Option<PathBuf>,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = DiffFormat::Text)]
+    pub format: DiffFormat,
+    /// File of the diff, printed when not given
+    #[arg(long = "output", short = 'o')]
+    pub output_path: Option<PathBuf>,
+}
+
+/// The project folder is laid out as `config.yaml`, `threats.yaml` and
+/// `diagrams`, its history is read from the local git repository.
+#[derive(Args, Debug, Clone)]
+pub struct HistoryArgs {
+    /// Project folder
+    #[arg(long = "project", default_value = ".")]
+    pub project_path: PathBuf,
+    /// Threat templates added to the built-in library
+    #[arg(long = "templates", env = "TEMPLATE_PATH")]
+    pub template_path: Option<PathBuf>,
+    /// Directory of `history.md` and `history.xlsx`
+    #[arg(long = "output", short = 'o')]
+    pub output_path: PathBuf,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum DiffFormat {
+    Text,
+    Markdown,
+    Json,
+}
+
+/// Existing files are never overwritten.
+#[derive(Args, Debug, Clone)]
+pub struct InitArgs {
+    /// Project folder
+    #[arg(default_value = ".")]
+    pub project_path: PathBuf,
+    /// Project template: web-app, microservice-broker or mobile-backend
+    #[arg(long = "template", short = 't', default_value = "web-app")]
+    pub template: String,
+    /// Title of the project
+    #[arg(long = "title")]
+    pub title: Option<String>,
+    /// Owner of the project
+    #[arg(long = "owner")]
+    pub owner: Option<String>,
+    /// CI pipeline generating the reports and checking the thresholds
+    #[arg(long = "ci", value_enum)]
+    pub ci: Option<CiProvider>,
+    /// Ask for the title, the owner and the first components
+    #[arg(long = "interactive", short = 'i')]
+    pub interactive: bool,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum CiProvider {
+    /// GitHub Actions workflow
+    Github,
+    /// GitLab CI pipeline
+    Gitlab,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct SchemaArgs {
+    /// Directory of the schema files
+    #[arg(long = "output", short = 'o', default_value = "schemas")]
+    pub output_path: PathBuf,
+}
+
+/// The project folder is laid out as `config.yaml`, `threats.yaml` and
+/// `diagrams`, the root of the workspace given by the editor takes precedence.
+#[derive(Args, Debug, Clone)]
+pub struct LspArgs {
+    /// Project folder
+    #[arg(long = "project", default_value = ".")]
+    pub project_path: PathBuf,
+    /// Threat templates added to the built-in library
+    #[arg(long = "templates", env = "TEMPLATE_PATH")]
+    pub template_path: Option<PathBuf>,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum ImportFormat {
+    /// Open Threat Model, JSON or YAML
+    Otm,
+    /// JSON report of OWASP pytm (`tm.py --json`)
+    Pytm,
+    /// Microsoft Threat Modeling Tool model (`.tm7`)
+    Tm7,
+    /// draw.io (diagrams.net) file, compressed or not
+    Drawio,
+}
@@ -0,0 +1,34 @@
+use std::{fs, path::Path};
+
+use threat_dragon_generator::{
+    diff::{diff_reports, project_diff},
+    project::{project::Project, revision},
+};
+
+use crate::cli::cli::{DiffArgs, DiffFormat};
+
+use super::fail;
+
+pub fn run(args: &DiffArgs) {
+    let load = |version: &str| {
+        let folder = Path::new(version);
+        let project = if folder.is_dir() {
+            Project::load_folder(folder, args.template_path.as_deref())
+        } else {
+            revision::load_revision(&args.project_path, version, args.template_path.as_deref())
+        };
+        project.unwrap_or_else(|error| fail(error))
+    };
+    let diff = project_diff::diff(&load(&args.old), &load(&args.new));
+
+    let content = match args.format {
+        DiffFormat::Text => diff_reports::render_text(&diff),
+        DiffFormat::Markdown => diff_reports::render_markdown(&diff),
+        DiffFormat::Json => serde_json::to_string_pretty(&diff).unwrap_or_else(|error| fail(error)),
+    };
+    match &args.output_path {
+        Some(output_path) => fs::write(output_path, content)
+            .unwrap_or_else(|error| fail(format!("{}: {}", output_path.display(), error))),
+        None => print!("{}", content),
+    }
+}
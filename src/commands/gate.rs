@@ -0,0 +1,75 @@
+use threat_dragon_generator::{
+    exports::junit,
+    input::threat::Severity,
+    project::project::Project,
+    validation::{
+        gate::{self, Gate, GateRule},
+        validator,
+    },
+};
+
+use crate::cli::cli::GateArgs;
+
+use super::fail;
+
+pub fn run(args: &GateArgs) {
+    let project = Project::load(
+        &args.project.config_path,
+        &args.project.threat_path,
+        &args.project.diagram_path,
+        args.project.template_path.as_deref(),
+    )
+    .unwrap_or_else(|error| fail(error));
+
+    let issues = validator::validate(
+        &project.diagrams,
+        &project.config,
+        &project.threats,
+        &project.component_overrides,
+    );
+    issues.iter().for_each(|issue| eprintln!("{}", issue));
+    if validator::has_errors(&issues) {
+        std::process::exit(1);
+    }
+
+    let gates: Vec<Gate> = [
+        (
+            GateRule::OpenThreats(Severity::High),
+            Some(args.max_open_high),
+        ),
+        (
+            GateRule::OpenThreats(Severity::Medium),
+            args.max_open_medium,
+        ),
+        (GateRule::OpenThreats(Severity::Low), args.max_open_low),
+        (
+            GateRule::UnmitigatedPublicFlows,
+            args.max_unmitigated_public_flows,
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(rule, max)| max.map(|max| Gate { rule, max }))
+    .collect();
+
+    let results = gate::evaluate(&project.diagrams, &project.threats, &gates);
+    if let Some(junit_path) = &args.junit_path {
+        junit::create_export(junit_path, &results).unwrap_or_else(|error| fail(error));
+    }
+
+    let failures: Vec<_> = results.iter().filter(|result| !result.passed()).collect();
+    failures.iter().for_each(|result| {
+        eprintln!("gate failed: {}", result);
+        result
+            .threats
+            .iter()
+            .for_each(|threat| eprintln!("  {}", threat));
+    });
+    println!(
+        "{} of {} gate(s) passed",
+        results.len() - failures.len(),
+        results.len()
+    );
+    if !failures.is_empty() {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,79 @@
+use std::{collections::HashSet, fs, path::Path};
+
+use threat_dragon_generator::{
+    project::project::Project,
+    reports::{
+        output_sink::FolderSink,
+        report_error::ReportError,
+        report_generator::{self, ResolvedModel},
+    },
+    validation::validator,
+};
+
+use crate::cli::cli::GenerateArgs;
+
+use super::fail;
+
+pub fn run(args: &GenerateArgs) {
+    let project = Project::load(
+        &args.project.config_path,
+        &args.project.threat_path,
+        &args.project.diagram_path,
+        args.project.template_path.as_deref(),
+    )
+    .unwrap_or_else(|error| fail(error));
+    let issues = project.validate();
+    issues.iter().for_each(|issue| eprintln!("{}", issue));
+    if validator::has_errors(&issues) {
+        // The findings still go to code scanning, the other outputs are not
+        // written.
+        if args.formats.is_empty() || args.formats.iter().any(|format| format == "sarif") {
+            write_outputs(&project, &args.output_path, &["sarif".to_string()], None)
+                .unwrap_or_else(|error| fail(error));
+        }
+        std::process::exit(1);
+    }
+
+    write_outputs(&project, &args.output_path, &args.formats, None)
+        .unwrap_or_else(|error| fail(error));
+}
+
+/// Write the outputs of the formats in `output_folder_path`, every format
+/// when `formats` is empty. With `affected`, the outputs of each diagram are
+/// only written for the diagrams listed, the outputs covering the whole
+/// project are always written. The files are named after the output
+/// directory, resolved for paths such as `.`.
+pub fn write_outputs(
+    project: &Project,
+    output_folder_path: &Path,
+    formats: &[String],
+    affected: Option<&HashSet<String>>,
+) -> Result<(), ReportError> {
+    let generators = if formats.is_empty() {
+        report_generator::generators()
+    } else {
+        report_generator::select(formats)?
+    };
+    let mut sink = FolderSink::new(output_folder_path)?;
+
+    let name = output_folder_path
+        .file_name()
+        .map(|directory| directory.to_string_lossy().to_string())
+        .or_else(|| {
+            fs::canonicalize(output_folder_path)
+                .ok()?
+                .file_name()
+                .map(|directory| directory.to_string_lossy().to_string())
+        })
+        .ok_or_else(|| {
+            ReportError::Save(format!(
+                "{}: the output directory has no name for the files",
+                output_folder_path.display()
+            ))
+        })?;
+    let model = ResolvedModel::new(project, &name).with_affected(affected);
+    for generator in generators.iter() {
+        generator.generate(&model, &mut sink)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use std::fs;
+
+use threat_dragon_generator::{
+    history::history,
+    project::revision,
+    reports::{markdown::markdown_reports, xls::xls_reports},
+};
+
+use crate::cli::cli::HistoryArgs;
+
+use super::fail;
+
+pub fn run(args: &HistoryArgs) {
+    let commits = revision::log(&args.project_path).unwrap_or_else(|error| fail(error));
+    // Versions that cannot be loaded, for example with a YAML error, are
+    // skipped: their changes show up in the next valid version.
+    let versions: Vec<_> = commits
+        .into_iter()
+        .filter_map(|commit| {
+            match revision::load_revision(
+                &args.project_path,
+                &commit.hash,
+                args.template_path.as_deref(),
+            ) {
+                Ok(project) => Some((commit, project)),
+                Err(error) => {
+                    eprintln!("skipping {}: {}", history::short_hash(&commit.hash), error);
+                    None
+                }
+            }
+        })
+        .collect();
+    let Some((_, last)) = versions.last() else {
+        eprintln!("no commit of the threat model found");
+        std::process::exit(1);
+    };
+    let history = history::build(&versions);
+
+    fs::create_dir_all(&args.output_path).unwrap_or_else(|error| fail(error));
+    markdown_reports::create_history(&args.output_path, &last.config, &history)
+        .unwrap_or_else(|error| fail(error));
+    xls_reports::create_history_report(&args.output_path, &history)
+        .unwrap_or_else(|error| fail(error));
+    println!(
+        "{} change(s) in {} commit(s) written to {}",
+        history.events.len(),
+        versions.len(),
+        args.output_path.display()
+    );
+}
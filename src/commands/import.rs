@@ -0,0 +1,24 @@
+use threat_dragon_generator::imports::{drawio, otm, pytm, tm7};
+
+use crate::cli::cli::{ImportArgs, ImportFormat};
+
+use super::fail;
+
+pub fn run(args: &ImportArgs) {
+    let project = match args.format {
+        ImportFormat::Otm => otm::import(&args.file),
+        ImportFormat::Pytm => pytm::import(&args.file),
+        ImportFormat::Tm7 => tm7::import(&args.file),
+        ImportFormat::Drawio => drawio::import(&args.file, args.mapping_path.as_deref()),
+    };
+    let project = project.unwrap_or_else(|error| fail(error));
+    project
+        .save(&args.output_path)
+        .unwrap_or_else(|error| fail(error));
+    println!(
+        "{} diagram(s) and {} threat(s) written to {}",
+        project.diagrams.len(),
+        project.threats.len(),
+        args.output_path.display()
+    );
+}
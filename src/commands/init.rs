@@ -0,0 +1,59 @@
+use std::io::{self, BufRead, Write};
+
+use threat_dragon_generator::scaffold::scaffold::{self, ProjectTemplate};
+
+use crate::cli::cli::{CiProvider, InitArgs};
+
+use super::fail;
+
+pub fn run(args: &InitArgs) {
+    let template = ProjectTemplate::find(&args.template).unwrap_or_else(|error| fail(error));
+    let mut title = args
+        .title
+        .clone()
+        .unwrap_or_else(|| template.config.title.clone());
+    let mut owner = args.owner.clone().unwrap_or_default();
+    let mut components = Vec::new();
+    if args.interactive {
+        title = ask("Title", &title);
+        owner = ask("Owner", &owner);
+        components = ask("First components, separated by commas", "")
+            .split(',')
+            .map(|component| component.trim().to_string())
+            .filter(|component| !component.is_empty())
+            .collect();
+    }
+
+    let project = template.to_project(&title, &owner, &components);
+    let extra = match args.ci {
+        Some(CiProvider::Github) => vec![scaffold::github_workflow()],
+        Some(CiProvider::Gitlab) => vec![scaffold::gitlab_ci()],
+        None => Vec::new(),
+    }
+    .into_iter()
+    .map(|(path, content)| (args.project_path.join(path), content))
+    .collect();
+    let files = scaffold::create_project(&args.project_path, &project, extra)
+        .unwrap_or_else(|error| fail(error));
+    files
+        .iter()
+        .for_each(|path| println!("created {}", path.display()));
+}
+
+/// Read a line of the standard input, `default` when it is empty.
+fn ask(question: &str, default: &str) -> String {
+    if default.is_empty() {
+        print!("{}: ", question);
+    } else {
+        print!("{} [{}]: ", question, default);
+    }
+    let _ = io::stdout().flush();
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer).is_err() {
+        return default.to_string();
+    }
+    match answer.trim() {
+        "" => default.to_string(),
+        answer => answer.to_string(),
+    }
+}
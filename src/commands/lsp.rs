@@ -0,0 +1,14 @@
+use threat_dragon_generator::lsp::server::Server;
+
+use crate::cli::cli::LspArgs;
+
+use super::fail;
+
+pub fn run(args: &LspArgs) {
+    let mut server = Server::new(args.project_path.clone(), args.template_path.clone());
+    match server.run() {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(1),
+        Err(error) => fail(error),
+    }
+}
@@ -0,0 +1,18 @@
+use std::{fmt::Display, process};
+
+pub mod diff;
+pub mod gate;
+pub mod generate;
+pub mod history;
+pub mod import;
+pub mod init;
+pub mod lsp;
+pub mod schema;
+pub mod watch;
+
+/// Print an error of the inputs or of the file system and exit with code 2,
+/// code 1 being kept for the checks that fail.
+pub fn fail(error: impl Display) -> ! {
+    eprintln!("error: {}", error);
+    process::exit(2)
+}
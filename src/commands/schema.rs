@@ -0,0 +1,10 @@
+use threat_dragon_generator::schema::schema;
+
+use crate::cli::cli::SchemaArgs;
+
+use super::fail;
+
+pub fn run(args: &SchemaArgs) {
+    schema::create_schemas(&args.output_path).unwrap_or_else(|error| fail(error));
+    println!("schemas written to {}", args.output_path.display());
+}
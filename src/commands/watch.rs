@@ -0,0 +1,246 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::PathBuf,
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use threat_dragon_generator::{project::project::Project, validation::validator};
+
+use crate::{cli::cli::GenerateArgs, commands::generate};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Poll the modification times of the project files and regenerate the
+/// outputs of the diagrams whose file changed, with their child diagrams. A
+/// change of the configuration, the threats or the templates regenerates
+/// every diagram, as does a change of an included file. Invalid inputs leave
+/// the previous outputs in place.
+pub fn run(args: &GenerateArgs) {
+    // Files changed since the last successful generation, `None` when every
+    // output has to be written.
+    let mut pending: Option<HashSet<PathBuf>> = None;
+    // Files included by the project when it was last loaded.
+    let mut included = Vec::new();
+    regenerate(args, &mut pending, &mut included);
+    let mut files = snapshot(args, &included);
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        let current = snapshot(args, &included);
+        if current == files {
+            continue;
+        }
+        if let Some(pending) = pending.as_mut() {
+            pending.extend(
+                current
+                    .iter()
+                    .filter(|(path, modified)| files.get(*path) != Some(*modified))
+                    .map(|(path, _)| path.clone()),
+            );
+            pending.extend(
+                files
+                    .keys()
+                    .filter(|path| !current.contains_key(*path))
+                    .cloned(),
+            );
+        }
+        files = current;
+        let previous = included.clone();
+        regenerate(args, &mut pending, &mut included);
+        if included != previous {
+            files = snapshot(args, &included);
+        }
+    }
+}
+
+fn regenerate(
+    args: &GenerateArgs,
+    pending: &mut Option<HashSet<PathBuf>>,
+    included: &mut Vec<PathBuf>,
+) {
+    let project = match Project::load(
+        &args.project.config_path,
+        &args.project.threat_path,
+        &args.project.diagram_path,
+        args.project.template_path.as_deref(),
+    ) {
+        Ok(project) => project,
+        Err(error) => {
+            eprintln!("{}", error);
+            eprintln!("outputs kept, waiting for changes");
+            return;
+        }
+    };
+    included.clone_from(&project.included_paths);
+    let issues = project.validate();
+    issues.iter().for_each(|issue| eprintln!("{}", issue));
+    if validator::has_errors(&issues) {
+        eprintln!("outputs kept, waiting for changes");
+        return;
+    }
+
+    let affected = pending
+        .as_ref()
+        .and_then(|changed| affected_diagrams(args, &project, changed));
+    match generate::write_outputs(
+        &project,
+        &args.output_path,
+        &args.formats,
+        affected.as_ref(),
+    ) {
+        Ok(()) => {
+            match &affected {
+                Some(affected) => {
+                    let mut titles: Vec<&String> = affected.iter().collect();
+                    titles.sort();
+                    println!(
+                        "regenerated {} diagram(s): {}",
+                        titles.len(),
+                        titles
+                            .iter()
+                            .map(|title| title.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    );
+                }
+                None => println!("generated {}", args.output_path.display()),
+            }
+            *pending = Some(HashSet::new());
+        }
+        Err(error) => eprintln!("{}", error),
+    }
+}
+
+/// Titles of the diagrams defined in the changed files and of their child
+/// diagrams, `None` when a change concerns every diagram.
+fn affected_diagrams(
+    args: &GenerateArgs,
+    project: &Project,
+    changed: &HashSet<PathBuf>,
+) -> Option<HashSet<String>> {
+    let shared = [
+        Some(&args.project.config_path),
+        Some(&args.project.threat_path),
+        args.project.template_path.as_ref(),
+    ];
+    if shared.iter().flatten().any(|path| changed.contains(*path))
+        || project
+            .included_paths
+            .iter()
+            .any(|path| changed.contains(path))
+    {
+        return None;
+    }
+    Some(
+        project
+            .diagrams
+            .iter()
+            .filter(|diagram| {
+                project
+                    .diagram_paths
+                    .get(&diagram.title)
+                    .is_some_and(|path| changed.contains(path))
+            })
+            .flat_map(|diagram| {
+                let mut titles = vec![diagram.title.clone()];
+                titles.extend(
+                    diagram
+                        .create_child_diagrams(&project.config)
+                        .into_iter()
+                        .map(|child| child.title),
+                );
+                titles
+            })
+            .collect(),
+    )
+}
+
+/// Modification time of the project files and of the files they include.
+fn snapshot(args: &GenerateArgs, included: &[PathBuf]) -> BTreeMap<PathBuf, Option<SystemTime>> {
+    let mut paths = vec![
+        args.project.config_path.clone(),
+        args.project.threat_path.clone(),
+    ];
+    paths.extend(args.project.template_path.clone());
+    paths.extend(included.iter().cloned());
+    if let Ok(entries) = fs::read_dir(&args.project.diagram_path) {
+        paths.extend(
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file()),
+        );
+    }
+    paths
+        .into_iter()
+        .map(|path| {
+            let modified = fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            (path, modified)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::cli::cli::ProjectArgs;
+
+    fn titles(affected: Option<HashSet<String>>) -> Vec<String> {
+        let mut titles: Vec<String> = affected.unwrap().into_iter().collect();
+        titles.sort();
+        titles
+    }
+
+    #[test]
+    fn changes_select_their_diagrams() {
+        let folder = Path::new(env!("CARGO_MANIFEST_DIR")).join("exemples");
+        let args = GenerateArgs {
+            project: ProjectArgs {
+                config_path: folder.join("config.yaml"),
+                threat_path: folder.join("threats.yaml"),
+                diagram_path: folder.join("diagrams"),
+                template_path: Some(folder.join("templates.yaml")),
+            },
+            output_path: folder.join("output"),
+            formats: Vec::new(),
+        };
+        let mut project = Project::load_folder(&folder, None).unwrap();
+        project.included_paths.push(folder.join("shared.yaml"));
+        let affected = |paths: &[PathBuf]| {
+            affected_diagrams(&args, &project, &paths.iter().cloned().collect())
+        };
+
+        assert_eq!(affected(&[folder.join("config.yaml")]), None);
+        assert_eq!(affected(&[folder.join("threats.yaml")]), None);
+        assert_eq!(affected(&[folder.join("templates.yaml")]), None);
+        assert_eq!(affected(&[folder.join("shared.yaml")]), None);
+        assert_eq!(
+            affected(&[
+                folder.join("diagrams/Diagram2.yaml"),
+                folder.join("shared.yaml")
+            ]),
+            None
+        );
+
+        assert_eq!(
+            titles(affected(&[folder.join("diagrams/Diagram1.yaml")])),
+            vec!["A1_Diagram1", "A2_Child_Data_Flow"]
+        );
+        assert_eq!(
+            titles(affected(&[
+                folder.join("diagrams/Diagram2.yaml"),
+                folder.join("diagrams/Diagram3.yaml")
+            ])),
+            vec!["B1_Diagram2", "B2_Child_Data_Flow", "C1_Privacy"]
+        );
+        assert_eq!(
+            titles(affected(&[folder.join("diagrams/Removed.yaml")])),
+            Vec::<String>::new()
+        );
+    }
+}
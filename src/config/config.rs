@@ -1,6 +1,9 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+use crate::input::input_diagram::Node;
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Config {
     pub threat_dragon_version: String,
@@ -10,9 +13,12 @@ pub struct Config {
     pub trust_boundaries: Vec<TrustBoundary>,
     pub assets: Vec<Asset>,
     pub diagrams: Vec<Diagrams>,
+    /// Nodes shared by several diagrams, referenced with `component: <name>`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub components: Vec<Node>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TrustBoundary {
     pub name: String,
@@ -21,7 +27,7 @@ pub struct TrustBoundary {
     pub level_of_authorization: String,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Diagrams {
     pub name: String,
@@ -30,18 +36,9 @@ pub struct Diagrams {
     pub nodes: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Asset {
     pub name: String,
     pub description: String,
 }
-
-#[derive(Debug, Clone)]
-pub enum ConfigError {
-    UnknownError(String),
-}
-
-pub trait ConfigYaml {
-    fn from_config(&mut self, config: &Config) -> Result<(), ConfigError>;
-}
@@ -0,0 +1,123 @@
+use super::project_diff::{Change, ChangeKind, ProjectDiff, ThreatChange};
+
+/// Plain text, one line per change prefixed by `+`, `-` or `~`.
+pub fn render_text(diff: &ProjectDiff) -> String {
+    if diff.is_empty() {
+        return "No change in the threat model\n".to_string();
+    }
+    let mut document = String::new();
+    for (section, changes) in change_sections(diff) {
+        document.push_str(&format!("{}\n", section));
+        for change in changes {
+            document.push_str(&format!("  {} {}\n", sign(change.kind), describe(change)));
+        }
+    }
+    if !diff.threats.is_empty() {
+        document.push_str("Threats\n");
+        for change in diff.threats.iter() {
+            document.push_str(&format!(
+                "  {} {}\n",
+                sign(change.kind),
+                describe_threat(change)
+            ));
+        }
+    }
+    document
+}
+
+/// Markdown for pull-request comments, a list per kind of element.
+pub fn render_markdown(diff: &ProjectDiff) -> String {
+    let mut document = "## Threat model changes\n\n".to_string();
+    if diff.is_empty() {
+        document.push_str("No change in the threat model.\n");
+        return document;
+    }
+    for (section, changes) in change_sections(diff) {
+        document.push_str(&format!("### {}\n\n", section));
+        for change in changes {
+            document.push_str(&format!(
+                "- **{}** {}\n",
+                kind_label(change.kind),
+                describe(change).replace('|', "\\|")
+            ));
+        }
+        document.push('\n');
+    }
+    if !diff.threats.is_empty() {
+        document.push_str("### Threats\n\n");
+        for change in diff.threats.iter() {
+            document.push_str(&format!(
+                "- **{}** {}\n",
+                kind_label(change.kind),
+                describe_threat(change)
+            ));
+        }
+        document.push('\n');
+    }
+    document
+}
+
+fn change_sections(diff: &ProjectDiff) -> Vec<(&'static str, &Vec<Change>)> {
+    [
+        ("Nodes", &diff.nodes),
+        ("Flows", &diff.flows),
+        ("Trust boundaries", &diff.trust_boundaries),
+        ("Assets", &diff.assets),
+    ]
+    .into_iter()
+    .filter(|(_, changes)| !changes.is_empty())
+    .collect()
+}
+
+fn describe(change: &Change) -> String {
+    let name = match &change.diagram {
+        Some(diagram) => format!("{} > {}", diagram, change.name),
+        None => change.name.clone(),
+    };
+    if change.fields.is_empty() {
+        name
+    } else {
+        format!("{} ({})", name, change.fields.join(", "))
+    }
+}
+
+fn describe_threat(change: &ThreatChange) -> String {
+    let mut moves = Vec::new();
+    match change.kind {
+        ChangeKind::Changed => {
+            if let (Some(old), Some(new)) = (&change.old_status, &change.new_status) {
+                moves.push(format!("status {} -> {}", old, new));
+            }
+            if let (Some(old), Some(new)) = (&change.old_severity, &change.new_severity) {
+                moves.push(format!("severity {} -> {}", old, new));
+            }
+        }
+        ChangeKind::Added | ChangeKind::Removed => {
+            let status = change.new_status.as_ref().or(change.old_status.as_ref());
+            let severity = change
+                .new_severity
+                .as_ref()
+                .or(change.old_severity.as_ref());
+            if let (Some(status), Some(severity)) = (status, severity) {
+                moves.push(format!("{}, {}", status, severity));
+            }
+        }
+    }
+    format!("{} ({})", change.title, moves.join(", "))
+}
+
+fn sign(kind: ChangeKind) -> char {
+    match kind {
+        ChangeKind::Added => '+',
+        ChangeKind::Removed => '-',
+        ChangeKind::Changed => '~',
+    }
+}
+
+pub fn kind_label(kind: ChangeKind) -> &'static str {
+    match kind {
+        ChangeKind::Added => "Added",
+        ChangeKind::Removed => "Removed",
+        ChangeKind::Changed => "Changed",
+    }
+}
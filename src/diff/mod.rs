@@ -0,0 +1,2 @@
+pub mod diff_reports;
+pub mod project_diff;
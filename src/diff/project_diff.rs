@@ -0,0 +1,278 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    input::{
+        input_diagram::TypeNode,
+        threat::{Severity, Status, Threat},
+    },
+    json::json::changed_fields,
+    project::project::Project,
+};
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// Element added, removed or changed, identified by its name. `fields` lists
+/// the YAML fields that changed.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Change {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diagram: Option<String>,
+    pub kind: ChangeKind,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+}
+
+/// Threat added or removed, or whose status or severity moved. The old values
+/// are set for removed and moved threats, the new ones for added and moved
+/// threats.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreatChange {
+    pub title: String,
+    pub kind: ChangeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_status: Option<Status>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_status: Option<Status>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_severity: Option<Severity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_severity: Option<Severity>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectDiff {
+    pub nodes: Vec<Change>,
+    pub flows: Vec<Change>,
+    pub trust_boundaries: Vec<Change>,
+    pub assets: Vec<Change>,
+    pub threats: Vec<ThreatChange>,
+}
+
+impl ProjectDiff {
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+            && self.flows.is_empty()
+            && self.trust_boundaries.is_empty()
+            && self.assets.is_empty()
+            && self.threats.is_empty()
+    }
+}
+
+/// Compare two versions of the project by name: nodes and flows within their
+/// diagram, trust boundaries and assets of the configuration, and threats by
+/// title.
+pub fn diff(old: &Project, new: &Project) -> ProjectDiff {
+    let nodes = |project: &Project, flows: bool| {
+        project
+            .diagrams
+            .iter()
+            .flat_map(|diagram| {
+                diagram
+                    .nodes
+                    .iter()
+                    .filter(move |node| (node.type_node == TypeNode::Flow) == flows)
+                    .map(|node| {
+                        (
+                            (Some(diagram.title.clone()), node.name.clone()),
+                            to_value(node),
+                        )
+                    })
+            })
+            .collect::<BTreeMap<_, _>>()
+    };
+    let trust_boundaries = |project: &Project| {
+        project
+            .config
+            .trust_boundaries
+            .iter()
+            .map(|boundary| ((None, boundary.name.clone()), to_value(boundary)))
+            .collect::<BTreeMap<_, _>>()
+    };
+    let assets = |project: &Project| {
+        project
+            .config
+            .assets
+            .iter()
+            .map(|asset| ((None, asset.name.clone()), to_value(asset)))
+            .collect::<BTreeMap<_, _>>()
+    };
+
+    ProjectDiff {
+        nodes: changes(&nodes(old, false), &nodes(new, false)),
+        flows: changes(&nodes(old, true), &nodes(new, true)),
+        trust_boundaries: changes(&trust_boundaries(old), &trust_boundaries(new)),
+        assets: changes(&assets(old), &assets(new)),
+        threats: threat_changes(&old.threats, &new.threats),
+    }
+}
+
+type Key = (Option<String>, String);
+
+fn changes(old: &BTreeMap<Key, Value>, new: &BTreeMap<Key, Value>) -> Vec<Change> {
+    let change = |(diagram, name): &Key, kind: ChangeKind, fields: Vec<String>| Change {
+        name: name.clone(),
+        diagram: diagram.clone(),
+        kind,
+        fields,
+    };
+    let mut changes: Vec<Change> = old
+        .iter()
+        .filter_map(|(key, old_value)| match new.get(key) {
+            None => Some(change(key, ChangeKind::Removed, Vec::new())),
+            Some(new_value) => {
+                let fields = changed_fields(old_value, new_value);
+                (!fields.is_empty()).then(|| change(key, ChangeKind::Changed, fields))
+            }
+        })
+        .collect();
+    changes.extend(
+        new.keys()
+            .filter(|key| !old.contains_key(*key))
+            .map(|key| change(key, ChangeKind::Added, Vec::new())),
+    );
+    changes
+}
+
+fn threat_changes(old: &[Threat], new: &[Threat]) -> Vec<ThreatChange> {
+    // The last threat with a title wins, as in the reports.
+    let by_title = |threats: &[Threat]| -> BTreeMap<String, Threat> {
+        threats
+            .iter()
+            .map(|threat| (threat.title.clone(), threat.clone()))
+            .collect()
+    };
+    let old = by_title(old);
+    let new = by_title(new);
+
+    let mut changes: Vec<ThreatChange> = old
+        .iter()
+        .filter_map(|(title, old_threat)| match new.get(title) {
+            None => Some(ThreatChange {
+                title: title.clone(),
+                kind: ChangeKind::Removed,
+                old_status: Some(old_threat.status.clone()),
+                new_status: None,
+                old_severity: Some(old_threat.severity.clone()),
+                new_severity: None,
+            }),
+            Some(new_threat) => {
+                let status_moved = old_threat.status != new_threat.status;
+                let severity_moved = old_threat.severity != new_threat.severity;
+                (status_moved || severity_moved).then(|| ThreatChange {
+                    title: title.clone(),
+                    kind: ChangeKind::Changed,
+                    old_status: status_moved.then(|| old_threat.status.clone()),
+                    new_status: status_moved.then(|| new_threat.status.clone()),
+                    old_severity: severity_moved.then(|| old_threat.severity.clone()),
+                    new_severity: severity_moved.then(|| new_threat.severity.clone()),
+                })
+            }
+        })
+        .collect();
+    changes.extend(
+        new.iter()
+            .filter(|(title, _)| !old.contains_key(*title))
+            .map(|(title, threat)| ThreatChange {
+                title: title.clone(),
+                kind: ChangeKind::Added,
+                old_status: None,
+                new_status: Some(threat.status.clone()),
+                old_severity: None,
+                new_severity: Some(threat.severity.clone()),
+            }),
+    );
+    changes
+}
+
+fn to_value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    fn example() -> Project {
+        let folder = Path::new(env!("CARGO_MANIFEST_DIR")).join("exemples");
+        Project::load_folder(&folder, None).unwrap()
+    }
+
+    #[test]
+    fn same_project_has_no_change() {
+        assert!(diff(&example(), &example()).is_empty());
+    }
+
+    #[test]
+    fn changes_are_reported_by_name() {
+        let old = example();
+        let mut new = example();
+        let diagram = &mut new.diagrams[0];
+        let process = diagram
+            .nodes
+            .iter_mut()
+            .find(|node| node.name == "Kafka")
+            .unwrap();
+        process.description = "Event broker".to_string();
+        process.out_of_scope = Some(false);
+        diagram.nodes.retain(|node| node.name != "PROM_PROC");
+        new.config.trust_boundaries.remove(0);
+        new.threats[0].status = Status::Mitigated;
+
+        let diff = diff(&old, &new);
+        assert_eq!(diff.nodes.len(), 1);
+        assert_eq!(diff.nodes[0].name, "Kafka");
+        assert_eq!(diff.nodes[0].diagram.as_deref(), Some("A1_Diagram1"));
+        assert_eq!(diff.nodes[0].kind, ChangeKind::Changed);
+        assert_eq!(diff.nodes[0].fields, vec!["description", "outOfScope"]);
+
+        assert_eq!(diff.flows.len(), 1);
+        assert_eq!(diff.flows[0].name, "PROM_PROC");
+        assert_eq!(diff.flows[0].kind, ChangeKind::Removed);
+
+        assert_eq!(diff.trust_boundaries[0].name, "Enablers");
+        assert_eq!(diff.trust_boundaries[0].kind, ChangeKind::Removed);
+        assert!(diff.assets.is_empty());
+
+        assert_eq!(diff.threats.len(), 1);
+        assert_eq!(diff.threats[0].title, old.threats[0].title);
+        assert_eq!(diff.threats[0].old_status, Some(Status::Open));
+        assert_eq!(diff.threats[0].new_status, Some(Status::Mitigated));
+        assert_eq!(diff.threats[0].old_severity, None);
+    }
+
+    #[test]
+    fn threats_are_added_and_removed_by_title() {
+        let old = example();
+        let mut new = example();
+        new.threats[0].title = "Renamed threat".to_string();
+
+        let diff = diff(&old, &new);
+        let kinds: Vec<(&str, ChangeKind)> = diff
+            .threats
+            .iter()
+            .map(|change| (change.title.as_str(), change.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (old.threats[0].title.as_str(), ChangeKind::Removed),
+                ("Renamed threat", ChangeKind::Added)
+            ]
+        );
+    }
+}
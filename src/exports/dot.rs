@@ -0,0 +1,123 @@
+use crate::{
+    input::{
+        input_diagram::{InputDiagram, Node, TypeNode},
+        threat::Threat,
+    },
+    reports::sections,
+};
+
+pub fn render_dot(input_diagram: &InputDiagram, threats: &[Threat]) -> String {
+    let node_threats = sections::node_threats(input_diagram, threats);
+    let threats_of = |node: &Node| -> Vec<&Threat> {
+        node_threats
+            .iter()
+            .filter(|(threat_node, _)| threat_node.name == node.name)
+            .map(|(_, threat)| *threat)
+            .collect()
+    };
+
+    let mut document = format!("digraph {} {{\n", quote(&input_diagram.title));
+    document.push_str(&format!("  label={};\n", quote(&input_diagram.title)));
+    document.push_str("  labelloc=t;\n  rankdir=LR;\n");
+    document.push_str("  node [fontname=\"Helvetica\"];\n  edge [fontname=\"Helvetica\"];\n\n");
+
+    // Elements grouped by trust boundary, sorted to keep the output stable.
+    let boundaries = sections::nodes_by_trust_boundary(input_diagram);
+    for (boundary, nodes) in boundaries.iter() {
+        let indent = match boundary {
+            Some(boundary) => {
+                document.push_str(&format!(
+                    "  subgraph {} {{\n    label={};\n    style=dashed;\n    color=gray40;\n",
+                    quote(&format!("cluster_{}", boundary)),
+                    quote(boundary)
+                ));
+                "    "
+            }
+            None => "  ",
+        };
+        for node in nodes {
+            document.push_str(&format!(
+                "{}{} [{}];\n",
+                indent,
+                quote(&node.name),
+                attributes(node, &threats_of(node))
+            ));
+        }
+        if boundary.is_some() {
+            document.push_str("  }\n");
+        }
+    }
+
+    let flows: Vec<&Node> = input_diagram
+        .nodes
+        .iter()
+        .filter(|node| node.type_node == TypeNode::Flow)
+        .collect();
+    if !flows.is_empty() {
+        document.push('\n');
+    }
+    for flow in flows {
+        if let (Some(source), Some(destination)) = (&flow.source, &flow.destination) {
+            document.push_str(&format!(
+                "  {} -> {} [{}];\n",
+                quote(source),
+                quote(destination),
+                attributes(flow, &threats_of(flow))
+            ));
+        }
+    }
+
+    document.push_str("}\n");
+    document
+}
+
+fn attributes(node: &Node, threats: &[&Threat]) -> String {
+    let mut attributes = Vec::new();
+    match node.type_node {
+        TypeNode::Actor => attributes.push("shape=box".to_string()),
+        TypeNode::Process => attributes.push("shape=ellipse".to_string()),
+        TypeNode::Store => attributes.push("shape=cylinder".to_string()),
+        TypeNode::Flow => {}
+    }
+    let label = match (&node.type_node, &node.protocol) {
+        (TypeNode::Flow, Some(protocol)) => format!("{}\n{}", node.name, protocol),
+        _ => node.name.clone(),
+    };
+    attributes.push(format!("label={}", quote(&label)));
+    if node.out_of_scope.unwrap_or(false) {
+        attributes.push("style=dashed".to_string());
+    }
+    if !threats.is_empty() {
+        attributes.push("color=red".to_string());
+        if node.type_node == TypeNode::Flow {
+            attributes.push("fontcolor=red".to_string());
+        }
+    }
+    attributes.push(format!("tooltip={}", quote(&tooltip(node, threats))));
+    attributes.join(", ")
+}
+
+fn tooltip(node: &Node, threats: &[&Threat]) -> String {
+    if threats.is_empty() {
+        return format!("{}: no threat", node.name);
+    }
+    let mut tooltip = format!("{} threats:", node.name);
+    threats.iter().for_each(|threat| {
+        tooltip.push_str(&format!(
+            "\n- {} ({}, {})",
+            threat.title, threat.severity, threat.status
+        ))
+    });
+    tooltip
+}
+
+/// Quote a DOT identifier, escaping quotes, backslashes and line breaks.
+fn quote(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
@@ -0,0 +1,16 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone)]
+pub enum ExportError {
+    Model(String),
+    Save(String),
+}
+
+impl Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportError::Model(e) => write!(f, "cannot export model: {}", e),
+            ExportError::Save(e) => write!(f, "cannot save export: {}", e),
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use std::{fs, path::Path};
+
+use crate::{validation::gate::GateResult, xml::xml::escape};
+
+use super::export_error::ExportError;
+
+/// Write the gate results as JUnit XML.
+pub fn create_export(export_path: &Path, results: &[GateResult]) -> Result<(), ExportError> {
+    fs::write(export_path, render_junit(results)).map_err(|e| ExportError::Save(format!("{}", e)))
+}
+
+/// A test suite per diagram and a test case per gate, failed when the
+/// threshold of the gate is exceeded.
+pub fn render_junit(results: &[GateResult]) -> String {
+    let failures = results.iter().filter(|result| !result.passed()).count();
+    let mut document = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n".to_string();
+    document.push_str(&format!(
+        "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\">\n",
+        env!("CARGO_PKG_NAME"),
+        results.len(),
+        failures
+    ));
+
+    let mut diagrams: Vec<&String> = Vec::new();
+    results.iter().for_each(|result| {
+        if !diagrams.contains(&&result.diagram) {
+            diagrams.push(&result.diagram);
+        }
+    });
+    for diagram in diagrams {
+        let cases: Vec<&GateResult> = results
+            .iter()
+            .filter(|result| result.diagram == *diagram)
+            .collect();
+        document.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">\n",
+            escape(diagram),
+            cases.len(),
+            cases.iter().filter(|result| !result.passed()).count()
+        ));
+        for case in cases {
+            document.push_str(&format!(
+                "    <testcase name=\"{}\" classname=\"{}\"",
+                escape(&case.gate.rule.name()),
+                escape(diagram)
+            ));
+            if case.passed() {
+                document.push_str("/>\n");
+                continue;
+            }
+            document.push_str(&format!(
+                ">\n      <failure message=\"{}\" type=\"{}\">{}</failure>\n    </testcase>\n",
+                escape(&case.to_string()),
+                escape(&case.gate.rule.name()),
+                escape(&case.threats.join("\n"))
+            ));
+        }
+        document.push_str("  </testsuite>\n");
+    }
+    document.push_str("</testsuites>\n");
+    document
+}
+
+#[cfg(test)]
+mod tests {
+    use roxmltree::Document;
+
+    use super::*;
+    use crate::{
+        input::threat::Severity,
+        validation::gate::{Gate, GateRule},
+    };
+
+    fn result(diagram: &str, severity: Severity, max: usize, threats: &[&str]) -> GateResult {
+        GateResult {
+            diagram: diagram.to_string(),
+            gate: Gate {
+                rule: GateRule::OpenThreats(severity),
+                max,
+            },
+            threats: threats.iter().map(|threat| threat.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn a_suite_per_diagram_and_a_case_per_gate() {
+        let junit = render_junit(&[
+            result("Orders", Severity::High, 0, &["Flooding <API> (Web)"]),
+            result("Orders", Severity::Medium, 1, &["Sniffing (Login)"]),
+            result("R&D", Severity::High, 0, &[]),
+        ]);
+        let document = Document::parse(&junit).unwrap();
+        let root = document.root_element();
+        assert_eq!(root.attribute("tests"), Some("3"));
+        assert_eq!(root.attribute("failures"), Some("1"));
+
+        let suites: Vec<(&str, &str, &str)> = root
+            .children()
+            .filter(|node| node.has_tag_name("testsuite"))
+            .map(|suite| {
+                (
+                    suite.attribute("name").unwrap(),
+                    suite.attribute("tests").unwrap(),
+                    suite.attribute("failures").unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(suites, vec![("Orders", "2", "1"), ("R&D", "1", "0")]);
+
+        let failures: Vec<_> = document
+            .descendants()
+            .filter(|node| node.has_tag_name("failure"))
+            .collect();
+        assert_eq!(failures.len(), 1);
+        let failure = failures[0];
+        assert_eq!(failure.attribute("type"), Some("open-high-threats"));
+        assert_eq!(
+            failure.attribute("message"),
+            Some("Orders: 1 Open High threats found, at most 0 allowed")
+        );
+        assert_eq!(failure.text(), Some("Flooding <API> (Web)"));
+        assert_eq!(
+            failure.parent().unwrap().attribute("name"),
+            Some("open-high-threats")
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use crate::{
+    config::config::Config,
+    input::input_diagram::{InputDiagram, Node, TypeNode},
+    process::process::MappingFromInputNode,
+    reports::sections,
+    threat_dragon_modeling::diagram::Body,
+};
+
+pub fn render_mermaid(input_diagram: &InputDiagram, config: &Config) -> String {
+    let mut document = format!(
+        "---\ntitle: {}\n---\nflowchart LR\n",
+        yaml_string(&input_diagram.title)
+    );
+    let mut styles = Vec::new();
+
+    // Mermaid identifiers cannot hold any character, elements are numbered.
+    let mut identifiers: HashMap<&String, String> = HashMap::new();
+    for (index, (boundary, nodes)) in sections::nodes_by_trust_boundary(input_diagram)
+        .iter()
+        .enumerate()
+    {
+        let indent = match boundary {
+            Some(boundary) => {
+                document.push_str(&format!(
+                    "  subgraph b{} [\"{}\"]\n",
+                    index,
+                    label(boundary)
+                ));
+                styles.push(format!("  style b{} stroke-dasharray: 4 3", index));
+                "    "
+            }
+            None => "  ",
+        };
+        for node in nodes {
+            let identifier = format!("n{}", identifiers.len());
+            document.push_str(&format!("{}{}{}\n", indent, identifier, shape(node)));
+            styles.push(format!("  style {} {}", identifier, style(node, config)));
+            identifiers.insert(&node.name, identifier);
+        }
+        if boundary.is_some() {
+            document.push_str("  end\n");
+        }
+    }
+
+    let flows = input_diagram
+        .nodes
+        .iter()
+        .filter(|node| node.type_node == TypeNode::Flow);
+    let mut link_index = 0;
+    for flow in flows {
+        let source = flow.source.as_ref().and_then(|name| identifiers.get(name));
+        let destination = flow
+            .destination
+            .as_ref()
+            .and_then(|name| identifiers.get(name));
+        if let (Some(source), Some(destination)) = (source, destination) {
+            let flow_label = match &flow.protocol {
+                Some(protocol) => format!("{} ({})", flow.name, protocol),
+                None => flow.name.clone(),
+            };
+            document.push_str(&format!(
+                "  {} -->|\"{}\"| {}\n",
+                source,
+                label(&flow_label),
+                destination
+            ));
+            styles.push(format!(
+                "  linkStyle {} {}",
+                link_index,
+                style(flow, config)
+            ));
+            link_index += 1;
+        }
+    }
+
+    styles.iter().for_each(|style| {
+        document.push_str(style);
+        document.push('\n');
+    });
+    document
+}
+
+fn shape(node: &Node) -> String {
+    let name = label(&node.name);
+    match node.type_node {
+        TypeNode::Actor => format!("[\"{}\"]", name),
+        TypeNode::Process => format!("((\"{}\"))", name),
+        TypeNode::Store => format!("[(\"{}\")]", name),
+        TypeNode::Flow => format!("[\"{}\"]", name),
+    }
+}
+
+/// Stroke of the element, following the Threat Dragon rendering.
+fn style(node: &Node, config: &Config) -> String {
+    let body = Body::from_input_diagram(node, config);
+    let mut style = format!(
+        "stroke:{},stroke-width:{}px",
+        body.stroke, body.stroke_width
+    );
+    if let Some(stroke_dasharray) = body.stroke_dasharray {
+        style.push_str(&format!(",stroke-dasharray: {}", stroke_dasharray));
+    }
+    style
+}
+
+/// Double-quoted YAML scalar of the front matter, whatever the characters
+/// of the value.
+fn yaml_string(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r")
+            .replace('\t', "\\t")
+    )
+}
+
+/// Escape a label written between double quotes.
+fn label(value: &str) -> String {
+    value.replace('"', "#quot;").replace('\n', "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+threatDragonVersion: "2.2.0"
+title: Orders
+owner: Platform
+description: ""
+trustBoundaries: []
+assets: []
+diagrams: []
+"#;
+
+    fn render(diagram: &str) -> String {
+        let input_diagram: InputDiagram = serde_yaml::from_str(diagram).unwrap();
+        let config: Config = serde_yaml::from_str(CONFIG).unwrap();
+        render_mermaid(&input_diagram, &config)
+    }
+
+    #[test]
+    fn title_is_quoted_in_the_front_matter() {
+        let document = render(
+            r#"
+title: "Orders: \"v2\" # draft\n---\\"
+description: ""
+nodes: []
+"#,
+        );
+        let front_matter = document
+            .strip_prefix("---\n")
+            .and_then(|rest| rest.split("\n---\n").next())
+            .unwrap();
+        let front_matter: serde_yaml::Value = serde_yaml::from_str(front_matter).unwrap();
+        assert_eq!(front_matter["title"], "Orders: \"v2\" # draft\n---\\");
+        assert!(document.contains("\n---\nflowchart LR\n"), "{}", document);
+    }
+
+    #[test]
+    fn nodes_boundaries_and_flows() {
+        let document = render(
+            r#"
+title: Orders
+description: ""
+nodes:
+  - name: User "admin"
+    type: actor
+    description: ""
+    trustBoundary: Internet
+    threats: []
+  - name: Web
+    type: process
+    description: ""
+    threats: []
+  - name: Login
+    type: flow
+    description: ""
+    source: User "admin"
+    destination: Web
+    protocol: HTTPS
+    threats: []
+"#,
+        );
+        let user = document
+            .lines()
+            .find(|line| line.contains("[\"User #quot;admin#quot;\"]"))
+            .unwrap();
+        assert!(user.starts_with("    n"), "{}", document);
+        assert!(document.contains("  subgraph b"), "{}", document);
+        assert!(document.contains("((\"Web\"))"), "{}", document);
+        assert!(document.contains("-->|\"Login (HTTPS)\"|"), "{}", document);
+        assert!(document.contains("  linkStyle 0 stroke:"), "{}", document);
+    }
+}
@@ -0,0 +1,7 @@
+pub mod dot;
+pub mod export_error;
+pub mod junit;
+pub mod mermaid;
+pub mod otm;
+pub mod plantuml;
+pub mod sarif;
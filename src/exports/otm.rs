@@ -0,0 +1,435 @@
+use std::collections::{HashMap, HashSet};
+
+use serde_json::{json, Value};
+
+use crate::{
+    input::{
+        input_diagram::{InputDiagram, Node, TypeNode},
+        threat::{Severity, Status, Threat},
+    },
+    otm::otm::{
+        Otm, OtmAsset, OtmAttributes, OtmComponent, OtmComponentAssets, OtmDataflow, OtmMitigation,
+        OtmMitigationInstance, OtmParent, OtmProject, OtmRepresentation, OtmRepresentationElement,
+        OtmThreat, OtmThreatInstance, OtmThreatRisk, OtmTrustZone, DEFAULT_TRUST_ZONE, OTM_VERSION,
+    },
+    project::project::Project,
+};
+
+use super::export_error::ExportError;
+
+/// Map the project to OTM. Each diagram file is a representation, its nodes
+/// are components or dataflows, and each threat of `threats.yaml` is a threat
+/// with its mitigation. Child diagrams are kept in the project attributes.
+/// A flow whose source or destination is not a node of its diagram is an
+/// error, OTM dataflows requiring both ends.
+pub fn to_otm(project: &Project) -> Result<Otm, ExportError> {
+    let config = &project.config;
+    let mut used_ids = HashSet::from([DEFAULT_TRUST_ZONE.to_string()]);
+
+    let mut project_attributes = OtmAttributes::new();
+    project_attributes.insert(
+        "threatDragonVersion".to_string(),
+        json!(config.threat_dragon_version),
+    );
+    if !config.diagrams.is_empty() {
+        project_attributes.insert("diagrams".to_string(), json!(config.diagrams));
+    }
+
+    let assets: Vec<OtmAsset> = config
+        .assets
+        .iter()
+        .map(|asset| OtmAsset {
+            name: asset.name.clone(),
+            id: unique_id(&asset.name, &mut used_ids),
+            description: Some(asset.description.clone()),
+        })
+        .collect();
+    let asset_ids: HashMap<&String, String> = config
+        .assets
+        .iter()
+        .zip(assets.iter())
+        .map(|(asset, otm_asset)| (&asset.name, otm_asset.id.clone()))
+        .collect();
+
+    // Trust zones of the configuration, then the ones only used by nodes.
+    let mut trust_zones: Vec<OtmTrustZone> = config
+        .trust_boundaries
+        .iter()
+        .map(|boundary| {
+            let mut attributes = OtmAttributes::new();
+            attributes.insert("limitOfAccess".to_string(), json!(boundary.limit_of_access));
+            attributes.insert(
+                "levelOfAuthorization".to_string(),
+                json!(boundary.level_of_authorization),
+            );
+            OtmTrustZone {
+                id: unique_id(&boundary.name, &mut used_ids),
+                name: boundary.name.clone(),
+                description: Some(boundary.description.clone()),
+                parent: None,
+                attributes,
+            }
+        })
+        .collect();
+    project
+        .diagrams
+        .iter()
+        .flat_map(|diagram| diagram.nodes.iter())
+        .filter(|node| node.type_node != TypeNode::Flow)
+        .for_each(|node| {
+            let name = node.trust_boundary.as_deref();
+            if !trust_zones.iter().any(|zone| {
+                Some(zone.name.as_str()) == name
+                    || (name.is_none() && zone.id == DEFAULT_TRUST_ZONE)
+            }) {
+                trust_zones.push(OtmTrustZone {
+                    id: match name {
+                        Some(name) => unique_id(name, &mut used_ids),
+                        None => DEFAULT_TRUST_ZONE.to_string(),
+                    },
+                    name: name.unwrap_or("Default").to_string(),
+                    description: None,
+                    parent: None,
+                    attributes: OtmAttributes::new(),
+                });
+            }
+        });
+
+    // Threats of the project, the last threat with a title wins as in the
+    // reports.
+    let mut unique_threats: Vec<&Threat> = Vec::new();
+    project.threats.iter().for_each(|threat| {
+        match unique_threats
+            .iter()
+            .position(|known| known.title == threat.title)
+        {
+            Some(index) => unique_threats[index] = threat,
+            None => unique_threats.push(threat),
+        }
+    });
+    let mut threats = Vec::new();
+    let mut mitigations = Vec::new();
+    let mut threat_ids: HashMap<&String, (String, Option<String>)> = HashMap::new();
+    for threat in unique_threats {
+        let id = unique_id(&threat.title, &mut used_ids);
+        let mitigation_id = if threat.mitigation.trim().is_empty() {
+            None
+        } else {
+            let mitigation_id = unique_id(&format!("{} mitigation", threat.title), &mut used_ids);
+            mitigations.push(OtmMitigation {
+                name: first_line(&threat.mitigation),
+                id: mitigation_id.clone(),
+                description: Some(threat.mitigation.clone()),
+                risk_reduction: 100.,
+            });
+            Some(mitigation_id)
+        };
+        threats.push(to_otm_threat(threat, &id));
+        threat_ids.insert(&threat.title, (id, mitigation_id));
+    }
+    let threat_instances = |node: &Node| -> Vec<OtmThreatInstance> {
+        node.threat_titles()
+            .filter_map(|title| {
+                let (threat_id, mitigation_id) = threat_ids.get(title)?;
+                let threat = project
+                    .threats
+                    .iter()
+                    .rfind(|threat| threat.title == *title)?;
+                Some(OtmThreatInstance {
+                    threat: threat_id.clone(),
+                    state: threat.status.to_string(),
+                    mitigations: mitigation_id
+                        .iter()
+                        .map(|mitigation_id| OtmMitigationInstance {
+                            mitigation: mitigation_id.clone(),
+                            state: match threat.status {
+                                Status::Mitigated => "implemented".to_string(),
+                                _ => "required".to_string(),
+                            },
+                        })
+                        .collect(),
+                })
+            })
+            .collect()
+    };
+
+    let mut representations = Vec::new();
+    let mut components = Vec::new();
+    let mut dataflows = Vec::new();
+    for diagram in project.diagrams.iter() {
+        let representation_id = unique_id(&diagram.title, &mut used_ids);
+        representations.push(to_otm_representation(diagram, &representation_id));
+
+        let component_ids: HashMap<&String, String> = diagram
+            .nodes
+            .iter()
+            .filter(|node| node.type_node != TypeNode::Flow)
+            .map(|node| {
+                (
+                    &node.name,
+                    unique_id(&format!("{} {}", diagram.title, node.name), &mut used_ids),
+                )
+            })
+            .collect();
+
+        for node in diagram.nodes.iter() {
+            let asset_ids: Vec<String> = node
+                .asset
+                .iter()
+                .filter_map(|asset| asset_ids.get(asset).cloned())
+                .collect();
+            if node.type_node == TypeNode::Flow {
+                let endpoint = |name: &Option<String>, end: &str| match name {
+                    Some(name) => component_ids.get(name).cloned().ok_or_else(|| {
+                        ExportError::Model(format!(
+                            "{}: {} {} of flow {} is not a node of the diagram",
+                            diagram.title, end, name, node.name
+                        ))
+                    }),
+                    None => Err(ExportError::Model(format!(
+                        "{}: flow {} has no {}",
+                        diagram.title, node.name, end
+                    ))),
+                };
+                let source = endpoint(&node.source, "source")?;
+                let destination = endpoint(&node.destination, "destination")?;
+                dataflows.push(OtmDataflow {
+                    id: unique_id(&format!("{} {}", diagram.title, node.name), &mut used_ids),
+                    name: node.name.clone(),
+                    description: Some(node.description.clone()),
+                    bidirectional: false,
+                    source,
+                    destination,
+                    assets: asset_ids,
+                    threats: threat_instances(node),
+                    attributes: node_attributes(node),
+                });
+                continue;
+            }
+
+            let trust_zone = match &node.trust_boundary {
+                Some(name) => trust_zones
+                    .iter()
+                    .find(|zone| zone.name == *name)
+                    .map(|zone| zone.id.clone()),
+                None => Some(DEFAULT_TRUST_ZONE.to_string()),
+            };
+            let component_id = component_ids[&node.name].clone();
+            components.push(OtmComponent {
+                representations: vec![OtmRepresentationElement {
+                    representation: representation_id.clone(),
+                    id: format!("{}-representation", component_id),
+                }],
+                id: component_id,
+                name: node.name.clone(),
+                type_field: node.type_node.to_string(),
+                description: Some(node.description.clone()),
+                parent: OtmParent {
+                    trust_zone,
+                    component: None,
+                },
+                assets: (!asset_ids.is_empty()).then(|| match node.type_node {
+                    TypeNode::Store => OtmComponentAssets {
+                        stored: asset_ids.clone(),
+                        ..Default::default()
+                    },
+                    _ => OtmComponentAssets {
+                        processed: asset_ids.clone(),
+                        ..Default::default()
+                    },
+                }),
+                threats: threat_instances(node),
+                attributes: node_attributes(node),
+            });
+        }
+    }
+
+    Ok(Otm {
+        otm_version: OTM_VERSION.to_string(),
+        project: OtmProject {
+            name: config.title.clone(),
+            id: slug(&config.title),
+            description: Some(config.description.clone()),
+            owner: Some(config.owner.clone()),
+            attributes: project_attributes,
+        },
+        representations,
+        assets,
+        trust_zones,
+        components,
+        dataflows,
+        threats,
+        mitigations,
+    })
+}
+
+fn to_otm_representation(diagram: &InputDiagram, id: &str) -> OtmRepresentation {
+    let mut attributes = OtmAttributes::new();
+    attributes.insert("methodology".to_string(), json!(diagram.methodology()));
+    OtmRepresentation {
+        name: diagram.title.clone(),
+        id: id.to_string(),
+        type_field: "diagram".to_string(),
+        description: Some(diagram.description.clone()),
+        attributes,
+    }
+}
+
+fn to_otm_threat(threat: &Threat, id: &str) -> OtmThreat {
+    // OTM rates threats from 0 to 100, the severity gives both values.
+    let score = match threat.severity {
+        Severity::Low => 25.,
+        Severity::Medium => 50.,
+        Severity::High => 75.,
+    };
+    let mut attributes = OtmAttributes::new();
+    attributes.insert("severity".to_string(), json!(threat.severity));
+    attributes.insert(
+        "methodology".to_string(),
+        json!(threat.type_field.methodology()),
+    );
+    if !threat.vector.is_empty() {
+        attributes.insert("vector".to_string(), json!(threat.vector));
+    }
+    let references = threat.references.clone().unwrap_or_default();
+    if !references.capec.is_empty() {
+        attributes.insert("capec".to_string(), json!(references.capec));
+    }
+    if !references.asvs.is_empty() {
+        attributes.insert("asvs".to_string(), json!(references.asvs));
+    }
+    if !references.urls.is_empty() {
+        attributes.insert("urls".to_string(), json!(references.urls));
+    }
+    OtmThreat {
+        name: threat.title.clone(),
+        id: id.to_string(),
+        description: Some(threat.description.clone()),
+        categories: vec![threat.type_field.to_string()],
+        cwes: references
+            .cwe
+            .iter()
+            .map(|cwe| format!("CWE-{}", cwe))
+            .collect(),
+        risk: Some(OtmThreatRisk {
+            likelihood: score,
+            impact: score,
+        }),
+        attributes,
+    }
+}
+
+fn node_attributes(node: &Node) -> OtmAttributes {
+    let mut attributes = OtmAttributes::new();
+    let mut insert = |key: &str, value: Option<Value>| {
+        if let Some(value) = value {
+            attributes.insert(key.to_string(), value);
+        }
+    };
+    insert("outOfScope", node.out_of_scope.map(|value| json!(value)));
+    insert(
+        "trustLevel",
+        node.trust_level.as_ref().map(|value| json!(value)),
+    );
+    insert("protocol", node.protocol.as_ref().map(|value| json!(value)));
+    insert("isEncrypted", node.is_encrypted.map(|value| json!(value)));
+    insert(
+        "isPublicNetwork",
+        node.is_public_network.map(|value| json!(value)),
+    );
+    attributes
+}
+
+fn first_line(value: &str) -> String {
+    value.lines().next().unwrap_or_default().trim().to_string()
+}
+
+/// Lower case identifier made of letters, digits and dashes.
+fn slug(value: &str) -> String {
+    value
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn unique_id(value: &str, used_ids: &mut HashSet<String>) -> String {
+    let base = slug(value);
+    let mut id = base.clone();
+    let mut index = 2;
+    while !used_ids.insert(id.clone()) {
+        id = format!("{}-{}", base, index);
+        index += 1;
+    }
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::imports::otm::from_otm;
+
+    fn example() -> Project {
+        let folder = Path::new(env!("CARGO_MANIFEST_DIR")).join("exemples");
+        Project::load_folder(&folder, None).unwrap()
+    }
+
+    #[test]
+    fn example_round_trip() {
+        let project = example();
+        let imported = from_otm(&to_otm(&project).unwrap()).unwrap();
+
+        let titles = |project: &Project| -> Vec<String> {
+            project
+                .diagrams
+                .iter()
+                .map(|diagram| diagram.title.clone())
+                .collect()
+        };
+        assert_eq!(titles(&imported), titles(&project));
+        for (diagram, imported_diagram) in project.diagrams.iter().zip(imported.diagrams.iter()) {
+            let mut names: Vec<&String> = diagram.nodes.iter().map(|node| &node.name).collect();
+            let mut imported_names: Vec<&String> = imported_diagram
+                .nodes
+                .iter()
+                .map(|node| &node.name)
+                .collect();
+            names.sort();
+            imported_names.sort();
+            assert_eq!(imported_names, names, "{}", diagram.title);
+        }
+        let threats = project
+            .threats
+            .iter()
+            .map(|threat| &threat.title)
+            .collect::<HashSet<_>>();
+        let imported_threats = imported
+            .threats
+            .iter()
+            .map(|threat| &threat.title)
+            .collect::<HashSet<_>>();
+        assert_eq!(imported_threats, threats);
+    }
+
+    #[test]
+    fn flow_to_unknown_node_is_an_error() {
+        let mut project = example();
+        let flow = project.diagrams[0]
+            .nodes
+            .iter_mut()
+            .find(|node| node.type_node == TypeNode::Flow)
+            .unwrap();
+        flow.destination = Some("Nowhere".to_string());
+        let flow_name = flow.name.clone();
+
+        let error = to_otm(&project).unwrap_err().to_string();
+        assert!(
+            error.contains(&format!("destination Nowhere of flow {}", flow_name)),
+            "{}",
+            error
+        );
+    }
+}
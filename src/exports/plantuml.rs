@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+
+use crate::{
+    config::config::Config,
+    input::input_diagram::{InputDiagram, Node, TypeNode},
+    process::process::MappingFromInputNode,
+    reports::sections,
+    threat_dragon_modeling::diagram::Body,
+};
+
+pub fn render_plantuml(input_diagram: &InputDiagram, config: &Config) -> String {
+    let mut document = format!(
+        "@startuml\ntitle {}\nleft to right direction\n\n",
+        label(&input_diagram.title)
+    );
+
+    let mut identifiers: HashMap<&String, String> = HashMap::new();
+    for (boundary, nodes) in sections::nodes_by_trust_boundary(input_diagram).iter() {
+        let indent = match boundary {
+            Some(boundary) => {
+                document.push_str(&format!(
+                    "package \"{}\" #line.dashed {{\n",
+                    label(boundary)
+                ));
+                "  "
+            }
+            None => "",
+        };
+        for node in nodes {
+            let identifier = format!("n{}", identifiers.len());
+            document.push_str(&format!(
+                "{}{} \"{}\" as {} {}\n",
+                indent,
+                keyword(node),
+                label(&node.name),
+                identifier,
+                style(node, config)
+            ));
+            identifiers.insert(&node.name, identifier);
+        }
+        if boundary.is_some() {
+            document.push_str("}\n");
+        }
+    }
+
+    document.push('\n');
+    let flows = input_diagram
+        .nodes
+        .iter()
+        .filter(|node| node.type_node == TypeNode::Flow);
+    for flow in flows {
+        let source = flow.source.as_ref().and_then(|name| identifiers.get(name));
+        let destination = flow
+            .destination
+            .as_ref()
+            .and_then(|name| identifiers.get(name));
+        if let (Some(source), Some(destination)) = (source, destination) {
+            let body = Body::from_input_diagram(flow, config);
+            let mut arrow_style = vec![format!("#{}", color(&body.stroke))];
+            if body.stroke_dasharray.is_some() {
+                arrow_style.push("dashed".to_string());
+            }
+            let flow_label = match &flow.protocol {
+                Some(protocol) => format!("{}\\n{}", label(&flow.name), label(protocol)),
+                None => label(&flow.name),
+            };
+            document.push_str(&format!(
+                "{} -[{}]-> {} : {}\n",
+                source,
+                arrow_style.join(","),
+                destination,
+                flow_label
+            ));
+        }
+    }
+
+    document.push_str("@enduml\n");
+    document
+}
+
+fn keyword(node: &Node) -> &'static str {
+    match node.type_node {
+        TypeNode::Actor => "actor",
+        TypeNode::Process => "component",
+        TypeNode::Store => "database",
+        TypeNode::Flow => "component",
+    }
+}
+
+/// Stroke of the element, following the Threat Dragon rendering.
+fn style(node: &Node, config: &Config) -> String {
+    let body = Body::from_input_diagram(node, config);
+    let mut style = format!("#line:{}", color(&body.stroke));
+    if body.stroke_dasharray.is_some() {
+        style.push_str(";line.dashed");
+    }
+    style
+}
+
+/// PlantUML colors are named or hexadecimal without the leading `#`.
+fn color(stroke: &str) -> &str {
+    stroke.trim_start_matches('#')
+}
+
+fn label(value: &str) -> String {
+    value.replace('"', "'").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn title_is_escaped() {
+        let input_diagram: InputDiagram = serde_yaml::from_str(
+            r#"
+title: "Orders \"v2\"\n@enduml"
+description: ""
+nodes: []
+"#,
+        )
+        .unwrap();
+        let config: Config = serde_yaml::from_str(
+            r#"
+threatDragonVersion: "2.2.0"
+title: Orders
+owner: Platform
+description: ""
+trustBoundaries: []
+assets: []
+diagrams: []
+"#,
+        )
+        .unwrap();
+        let document = render_plantuml(&input_diagram, &config);
+        assert!(
+            document.starts_with("@startuml\ntitle Orders 'v2'\\n@enduml\nleft to right"),
+            "{}",
+            document
+        );
+    }
+}
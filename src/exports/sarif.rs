@@ -0,0 +1,318 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use serde_json::{json, Value};
+
+use crate::{
+    project::{include::normalize, project::Project},
+    validation::validator::{IssueLevel, ValidationIssue},
+};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// One run of the generator with a result per finding. Each result points to
+/// the line of its node in the diagram file, or in the file it is included
+/// from, or to the first line of the diagram file when the finding is about
+/// the whole diagram.
+pub fn to_sarif(project: &Project, issues: &[ValidationIssue]) -> Value {
+    let mut rule_ids: Vec<&str> = issues.iter().map(|issue| issue.rule.as_str()).collect();
+    rule_ids.sort();
+    rule_ids.dedup();
+    let rules: Vec<Value> = rule_ids
+        .iter()
+        .map(|rule| {
+            json!({
+                "id": rule,
+                "shortDescription": { "text": rule_description(rule) },
+            })
+        })
+        .collect();
+
+    let mut contents: HashMap<&Path, Option<String>> = HashMap::new();
+    let results: Vec<Value> = issues
+        .iter()
+        .map(|issue| {
+            let mut result = json!({
+                "ruleId": issue.rule,
+                "ruleIndex": rule_ids.iter().position(|rule| *rule == issue.rule),
+                "level": match issue.level {
+                    IssueLevel::Error => "error",
+                    IssueLevel::Warning => "warning",
+                },
+                "message": { "text": format!("{}: {}", issue.diagram, issue.message) },
+            });
+            let path = match &issue.node {
+                Some(node) => project.node_path(&issue.diagram, node),
+                None => project.diagram_path(&issue.diagram),
+            };
+            if let Some(path) = path {
+                let content = contents
+                    .entry(path)
+                    .or_insert_with(|| fs::read_to_string(path).ok());
+                let line = match (content, &issue.node) {
+                    (Some(content), Some(node)) => node_line(content, node),
+                    _ => 1,
+                };
+                result["locations"] = json!([{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": artifact_uri(path) },
+                        "region": { "startLine": line },
+                    }
+                }]);
+            }
+            result
+        })
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/dedal-lab/threat-dragon-generator",
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }]
+    })
+}
+
+fn rule_description(rule: &str) -> &'static str {
+    match rule {
+        "unknown-threat" => "Threat not defined in the threat list",
+        "methodology" => "Threat type outside of the diagram methodology",
+        "stride-coverage" => "Element without a threat for an applicable STRIDE category",
+        "open-high-threat" => "Open threat with a High severity",
+        "missing-mitigation" => "Threat closed without mitigation or reason",
+        "unencrypted-public-flow" => "Unencrypted flow through a public network",
+        "undeclared-trust-boundary" => "Trust boundary missing from the configuration",
+        "undeclared-asset" => "Asset missing from the configuration",
+        "unknown-endpoint" => "Flow whose source or destination is not a node of its diagram",
+        "diverging-component" => "Component differing from its other copies",
+        _ => "Threat model finding",
+    }
+}
+
+/// Line of the `name:` entry of the node, or of the `component:` reference
+/// of a component kept under its name, 1 when the node is not found.
+fn node_line(content: &str, node: &str) -> usize {
+    content
+        .lines()
+        .position(|line| {
+            let line = line.trim_start().trim_start_matches('-').trim_start();
+            line.strip_prefix("name:")
+                .or_else(|| line.strip_prefix("component:"))
+                .map(|value| {
+                    let value = value.split(" #").next().unwrap_or_default().trim();
+                    value.trim_matches(|c| c == '"' || c == '\'') == node
+                })
+                .unwrap_or(false)
+        })
+        .map(|index| index + 1)
+        .unwrap_or(1)
+}
+
+/// Relative paths are kept relative to the repository root, absolute ones
+/// become `file` URIs.
+fn artifact_uri(path: &Path) -> String {
+    let uri = normalize(path).to_string_lossy().replace('\\', "/");
+    if path.is_absolute() {
+        format!("file://{}", uri)
+    } else {
+        uri
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use uuid::Uuid;
+
+    use super::*;
+
+    const CONFIG: &str = r#"threatDragonVersion: "2.2.0"
+title: "Shop"
+owner: "Team"
+description: ""
+trustBoundaries: []
+assets: []
+diagrams: []
+"#;
+
+    const DIAGRAM: &str = r#"title: "Shop"
+description: ""
+nodes:
+  - name: Web
+    type: process
+    description: ""
+    threats: []
+  - include: ../shared/nodes.yaml
+"#;
+
+    const NODES: &str = r#"# Nodes shared by the diagrams
+- name: Database
+  type: store
+  description: ""
+  threats: []
+"#;
+
+    /// File and line of each result.
+    fn locations(sarif: &Value) -> Vec<(String, u64)> {
+        sarif["runs"][0]["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|result| {
+                let location = &result["locations"][0]["physicalLocation"];
+                (
+                    location["artifactLocation"]["uri"]
+                        .as_str()
+                        .unwrap()
+                        .to_string(),
+                    location["region"]["startLine"].as_u64().unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn results_point_to_the_file_of_their_node() {
+        let folder: PathBuf =
+            std::env::temp_dir().join(format!("threat-dragon-sarif-{}", Uuid::new_v4()));
+        for (name, content) in [
+            ("config.yaml", CONFIG),
+            ("threats.yaml", "[]\n"),
+            ("diagrams/shop.yaml", DIAGRAM),
+            ("shared/nodes.yaml", NODES),
+        ] {
+            let path = folder.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        let project = Project::load_folder(&folder, None).unwrap();
+        let sarif = to_sarif(&project, &project.findings());
+        fs::remove_dir_all(&folder).unwrap();
+
+        // Both nodes miss STRIDE categories.
+        let locations = locations(&sarif);
+        let uri = |name: &str| format!("file://{}", folder.join(name).display());
+        assert!(locations.contains(&(uri("diagrams/shop.yaml"), 4)));
+        assert!(locations.contains(&(uri("shared/nodes.yaml"), 2)));
+    }
+
+    fn issue(level: IssueLevel, rule: &str, node: Option<&str>, message: &str) -> ValidationIssue {
+        ValidationIssue {
+            level,
+            rule: rule.to_string(),
+            diagram: "Shop".to_string(),
+            node: node.map(str::to_string),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn rules_and_results_of_the_issues() {
+        let diagram = DIAGRAM.replace("  - include: ../shared/nodes.yaml\n", "");
+        let project = Project::parse(
+            (Path::new("config.yaml"), CONFIG),
+            (Path::new("threats.yaml"), "[]\n"),
+            &[(PathBuf::from("./diagrams/shop.yaml"), diagram)],
+            None,
+        )
+        .unwrap();
+        let issues = [
+            issue(
+                IssueLevel::Warning,
+                "stride-coverage",
+                Some("Web"),
+                "no threat for Spoofing",
+            ),
+            issue(
+                IssueLevel::Error,
+                "methodology",
+                Some("Web"),
+                "threat does not fit",
+            ),
+            issue(
+                IssueLevel::Warning,
+                "stride-coverage",
+                None,
+                "no threat for Tampering",
+            ),
+        ];
+        let sarif = to_sarif(&project, &issues);
+        let run = &sarif["runs"][0];
+
+        assert_eq!(sarif["version"], "2.1.0");
+        assert_eq!(
+            run["tool"]["driver"]["rules"],
+            json!([
+                {
+                    "id": "methodology",
+                    "shortDescription": { "text": "Threat type outside of the diagram methodology" },
+                },
+                {
+                    "id": "stride-coverage",
+                    "shortDescription": { "text": "Element without a threat for an applicable STRIDE category" },
+                },
+            ])
+        );
+        let results = run["results"].as_array().unwrap();
+        let summary: Vec<(&Value, &Value, &Value, &Value)> = results
+            .iter()
+            .map(|result| {
+                (
+                    &result["ruleId"],
+                    &result["ruleIndex"],
+                    &result["level"],
+                    &result["message"]["text"],
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    &json!("stride-coverage"),
+                    &json!(1),
+                    &json!("warning"),
+                    &json!("Shop: no threat for Spoofing")
+                ),
+                (
+                    &json!("methodology"),
+                    &json!(0),
+                    &json!("error"),
+                    &json!("Shop: threat does not fit")
+                ),
+                (
+                    &json!("stride-coverage"),
+                    &json!(1),
+                    &json!("warning"),
+                    &json!("Shop: no threat for Tampering")
+                ),
+            ]
+        );
+        // Relative paths stay relative to the repository root.
+        assert!(locations(&sarif)
+            .iter()
+            .all(|(uri, _)| uri == "diagrams/shop.yaml"));
+    }
+
+    #[test]
+    fn lines_of_the_nodes() {
+        let content = r#"nodes:
+  - name: Web # Front
+    type: process
+  - component: "Broker"
+  - name: 'Database'
+"#;
+        assert_eq!(node_line(content, "Web"), 2);
+        assert_eq!(node_line(content, "Broker"), 4);
+        assert_eq!(node_line(content, "Database"), 5);
+        assert_eq!(node_line(content, "Unknown"), 1);
+    }
+}
@@ -0,0 +1,349 @@
+use std::collections::HashMap;
+
+use crate::{
+    diff::{
+        diff_reports::kind_label,
+        project_diff::{self, Change, ChangeKind, ThreatChange},
+    },
+    input::threat::{Severity, Status},
+    project::{project::Project, revision::Commit},
+    reports::sections::Table,
+};
+
+const SECONDS_PER_DAY: f64 = 86400.;
+
+/// Change of an element in a commit of the history.
+#[derive(Debug, Clone)]
+pub struct HistoryEvent {
+    pub commit: Commit,
+    pub element_type: &'static str,
+    pub element: String,
+    pub kind: ChangeKind,
+    pub detail: String,
+}
+
+/// Threat moved from Open to Mitigated.
+#[derive(Debug, Clone)]
+pub struct Mitigation {
+    pub title: String,
+    pub severity: Severity,
+    /// Dates of the commits opening and mitigating the threat.
+    pub opened: String,
+    pub mitigated: String,
+    pub days: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    /// Events in the order of the commits.
+    pub events: Vec<HistoryEvent>,
+    pub mitigations: Vec<Mitigation>,
+    /// Threats still Open at the last commit, with their severity.
+    pub open: Vec<(String, Severity)>,
+}
+
+/// Rebuild the timeline of the threats and nodes from the versions of the
+/// project, oldest first. The first version is compared to an empty project.
+pub fn build(versions: &[(Commit, Project)]) -> History {
+    let mut history = History::default();
+    let Some((_, first)) = versions.first() else {
+        return history;
+    };
+    let mut empty = first.clone();
+    empty.threats.clear();
+    empty.diagrams.clear();
+    empty.config.trust_boundaries.clear();
+    empty.config.assets.clear();
+
+    let mut opened: HashMap<String, &Commit> = HashMap::new();
+    let mut severities: HashMap<String, Severity> = HashMap::new();
+    let mut previous = &empty;
+    for (commit, project) in versions {
+        let diff = project_diff::diff(previous, project);
+        previous = project;
+
+        for (element_type, changes) in [
+            ("Node", &diff.nodes),
+            ("Flow", &diff.flows),
+            ("Trust boundary", &diff.trust_boundaries),
+            ("Asset", &diff.assets),
+        ] {
+            history.events.extend(
+                changes
+                    .iter()
+                    .map(|change| element_event(commit, element_type, change)),
+            );
+        }
+
+        for change in diff.threats.iter() {
+            if let Some(severity) = &change.new_severity {
+                severities.insert(change.title.clone(), severity.clone());
+            }
+            match (&change.kind, &change.new_status) {
+                (ChangeKind::Removed, _) => {
+                    opened.remove(&change.title);
+                }
+                (_, Some(Status::Open)) => {
+                    opened.insert(change.title.clone(), commit);
+                }
+                (_, Some(Status::Mitigated)) => {
+                    if let (Some(opened_at), Some(severity)) =
+                        (opened.remove(&change.title), severities.get(&change.title))
+                    {
+                        history.mitigations.push(Mitigation {
+                            title: change.title.clone(),
+                            severity: severity.clone(),
+                            opened: opened_at.date.clone(),
+                            mitigated: commit.date.clone(),
+                            days: (commit.timestamp - opened_at.timestamp) as f64 / SECONDS_PER_DAY,
+                        });
+                    }
+                }
+                (_, Some(Status::NotApplicable)) => {
+                    opened.remove(&change.title);
+                }
+                (_, None) => {}
+            }
+            history.events.push(threat_event(commit, change));
+        }
+    }
+
+    let mut open: Vec<(String, Severity)> = opened
+        .into_keys()
+        .filter_map(|title| {
+            let severity = severities.get(&title).cloned()?;
+            Some((title, severity))
+        })
+        .collect();
+    open.sort_by(|a, b| a.0.cmp(&b.0));
+    history.open = open;
+    history
+}
+
+fn element_event(commit: &Commit, element_type: &'static str, change: &Change) -> HistoryEvent {
+    HistoryEvent {
+        commit: commit.clone(),
+        element_type,
+        element: match &change.diagram {
+            Some(diagram) => format!("{} > {}", diagram, change.name),
+            None => change.name.clone(),
+        },
+        kind: change.kind,
+        detail: change.fields.join(", "),
+    }
+}
+
+fn threat_event(commit: &Commit, change: &ThreatChange) -> HistoryEvent {
+    let detail = match change.kind {
+        ChangeKind::Changed => {
+            let mut moves = Vec::new();
+            if let (Some(old), Some(new)) = (&change.old_status, &change.new_status) {
+                moves.push(format!("status {} -> {}", old, new));
+            }
+            if let (Some(old), Some(new)) = (&change.old_severity, &change.new_severity) {
+                moves.push(format!("severity {} -> {}", old, new));
+            }
+            moves.join(", ")
+        }
+        ChangeKind::Added => format!(
+            "{}, {}",
+            change
+                .new_status
+                .as_ref()
+                .map(Status::to_string)
+                .unwrap_or_default(),
+            change
+                .new_severity
+                .as_ref()
+                .map(Severity::to_string)
+                .unwrap_or_default()
+        ),
+        ChangeKind::Removed => String::new(),
+    };
+    HistoryEvent {
+        commit: commit.clone(),
+        element_type: "Threat",
+        element: change.title.clone(),
+        kind: change.kind,
+        detail,
+    }
+}
+
+/// Every event of the history, oldest first.
+pub fn history_table(history: &History) -> Table {
+    let mut table = Table::new(vec![
+        "Date",
+        "Commit",
+        "Author",
+        "Element type",
+        "Element",
+        "Change",
+        "Detail",
+    ]);
+    history.events.iter().for_each(|event| {
+        table.rows.push(vec![
+            event.commit.date.clone(),
+            short_hash(&event.commit.hash).to_string(),
+            event.commit.author.clone(),
+            event.element_type.to_string(),
+            event.element.clone(),
+            kind_label(event.kind).to_string(),
+            event.detail.clone(),
+        ])
+    });
+    table
+}
+
+/// Threats moved from Open to Mitigated, in the order of the mitigations.
+pub fn mitigations_table(history: &History) -> Table {
+    let mut table = Table::new(vec!["Threat", "Severity", "Opened", "Mitigated", "Days"]);
+    history.mitigations.iter().for_each(|mitigation| {
+        table.rows.push(vec![
+            mitigation.title.clone(),
+            mitigation.severity.to_string(),
+            mitigation.opened.clone(),
+            mitigation.mitigated.clone(),
+            format!("{:.1}", mitigation.days),
+        ])
+    });
+    table
+}
+
+/// Days from Open to Mitigated for each severity, the threats mitigated
+/// without having been Open in the history are not counted.
+pub fn time_to_mitigate_table(history: &History) -> Table {
+    let mut table = Table::new(vec![
+        "Severity",
+        "Mitigated",
+        "Mean days",
+        "Median days",
+        "Max days",
+        "Still open",
+    ]);
+    for severity in [Severity::High, Severity::Medium, Severity::Low] {
+        let mut days: Vec<f64> = history
+            .mitigations
+            .iter()
+            .filter(|mitigation| mitigation.severity == severity)
+            .map(|mitigation| mitigation.days)
+            .collect();
+        days.sort_by(|a, b| a.total_cmp(b));
+        let still_open = history
+            .open
+            .iter()
+            .filter(|(_, open_severity)| *open_severity == severity)
+            .count();
+        let (mean, median, max) = match days.len() {
+            0 => (String::new(), String::new(), String::new()),
+            count => (
+                format!("{:.1}", days.iter().sum::<f64>() / count as f64),
+                format!(
+                    "{:.1}",
+                    if count % 2 == 0 {
+                        (days[count / 2 - 1] + days[count / 2]) / 2.
+                    } else {
+                        days[count / 2]
+                    }
+                ),
+                format!("{:.1}", days[count - 1]),
+            ),
+        };
+        table.rows.push(vec![
+            severity.to_string(),
+            days.len().to_string(),
+            mean,
+            median,
+            max,
+            still_open.to_string(),
+        ]);
+    }
+    table
+}
+
+pub fn short_hash(hash: &str) -> &str {
+    &hash[..hash.len().min(8)]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    fn example() -> Project {
+        let folder = Path::new(env!("CARGO_MANIFEST_DIR")).join("exemples");
+        Project::load_folder(&folder, None).unwrap()
+    }
+
+    fn commit(hash: &str, day: i64) -> Commit {
+        Commit {
+            hash: hash.to_string(),
+            timestamp: day * SECONDS_PER_DAY as i64,
+            date: format!("1970-01-{:02}", day + 1),
+            author: "Author".to_string(),
+            subject: format!("Commit {}", hash),
+        }
+    }
+
+    fn versions() -> Vec<(Commit, Project)> {
+        let first = example();
+        let mut second = example();
+        second.threats[0].status = Status::Mitigated;
+        second.diagrams[0].nodes.retain(|node| node.name != "Kafka");
+        vec![(commit("a", 0), first), (commit("b", 3), second)]
+    }
+
+    #[test]
+    fn first_version_adds_everything() {
+        let versions = versions();
+        let history = build(&versions[..1]);
+        let added = |element_type: &str| {
+            history
+                .events
+                .iter()
+                .filter(|event| event.element_type == element_type)
+                .all(|event| event.kind == ChangeKind::Added)
+        };
+        assert!(["Node", "Flow", "Trust boundary", "Threat"]
+            .into_iter()
+            .all(added));
+        assert!(history.mitigations.is_empty());
+    }
+
+    #[test]
+    fn mitigations_are_timed_from_the_opening_commit() {
+        let versions = versions();
+        let title = versions[0].1.threats[0].title.clone();
+        let history = build(&versions);
+
+        let events: Vec<(&str, &str, ChangeKind)> = history
+            .events
+            .iter()
+            .filter(|event| event.commit.hash == "b")
+            .map(|event| (event.element_type, event.element.as_str(), event.kind))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                ("Node", "A1_Diagram1 > Kafka", ChangeKind::Removed),
+                ("Threat", title.as_str(), ChangeKind::Changed)
+            ]
+        );
+
+        assert_eq!(history.mitigations.len(), 1);
+        let mitigation = &history.mitigations[0];
+        assert_eq!(mitigation.title, title);
+        assert_eq!(mitigation.opened, "1970-01-01");
+        assert_eq!(mitigation.mitigated, "1970-01-04");
+        assert_eq!(mitigation.days, 3.);
+        assert!(history.open.iter().all(|(open, _)| *open != title));
+
+        let table = time_to_mitigate_table(&history);
+        let row = table
+            .rows
+            .iter()
+            .find(|row| row[0] == mitigation.severity.to_string())
+            .unwrap();
+        assert_eq!(row[1..5], ["1", "3.0", "3.0", "3.0"]);
+    }
+}
@@ -0,0 +1,499 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::Read,
+    path::Path,
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use flate2::read::DeflateDecoder;
+use roxmltree::{Document, Node as XmlNode};
+
+use crate::{
+    config::config::{Config, TrustBoundary},
+    input::input_diagram::{InputDiagram, Node, TypeNode},
+    project::project::Project,
+};
+
+use super::{import_error::ImportError, mapping};
+
+/// Node type of the shapes whose style contains a pattern, such as
+/// `mxgraph.aws4.rds: store`. The longest matching pattern wins, the type of
+/// the other shapes is inferred from their style.
+pub type StyleMapping = Vec<(String, TypeNode)>;
+
+/// Read a draw.io file, with compressed or uncompressed diagrams, and the
+/// optional YAML mapping table of the shape styles.
+pub fn import(path: &Path, mapping_path: Option<&Path>) -> Result<Project, ImportError> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| ImportError::Read(format!("{}: {}", path.display(), e)))?;
+    let style_mapping = match mapping_path {
+        Some(mapping_path) => {
+            let mapping_content = fs::read_to_string(mapping_path)
+                .map_err(|e| ImportError::Read(format!("{}: {}", mapping_path.display(), e)))?;
+            let table: HashMap<String, TypeNode> = serde_yaml::from_str(&mapping_content)
+                .map_err(|e| ImportError::Parse(format!("{}: {}", mapping_path.display(), e)))?;
+            table.into_iter().collect()
+        }
+        None => Vec::new(),
+    };
+    let title = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    from_drawio(&title, &content, style_mapping).map_err(|e| match e {
+        ImportError::Parse(e) => ImportError::Parse(format!("{}: {}", path.display(), e)),
+        e => e,
+    })
+}
+
+/// Map the pages of a draw.io file to diagrams. Shapes become nodes, edges
+/// with both ends on a node become flows and container shapes become the
+/// trust boundary of their children. The project has no threat yet.
+pub fn from_drawio(
+    title: &str,
+    content: &str,
+    mut style_mapping: StyleMapping,
+) -> Result<Project, ImportError> {
+    style_mapping.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then(a.cmp(b)));
+    let document = Document::parse(content).map_err(|e| ImportError::Parse(format!("{}", e)))?;
+    let root = document.root_element();
+
+    // Pages of a file, or the graph model of a file exported without pages.
+    let pages: Vec<(String, String)> = match root.tag_name().name() {
+        "mxfile" => root
+            .children()
+            .filter(|child| child.has_tag_name("diagram"))
+            .enumerate()
+            .map(|(index, page)| {
+                let name = page
+                    .attribute("name")
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("Page-{}", index + 1));
+                Ok((name, page_model(page)?))
+            })
+            .collect::<Result<_, ImportError>>()?,
+        "mxGraphModel" => vec![(title.to_string(), content.to_string())],
+        name => {
+            return Err(ImportError::Model(format!(
+                "unexpected root element {}",
+                name
+            )))
+        }
+    };
+
+    let mut trust_boundaries: Vec<TrustBoundary> = Vec::new();
+    let mut diagrams = Vec::new();
+    for (page_name, model) in pages.iter() {
+        let model_document =
+            Document::parse(model).map_err(|e| ImportError::Parse(format!("{}", e)))?;
+        let cells = cells(model_document.root_element());
+        let cell = |id: &str| cells.iter().find(|cell| cell.id == id);
+
+        let is_container = |candidate: &Cell| {
+            candidate.vertex
+                && (candidate.style.contains("container=1")
+                    || candidate.style.contains("swimlane")
+                    || cells
+                        .iter()
+                        .any(|child| child.vertex && child.parent == candidate.id))
+        };
+        // Closest labelled container around a cell, a cyclic chain of
+        // parents stops at the first cell seen twice.
+        let boundary = |start: &Cell| {
+            let mut visited = HashSet::from([start.id.as_str()]);
+            let mut current = cell(&start.parent);
+            while let Some(parent) = current.filter(|parent| visited.insert(&parent.id)) {
+                if is_container(parent) && !parent.label.is_empty() {
+                    return Some(parent.label.clone());
+                }
+                current = cell(&parent.parent);
+            }
+            None
+        };
+
+        let mut nodes: Vec<Node> = Vec::new();
+        let mut names: HashMap<&str, String> = HashMap::new();
+        for shape in cells.iter().filter(|cell| cell.vertex) {
+            // Labels of edges are vertices attached to the edge.
+            if cell(&shape.parent).is_some_and(|parent| parent.edge) {
+                continue;
+            }
+            if is_container(shape) {
+                if !shape.label.is_empty()
+                    && !trust_boundaries
+                        .iter()
+                        .any(|known| known.name == shape.label)
+                {
+                    trust_boundaries.push(TrustBoundary {
+                        name: shape.label.clone(),
+                        description: shape.tooltip.clone().unwrap_or_default(),
+                        limit_of_access: String::new(),
+                        level_of_authorization: String::new(),
+                    });
+                }
+                continue;
+            }
+            if shape.label.is_empty() {
+                continue;
+            }
+            let name = mapping::unique_name(&nodes, shape.label.clone());
+            names.insert(&shape.id, name.clone());
+            nodes.push(Node {
+                type_node: node_type(&shape.style, &style_mapping),
+                description: shape.tooltip.clone().unwrap_or_default(),
+                out_of_scope: None,
+                trust_boundary: boundary(shape),
+                trust_level: None,
+                source: None,
+                destination: None,
+                asset: None,
+                protocol: None,
+                is_encrypted: None,
+                is_public_network: None,
+                threats: Vec::new(),
+                name,
+            });
+        }
+
+        for edge in cells.iter().filter(|cell| cell.edge) {
+            let end = |id: &Option<String>| id.as_deref().and_then(|id| names.get(id)).cloned();
+            let (Some(source), Some(destination)) = (end(&edge.source), end(&edge.target)) else {
+                continue;
+            };
+            let label = if edge.label.is_empty() {
+                cells
+                    .iter()
+                    .find(|child| {
+                        child.vertex && child.parent == edge.id && !child.label.is_empty()
+                    })
+                    .map(|child| child.label.clone())
+                    .unwrap_or_else(|| format!("{} to {}", source, destination))
+            } else {
+                edge.label.clone()
+            };
+            let name = mapping::unique_name(&nodes, label);
+            nodes.push(Node {
+                name,
+                type_node: TypeNode::Flow,
+                description: edge.tooltip.clone().unwrap_or_default(),
+                out_of_scope: None,
+                trust_boundary: None,
+                trust_level: None,
+                source: Some(source),
+                destination: Some(destination),
+                asset: None,
+                protocol: None,
+                is_encrypted: None,
+                is_public_network: None,
+                threats: Vec::new(),
+            });
+        }
+
+        // Diagrams are saved under their title, pages of the same name get
+        // a suffix so that their files stay apart.
+        let diagram_title = if pages.len() == 1 {
+            title.to_string()
+        } else {
+            let mut diagram_title = page_name.clone();
+            let mut suffix = 2;
+            while diagrams
+                .iter()
+                .any(|diagram: &InputDiagram| diagram.title == diagram_title)
+            {
+                diagram_title = format!("{} ({})", page_name, suffix);
+                suffix += 1;
+            }
+            diagram_title
+        };
+        diagrams.push(InputDiagram {
+            title: diagram_title,
+            description: String::new(),
+            methodology: None,
+            nodes,
+        });
+    }
+
+    Ok(Project {
+        config: Config {
+            threat_dragon_version: mapping::DEFAULT_THREAT_DRAGON_VERSION.to_string(),
+            title: title.to_string(),
+            owner: String::new(),
+            description: String::new(),
+            trust_boundaries,
+            assets: Vec::new(),
+            diagrams: Vec::new(),
+            components: Vec::new(),
+        },
+        threats: Vec::new(),
+        diagrams,
+        diagram_paths: HashMap::new(),
+        node_paths: HashMap::new(),
+        component_overrides: HashMap::new(),
+        included_paths: Vec::new(),
+    })
+}
+
+/// Cell of a graph model, with the attributes of its `UserObject` or `object`
+/// wrapper.
+struct Cell {
+    id: String,
+    label: String,
+    tooltip: Option<String>,
+    style: String,
+    parent: String,
+    vertex: bool,
+    edge: bool,
+    source: Option<String>,
+    target: Option<String>,
+}
+
+fn cells(model: XmlNode) -> Vec<Cell> {
+    model
+        .descendants()
+        .filter(|node| node.has_tag_name("mxCell"))
+        .map(|cell| {
+            let wrapper = cell
+                .parent_element()
+                .filter(|parent| matches!(parent.tag_name().name(), "UserObject" | "object"));
+            let attribute = |name: &str| cell.attribute(name).map(str::to_string);
+            Cell {
+                id: wrapper
+                    .and_then(|wrapper| wrapper.attribute("id"))
+                    .or_else(|| cell.attribute("id"))
+                    .unwrap_or_default()
+                    .to_string(),
+                label: plain_text(
+                    wrapper
+                        .and_then(|wrapper| wrapper.attribute("label"))
+                        .or_else(|| cell.attribute("value"))
+                        .unwrap_or_default(),
+                ),
+                tooltip: wrapper
+                    .and_then(|wrapper| wrapper.attribute("tooltip"))
+                    .map(plain_text),
+                style: attribute("style").unwrap_or_default().to_lowercase(),
+                parent: attribute("parent").unwrap_or_default(),
+                vertex: cell.attribute("vertex") == Some("1"),
+                edge: cell.attribute("edge") == Some("1"),
+                source: attribute("source"),
+                target: attribute("target"),
+            }
+        })
+        .collect()
+}
+
+/// Graph model of a page, stored as XML or compressed: base64 of the raw
+/// deflate of the URL encoded XML.
+fn page_model(page: XmlNode) -> Result<String, ImportError> {
+    if let Some(model) = page
+        .children()
+        .find(|child| child.has_tag_name("mxGraphModel"))
+    {
+        let range = model.range();
+        return Ok(page.document().input_text()[range].to_string());
+    }
+    let text: String = page
+        .text()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    let compressed = STANDARD
+        .decode(text)
+        .map_err(|e| ImportError::Parse(format!("invalid compressed diagram: {}", e)))?;
+    let mut encoded = String::new();
+    DeflateDecoder::new(compressed.as_slice())
+        .read_to_string(&mut encoded)
+        .map_err(|e| ImportError::Parse(format!("invalid compressed diagram: {}", e)))?;
+    Ok(percent_decode(&encoded))
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = bytes
+            .get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[index], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Label without the HTML markup of draw.io rich text.
+fn plain_text(value: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in value.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+                text.push(' ');
+            }
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn node_type(style: &str, style_mapping: &StyleMapping) -> TypeNode {
+    style_mapping
+        .iter()
+        .find(|(pattern, _)| style.contains(&pattern.to_lowercase()))
+        .map(|(_, type_node)| type_node.clone())
+        .unwrap_or_else(|| mapping::node_type(style))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{write::DeflateEncoder, Compression};
+
+    use super::*;
+
+    const MODEL: &str = r#"<mxGraphModel><root>
+  <mxCell id="0"/>
+  <mxCell id="1" parent="0"/>
+  <mxCell id="dmz" value="DMZ" style="swimlane;container=1" vertex="1" parent="1"/>
+  <UserObject id="user" label="&lt;b&gt;User&lt;/b&gt;" tooltip="Customer">
+    <mxCell style="shape=umlActor" vertex="1" parent="1"/>
+  </UserObject>
+  <mxCell id="web" value="Web" style="rounded=1" vertex="1" parent="dmz"/>
+  <mxCell id="db" value="Orders" style="shape=mxgraph.aws4.rds" vertex="1" parent="dmz"/>
+  <mxCell id="login" value="Login" edge="1" parent="1" source="user" target="web"/>
+  <mxCell id="query" edge="1" parent="1" source="web" target="db"/>
+  <mxCell id="dangling" value="Dangling" edge="1" parent="1" source="web"/>
+</root></mxGraphModel>"#;
+
+    fn node<'a>(project: &'a Project, name: &str) -> &'a Node {
+        project.diagrams[0]
+            .nodes
+            .iter()
+            .find(|node| node.name == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn shapes_and_edges_become_nodes_and_flows() {
+        let project = from_drawio("Shop", MODEL, Vec::new()).unwrap();
+        assert_eq!(project.diagrams[0].title, "Shop");
+        assert_eq!(project.config.trust_boundaries[0].name, "DMZ");
+
+        let user = node(&project, "User");
+        assert_eq!(user.type_node, TypeNode::Actor);
+        assert_eq!(user.description, "Customer");
+        assert_eq!(user.trust_boundary, None);
+        assert_eq!(node(&project, "Web").type_node, TypeNode::Process);
+        assert_eq!(node(&project, "Web").trust_boundary.as_deref(), Some("DMZ"));
+
+        let login = node(&project, "Login");
+        assert_eq!(login.type_node, TypeNode::Flow);
+        assert_eq!(login.source.as_deref(), Some("User"));
+        assert_eq!(login.destination.as_deref(), Some("Web"));
+        assert_eq!(node(&project, "Web to Orders").type_node, TypeNode::Flow);
+        assert!(project.diagrams[0]
+            .nodes
+            .iter()
+            .all(|node| node.name != "Dangling"));
+    }
+
+    #[test]
+    fn style_mapping_overrides_the_inferred_type() {
+        let project = from_drawio("Shop", MODEL, Vec::new()).unwrap();
+        assert_eq!(node(&project, "Orders").type_node, TypeNode::Process);
+
+        let style_mapping = vec![("mxgraph.aws4.rds".to_string(), TypeNode::Store)];
+        let project = from_drawio("Shop", MODEL, style_mapping).unwrap();
+        assert_eq!(node(&project, "Orders").type_node, TypeNode::Store);
+    }
+
+    #[test]
+    fn compressed_pages_are_decoded() {
+        let encoded: String = MODEL
+            .bytes()
+            .map(|byte| match byte {
+                b'<' | b'>' | b'"' | b'&' | b'%' => format!("%{:02X}", byte),
+                byte => (byte as char).to_string(),
+            })
+            .collect();
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(encoded.as_bytes()).unwrap();
+        let compressed = STANDARD.encode(encoder.finish().unwrap());
+        let content = format!(
+            r#"<mxfile><diagram name="Front">{}</diagram><diagram name="Back">{}</diagram></mxfile>"#,
+            compressed, compressed
+        );
+
+        let project = from_drawio("Shop", &content, Vec::new()).unwrap();
+        let titles: Vec<&str> = project
+            .diagrams
+            .iter()
+            .map(|diagram| diagram.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["Front", "Back"]);
+        assert_eq!(node(&project, "Login").source.as_deref(), Some("User"));
+    }
+
+    #[test]
+    fn cyclic_parents_have_no_boundary() {
+        let model = r#"<mxGraphModel><root>
+  <mxCell id="a" value="A" vertex="1" parent="b"/>
+  <mxCell id="b" value="" vertex="1" parent="a"/>
+  <mxCell id="web" value="Web" vertex="1" parent="a"/>
+</root></mxGraphModel>"#;
+        let project = from_drawio("Loop", model, Vec::new()).unwrap();
+        assert_eq!(node(&project, "Web").trust_boundary.as_deref(), Some("A"));
+
+        let model = r#"<mxGraphModel><root>
+  <mxCell id="a" value="" vertex="1" parent="b"/>
+  <mxCell id="b" value="" vertex="1" parent="a"/>
+  <mxCell id="web" value="Web" vertex="1" parent="a"/>
+</root></mxGraphModel>"#;
+        let project = from_drawio("Loop", model, Vec::new()).unwrap();
+        assert_eq!(node(&project, "Web").trust_boundary, None);
+    }
+
+    #[test]
+    fn pages_of_the_same_name_get_a_suffix() {
+        let content = format!(
+            r#"<mxfile><diagram name="Front">{}</diagram><diagram name="Front">{}</diagram><diagram name="Front">{}</diagram></mxfile>"#,
+            MODEL, MODEL, MODEL
+        );
+        let project = from_drawio("Shop", &content, Vec::new()).unwrap();
+        let titles: Vec<&str> = project
+            .diagrams
+            .iter()
+            .map(|diagram| diagram.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["Front", "Front (2)", "Front (3)"]);
+    }
+
+    #[test]
+    fn other_roots_are_rejected() {
+        assert!(matches!(
+            from_drawio("Shop", "<svg/>", Vec::new()),
+            Err(ImportError::Model(_))
+        ));
+    }
+}
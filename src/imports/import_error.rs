@@ -0,0 +1,18 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone)]
+pub enum ImportError {
+    Read(String),
+    Parse(String),
+    Model(String),
+}
+
+impl Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::Read(e) => write!(f, "cannot read model: {}", e),
+            ImportError::Parse(e) => write!(f, "cannot parse model: {}", e),
+            ImportError::Model(e) => write!(f, "invalid model: {}", e),
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use crate::input::{
+    input_diagram::{Node, TypeNode},
+    threat::{References, Severity, Status, StrideThreat, TypeThreat},
+};
+
+/// Threat Dragon version of the imported projects.
+pub const DEFAULT_THREAT_DRAGON_VERSION: &str = "2.2.0";
+
+/// Node type of an element of another tool, from its type or stencil name.
+/// Elements which are neither actors nor stores are processes.
+pub fn node_type(kind: &str) -> TypeNode {
+    let kind = kind.to_lowercase();
+    let contains_any = |words: &[&str]| words.iter().any(|word| kind.contains(word));
+    if contains_any(&[
+        "actor",
+        "user",
+        "person",
+        "external",
+        "interactor",
+        "browser",
+        "client",
+    ]) {
+        TypeNode::Actor
+    } else if contains_any(&[
+        "store",
+        "database",
+        "datastore",
+        "storage",
+        "bucket",
+        "filesystem",
+        "cache",
+        "cylinder",
+    ]) {
+        TypeNode::Store
+    } else {
+        TypeNode::Process
+    }
+}
+
+/// Status of a threat from a state name of another tool.
+pub fn status(state: &str) -> Status {
+    let state: String = state
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+    match state.as_str() {
+        "mitigated" | "implemented" | "closed" | "fixed" | "resolved" => Status::Mitigated,
+        "notapplicable" | "na" | "accepted" | "notrelevant" => Status::NotApplicable,
+        _ => Status::Open,
+    }
+}
+
+/// Severity from a score between 0 and 100.
+pub fn severity_from_score(score: f64) -> Severity {
+    if score > 66. {
+        Severity::High
+    } else if score > 33. {
+        Severity::Medium
+    } else {
+        Severity::Low
+    }
+}
+
+/// Severity from a name such as `High` or `critical`, unknown names are
+/// `Medium`.
+pub fn severity_from_name(name: &str) -> Severity {
+    match name.trim().to_lowercase().as_str() {
+        "low" | "info" | "informational" | "very low" => Severity::Low,
+        "high" | "critical" | "very high" => Severity::High,
+        _ => Severity::Medium,
+    }
+}
+
+/// Threat type from the categories of another tool, then from keywords of
+/// the threat text. Threats without any clue are `Tampering`.
+pub fn threat_type(categories: &[String], text: &str) -> TypeThreat {
+    if let Some(type_threat) = categories
+        .iter()
+        .find_map(|category| TypeThreat::from_name(category))
+    {
+        return type_threat;
+    }
+    let text = format!("{} {}", categories.join(" "), text).to_lowercase();
+    let keywords = [
+        ("spoof", StrideThreat::Spoofing),
+        ("impersonat", StrideThreat::Spoofing),
+        ("authenticat", StrideThreat::Spoofing),
+        ("repudiat", StrideThreat::Repudiation),
+        ("disclos", StrideThreat::InformationDisclosure),
+        ("leak", StrideThreat::InformationDisclosure),
+        ("sniff", StrideThreat::InformationDisclosure),
+        ("denial", StrideThreat::DenialOfService),
+        ("flood", StrideThreat::DenialOfService),
+        ("exhaust", StrideThreat::DenialOfService),
+        ("elevat", StrideThreat::ElevationOfPrivilege),
+        ("privilege", StrideThreat::ElevationOfPrivilege),
+        ("tamper", StrideThreat::Tampering),
+        ("inject", StrideThreat::Tampering),
+    ];
+    let type_threat = keywords
+        .into_iter()
+        .find(|(keyword, _)| text.contains(keyword))
+        .map(|(_, type_threat)| type_threat)
+        .unwrap_or(StrideThreat::Tampering);
+    TypeThreat::Stride(type_threat)
+}
+
+/// References found in a free text: CWE and CAPEC identifiers or links, the
+/// other links being kept as URLs.
+pub fn references(text: &str) -> Option<References> {
+    let mut references = References::default();
+    let id_after = |token: &str, prefix: &str| -> Option<u32> {
+        let start = token.find(prefix)? + prefix.len();
+        let digits: String = token[start..]
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+        digits.parse().ok()
+    };
+    for token in text.split(|c: char| c.is_whitespace() || c == ',' || c == ';') {
+        let token = token.trim_matches(|c: char| matches!(c, '(' | ')' | '[' | ']' | '<' | '>'));
+        let upper = token.to_uppercase();
+        if let Some(id) =
+            id_after(token, "cwe.mitre.org/data/definitions/").or_else(|| id_after(&upper, "CWE-"))
+        {
+            references.cwe.push(id);
+        } else if let Some(id) = id_after(token, "capec.mitre.org/data/definitions/")
+            .or_else(|| id_after(&upper, "CAPEC-"))
+        {
+            references.capec.push(id);
+        } else if token.starts_with("http://") || token.starts_with("https://") {
+            references.urls.push(token.to_string());
+        }
+    }
+    references.cwe.dedup();
+    references.capec.dedup();
+    let empty =
+        references.cwe.is_empty() && references.capec.is_empty() && references.urls.is_empty();
+    (!empty).then_some(references)
+}
+
+/// Name not used by the nodes yet, numbered when the name is already taken.
+pub fn unique_name(nodes: &[Node], name: String) -> String {
+    let mut unique = name.clone();
+    let mut index = 2;
+    while nodes.iter().any(|node| node.name == unique) {
+        unique = format!("{} ({})", name, index);
+        index += 1;
+    }
+    unique
+}
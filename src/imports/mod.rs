@@ -0,0 +1,6 @@
+pub mod drawio;
+pub mod import_error;
+pub mod mapping;
+pub mod otm;
+pub mod pytm;
+pub mod tm7;
@@ -0,0 +1,396 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use serde_json::Value;
+
+use crate::{
+    config::config::{Asset, Config, TrustBoundary},
+    input::{
+        input_diagram::{InputDiagram, Node, ThreatReference, TypeNode},
+        threat::{Methodology, References, Status, Threat},
+    },
+    otm::otm::{Otm, OtmAttributes, OtmComponent, OtmThreatInstance, DEFAULT_TRUST_ZONE},
+    project::project::Project,
+};
+
+use super::{import_error::ImportError, mapping};
+
+/// Read an OTM file, in JSON or YAML.
+pub fn import(path: &Path) -> Result<Project, ImportError> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| ImportError::Read(format!("{}: {}", path.display(), e)))?;
+    // JSON documents are YAML documents as well.
+    let otm: Otm = serde_yaml::from_str(&content)
+        .map_err(|e| ImportError::Parse(format!("{}: {}", path.display(), e)))?;
+    from_otm(&otm)
+}
+
+/// Map an OTM document to a project. Components and dataflows are grouped in
+/// one diagram per representation, the elements without representation in a
+/// diagram named after the project.
+pub fn from_otm(otm: &Otm) -> Result<Project, ImportError> {
+    let zone_names: HashMap<&String, &String> = otm
+        .trust_zones
+        .iter()
+        .filter(|zone| zone.id != DEFAULT_TRUST_ZONE)
+        .map(|zone| (&zone.id, &zone.name))
+        .collect();
+    let asset_names: HashMap<&String, &String> = otm
+        .assets
+        .iter()
+        .map(|asset| (&asset.id, &asset.name))
+        .collect();
+    let components: HashMap<&String, &OtmComponent> = otm
+        .components
+        .iter()
+        .map(|component| (&component.id, component))
+        .collect();
+
+    let config = Config {
+        threat_dragon_version: string_attribute(&otm.project.attributes, "threatDragonVersion")
+            .unwrap_or_else(|| mapping::DEFAULT_THREAT_DRAGON_VERSION.to_string()),
+        title: otm.project.name.clone(),
+        owner: otm.project.owner.clone().unwrap_or_default(),
+        description: otm.project.description.clone().unwrap_or_default(),
+        trust_boundaries: otm
+            .trust_zones
+            .iter()
+            .filter(|zone| zone.id != DEFAULT_TRUST_ZONE)
+            .map(|zone| TrustBoundary {
+                name: zone.name.clone(),
+                description: zone.description.clone().unwrap_or_default(),
+                limit_of_access: string_attribute(&zone.attributes, "limitOfAccess")
+                    .unwrap_or_default(),
+                level_of_authorization: string_attribute(&zone.attributes, "levelOfAuthorization")
+                    .unwrap_or_default(),
+            })
+            .collect(),
+        assets: otm
+            .assets
+            .iter()
+            .map(|asset| Asset {
+                name: asset.name.clone(),
+                description: asset.description.clone().unwrap_or_default(),
+            })
+            .collect(),
+        diagrams: otm
+            .project
+            .attributes
+            .get("diagrams")
+            .and_then(|diagrams| serde_json::from_value(diagrams.clone()).ok())
+            .unwrap_or_default(),
+        components: Vec::new(),
+    };
+
+    let threats = to_threats(otm);
+    let threat_titles: HashMap<&String, &String> = otm
+        .threats
+        .iter()
+        .map(|threat| (&threat.id, &threat.name))
+        .collect();
+    let threat_references = |instances: &[OtmThreatInstance]| -> Vec<ThreatReference> {
+        instances
+            .iter()
+            .filter_map(|instance| threat_titles.get(&instance.threat))
+            .map(|title| ThreatReference::Title(title.to_string()))
+            .collect()
+    };
+
+    let mut diagrams: Vec<InputDiagram> = otm
+        .representations
+        .iter()
+        .map(|representation| InputDiagram {
+            title: representation.name.clone(),
+            description: representation.description.clone().unwrap_or_default(),
+            methodology: representation
+                .attributes
+                .get("methodology")
+                .and_then(|methodology| {
+                    serde_json::from_value::<Methodology>(methodology.clone()).ok()
+                }),
+            nodes: Vec::new(),
+        })
+        .collect();
+    let mut default_diagram = InputDiagram {
+        title: otm.project.name.clone(),
+        description: otm.project.description.clone().unwrap_or_default(),
+        methodology: None,
+        nodes: Vec::new(),
+    };
+    let diagram_index = |component: &OtmComponent| {
+        component.representations.iter().find_map(|element| {
+            otm.representations
+                .iter()
+                .position(|representation| representation.id == element.representation)
+        })
+    };
+
+    for component in otm.components.iter() {
+        let node = Node {
+            name: component.name.clone(),
+            type_node: mapping::node_type(&component.type_field),
+            description: component.description.clone().unwrap_or_default(),
+            out_of_scope: bool_attribute(&component.attributes, "outOfScope"),
+            trust_boundary: trust_zone(component, &components)
+                .and_then(|zone| zone_names.get(&zone))
+                .map(|name| name.to_string()),
+            trust_level: string_attribute(&component.attributes, "trustLevel"),
+            source: None,
+            destination: None,
+            asset: component.assets.as_ref().and_then(|assets| {
+                assets
+                    .processed
+                    .iter()
+                    .chain(assets.stored.iter())
+                    .find_map(|asset| asset_names.get(asset).map(|name| name.to_string()))
+            }),
+            protocol: None,
+            is_encrypted: None,
+            is_public_network: None,
+            threats: threat_references(&component.threats),
+        };
+        match diagram_index(component) {
+            Some(index) => diagrams[index].nodes.push(node),
+            None => default_diagram.nodes.push(node),
+        }
+    }
+
+    for dataflow in otm.dataflows.iter() {
+        let endpoint = |id: &String| {
+            components.get(id).copied().ok_or_else(|| {
+                ImportError::Model(format!(
+                    "unknown component {} in dataflow {}",
+                    id, dataflow.name
+                ))
+            })
+        };
+        let source = endpoint(&dataflow.source)?;
+        let destination = endpoint(&dataflow.destination)?;
+        let flows = if dataflow.bidirectional {
+            vec![(source, destination), (destination, source)]
+        } else {
+            vec![(source, destination)]
+        };
+        for (source, destination) in flows {
+            let node = Node {
+                name: dataflow.name.clone(),
+                type_node: TypeNode::Flow,
+                description: dataflow.description.clone().unwrap_or_default(),
+                out_of_scope: bool_attribute(&dataflow.attributes, "outOfScope"),
+                trust_boundary: None,
+                trust_level: string_attribute(&dataflow.attributes, "trustLevel"),
+                source: Some(source.name.clone()),
+                destination: Some(destination.name.clone()),
+                asset: dataflow
+                    .assets
+                    .iter()
+                    .find_map(|asset| asset_names.get(asset).map(|name| name.to_string())),
+                protocol: string_attribute(&dataflow.attributes, "protocol"),
+                is_encrypted: bool_attribute(&dataflow.attributes, "isEncrypted"),
+                is_public_network: bool_attribute(&dataflow.attributes, "isPublicNetwork"),
+                threats: threat_references(&dataflow.threats),
+            };
+            match diagram_index(source) {
+                Some(index) => diagrams[index].nodes.push(node),
+                None => default_diagram.nodes.push(node),
+            }
+        }
+    }
+
+    if !default_diagram.nodes.is_empty() {
+        diagrams.push(default_diagram);
+    }
+
+    Ok(Project {
+        config,
+        threats,
+        diagrams,
+        diagram_paths: HashMap::new(),
+        node_paths: HashMap::new(),
+        component_overrides: HashMap::new(),
+        included_paths: Vec::new(),
+    })
+}
+
+fn to_threats(otm: &Otm) -> Vec<Threat> {
+    let instances: Vec<&OtmThreatInstance> = otm
+        .components
+        .iter()
+        .flat_map(|component| component.threats.iter())
+        .chain(
+            otm.dataflows
+                .iter()
+                .flat_map(|dataflow| dataflow.threats.iter()),
+        )
+        .collect();
+
+    otm.threats
+        .iter()
+        .map(|threat| {
+            let threat_instances: Vec<&&OtmThreatInstance> = instances
+                .iter()
+                .filter(|instance| instance.threat == threat.id)
+                .collect();
+            // threats.yaml has one status per threat: it stays open while
+            // one of its instances is open.
+            let statuses: Vec<Status> = threat_instances
+                .iter()
+                .map(|instance| mapping::status(&instance.state))
+                .collect();
+            let status = if statuses.is_empty() || statuses.contains(&Status::Open) {
+                Status::Open
+            } else if statuses.contains(&Status::Mitigated) {
+                Status::Mitigated
+            } else {
+                Status::NotApplicable
+            };
+
+            let mut mitigations: Vec<String> = Vec::new();
+            threat_instances
+                .iter()
+                .flat_map(|instance| instance.mitigations.iter())
+                .filter_map(|instance| {
+                    otm.mitigations
+                        .iter()
+                        .find(|mitigation| mitigation.id == instance.mitigation)
+                })
+                .for_each(|mitigation| {
+                    let text = mitigation
+                        .description
+                        .clone()
+                        .unwrap_or_else(|| mitigation.name.clone());
+                    if !mitigations.contains(&text) {
+                        mitigations.push(text);
+                    }
+                });
+
+            let severity = match string_attribute(&threat.attributes, "severity") {
+                Some(severity) => mapping::severity_from_name(&severity),
+                None => threat
+                    .risk
+                    .as_ref()
+                    .map(|risk| mapping::severity_from_score(risk.impact))
+                    .unwrap_or(mapping::severity_from_name("")),
+            };
+            let description = threat.description.clone().unwrap_or_default();
+            let references = References {
+                cwe: threat
+                    .cwes
+                    .iter()
+                    .filter_map(|cwe| cwe.trim_start_matches("CWE-").parse().ok())
+                    .collect(),
+                capec: array_attribute(&threat.attributes, "capec")
+                    .iter()
+                    .filter_map(Value::as_u64)
+                    .map(|capec| capec as u32)
+                    .collect(),
+                asvs: array_attribute(&threat.attributes, "asvs")
+                    .iter()
+                    .filter_map(|asvs| asvs.as_str().map(str::to_string))
+                    .collect(),
+                urls: array_attribute(&threat.attributes, "urls")
+                    .iter()
+                    .filter_map(|url| url.as_str().map(str::to_string))
+                    .collect(),
+            };
+            let has_references = !(references.cwe.is_empty()
+                && references.capec.is_empty()
+                && references.asvs.is_empty()
+                && references.urls.is_empty());
+
+            Threat {
+                title: threat.name.clone(),
+                status,
+                severity,
+                type_field: mapping::threat_type(
+                    &threat.categories,
+                    &format!("{} {}", threat.name, description),
+                ),
+                description,
+                mitigation: mitigations.join("\n"),
+                vector: string_attribute(&threat.attributes, "vector").unwrap_or_default(),
+                references: has_references.then_some(references),
+            }
+        })
+        .collect()
+}
+
+/// Trust zone of the component, or of its closest parent component.
+fn trust_zone(
+    component: &OtmComponent,
+    components: &HashMap<&String, &OtmComponent>,
+) -> Option<String> {
+    let mut current = component;
+    for _ in 0..components.len() {
+        if let Some(trust_zone) = &current.parent.trust_zone {
+            return Some(trust_zone.clone());
+        }
+        current = components.get(current.parent.component.as_ref()?)?;
+    }
+    None
+}
+
+fn string_attribute(attributes: &OtmAttributes, key: &str) -> Option<String> {
+    attributes.get(key).and_then(|value| match value {
+        Value::String(value) => Some(value.clone()),
+        Value::Null => None,
+        value => Some(value.to_string()),
+    })
+}
+
+fn bool_attribute(attributes: &OtmAttributes, key: &str) -> Option<bool> {
+    attributes.get(key).and_then(Value::as_bool)
+}
+
+fn array_attribute(attributes: &OtmAttributes, key: &str) -> Vec<Value> {
+    attributes
+        .get(key)
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn otm_with_states(states: &[&str]) -> Otm {
+        let components: Vec<Value> = states
+            .iter()
+            .enumerate()
+            .map(|(index, state)| {
+                serde_json::json!({
+                    "id": format!("c{}", index),
+                    "name": format!("Component {}", index),
+                    "type": "process",
+                    "parent": { "trustZone": "internet" },
+                    "threats": [{ "threat": "t1", "state": state }]
+                })
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "otmVersion": "0.2.0",
+            "project": { "name": "Project", "id": "project" },
+            "components": components,
+            "threats": [{ "name": "Threat", "id": "t1", "categories": ["Spoofing"] }]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn threat_stays_open_while_an_instance_is_open() {
+        let threats = to_threats(&otm_with_states(&["mitigated", "open"]));
+        assert_eq!(threats[0].status, Status::Open);
+        let threats = to_threats(&otm_with_states(&["open", "mitigated"]));
+        assert_eq!(threats[0].status, Status::Open);
+    }
+
+    #[test]
+    fn threat_is_mitigated_when_every_instance_is_closed() {
+        let threats = to_threats(&otm_with_states(&["mitigated", "notApplicable"]));
+        assert_eq!(threats[0].status, Status::Mitigated);
+        let threats = to_threats(&otm_with_states(&["notApplicable"]));
+        assert_eq!(threats[0].status, Status::NotApplicable);
+        let threats = to_threats(&otm_with_states(&[]));
+        assert_eq!(threats[0].status, Status::Open);
+    }
+}
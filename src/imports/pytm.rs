@@ -0,0 +1,305 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use serde_json::Value;
+
+use crate::{
+    config::config::{Asset, Config, TrustBoundary},
+    input::{
+        input_diagram::{InputDiagram, Node, ThreatReference, TypeNode},
+        threat::{Methodology, Status, Threat},
+    },
+    project::project::Project,
+};
+
+use super::{import_error::ImportError, mapping};
+
+/// Read the JSON report written by `tm.py --json`.
+pub fn import(path: &Path) -> Result<Project, ImportError> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| ImportError::Read(format!("{}: {}", path.display(), e)))?;
+    let model: Value = serde_json::from_str(&content)
+        .map_err(|e| ImportError::Parse(format!("{}: {}", path.display(), e)))?;
+    from_pytm(&model)
+}
+
+/// Map a pytm model to a project with one diagram. Actors, assets and flows
+/// become nodes, boundaries become trust boundaries and findings become
+/// threats, one threat per pytm threat id.
+pub fn from_pytm(model: &Value) -> Result<Project, ImportError> {
+    let title = string(model, "name")
+        .ok_or_else(|| ImportError::Model("the model has no name".to_string()))?;
+    let description = string(model, "description").unwrap_or_default();
+
+    let config = Config {
+        threat_dragon_version: mapping::DEFAULT_THREAT_DRAGON_VERSION.to_string(),
+        title: title.clone(),
+        owner: String::new(),
+        description: description.clone(),
+        trust_boundaries: array(model, "boundaries")
+            .iter()
+            .filter_map(|boundary| {
+                Some(TrustBoundary {
+                    name: string(boundary, "name")?,
+                    description: string(boundary, "description").unwrap_or_default(),
+                    limit_of_access: String::new(),
+                    level_of_authorization: String::new(),
+                })
+            })
+            .collect(),
+        assets: array(model, "data")
+            .iter()
+            .filter_map(|data| {
+                Some(Asset {
+                    name: string(data, "name")?,
+                    description: string(data, "description").unwrap_or_default(),
+                })
+            })
+            .collect(),
+        diagrams: Vec::new(),
+        components: Vec::new(),
+    };
+
+    // Elements are listed by kind, `elements` repeating some of them.
+    let mut nodes: Vec<Node> = Vec::new();
+    let elements = array(model, "actors")
+        .into_iter()
+        .chain(array(model, "assets"))
+        .chain(array(model, "elements"));
+    for element in elements {
+        let Some(name) = string(element, "name") else {
+            continue;
+        };
+        if element.get("source").is_some() || element.get("sink").is_some() {
+            continue;
+        }
+        if nodes.iter().any(|node| node.name == name) {
+            continue;
+        }
+        let class = string(element, "__class__").unwrap_or_default();
+        nodes.push(Node {
+            type_node: mapping::node_type(&class),
+            description: string(element, "description").unwrap_or_default(),
+            out_of_scope: boolean(element, "inScope").map(|in_scope| !in_scope),
+            trust_boundary: string(element, "inBoundary"),
+            trust_level: None,
+            source: None,
+            destination: None,
+            asset: None,
+            protocol: None,
+            is_encrypted: None,
+            is_public_network: None,
+            threats: Vec::new(),
+            name,
+        });
+    }
+
+    for flow in array(model, "flows") {
+        let (Some(name), Some(source), Some(sink)) = (
+            string(flow, "name"),
+            string(flow, "source"),
+            string(flow, "sink"),
+        ) else {
+            continue;
+        };
+        for end in [&source, &sink] {
+            if !nodes.iter().any(|node| node.name == *end) {
+                return Err(ImportError::Model(format!(
+                    "unknown element {} in flow {}",
+                    end, name
+                )));
+            }
+        }
+        nodes.push(Node {
+            name,
+            type_node: TypeNode::Flow,
+            description: string(flow, "description")
+                .or_else(|| string(flow, "note"))
+                .unwrap_or_default(),
+            out_of_scope: boolean(flow, "inScope").map(|in_scope| !in_scope),
+            trust_boundary: None,
+            trust_level: None,
+            source: Some(source),
+            destination: Some(sink),
+            asset: array(flow, "data")
+                .first()
+                .and_then(|data| data.as_str().map(str::to_string)),
+            protocol: string(flow, "protocol"),
+            is_encrypted: boolean(flow, "isEncrypted").or_else(|| {
+                flow.get("controls")
+                    .and_then(|controls| boolean(controls, "isEncrypted"))
+            }),
+            is_public_network: None,
+            threats: Vec::new(),
+        });
+    }
+
+    let mut threats: Vec<(String, Threat)> = Vec::new();
+    for finding in array(model, "findings") {
+        let threat_title = string(finding, "description").unwrap_or_default();
+        let threat_id = string(finding, "threat_id").unwrap_or_else(|| threat_title.clone());
+        let title = if threat_title.is_empty() || threat_id == threat_title {
+            threat_id.clone()
+        } else {
+            format!("{} {}", threat_id, threat_title)
+        };
+        if !threats.iter().any(|(known, _)| *known == threat_id) {
+            let details = string(finding, "details").unwrap_or_default();
+            threats.push((
+                threat_id,
+                Threat {
+                    title: title.clone(),
+                    status: string(finding, "response")
+                        .map(|response| mapping::status(&response))
+                        .unwrap_or(Status::Open),
+                    severity: mapping::severity_from_name(
+                        &string(finding, "severity").unwrap_or_default(),
+                    ),
+                    type_field: mapping::threat_type(&[], &format!("{} {}", threat_title, details)),
+                    description: details,
+                    mitigation: string(finding, "mitigations").unwrap_or_default(),
+                    vector: string(finding, "example").unwrap_or_default(),
+                    references: mapping::references(
+                        &string(finding, "references").unwrap_or_default(),
+                    ),
+                },
+            ));
+        }
+
+        let target = string(finding, "target").unwrap_or_default();
+        if let Some(node) = nodes.iter_mut().find(|node| node.name == target) {
+            if !node.threat_titles().any(|known| *known == title) {
+                node.threats.push(ThreatReference::Title(title));
+            }
+        }
+    }
+
+    Ok(Project {
+        config,
+        threats: threats.into_iter().map(|(_, threat)| threat).collect(),
+        diagrams: vec![InputDiagram {
+            title,
+            description,
+            methodology: Some(Methodology::Stride),
+            nodes,
+        }],
+        diagram_paths: HashMap::new(),
+        node_paths: HashMap::new(),
+        component_overrides: HashMap::new(),
+        included_paths: Vec::new(),
+    })
+}
+
+fn string(value: &Value, key: &str) -> Option<String> {
+    match value.get(key)? {
+        Value::String(value) if !value.is_empty() => Some(value.clone()),
+        Value::Number(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+fn boolean(value: &Value, key: &str) -> Option<bool> {
+    value.get(key).and_then(Value::as_bool)
+}
+
+fn array<'a>(value: &'a Value, key: &str) -> Vec<&'a Value> {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .map(|values| values.iter().collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::input::threat::Severity;
+
+    fn model() -> Value {
+        json!({
+            "name": "Shop",
+            "description": "Online shop",
+            "boundaries": [{ "name": "Internet" }],
+            "data": [{ "name": "Credentials", "description": "Login and password" }],
+            "actors": [{ "__class__": "Actor", "name": "User", "inBoundary": "Internet" }],
+            "assets": [
+                { "__class__": "Server", "name": "Web" },
+                { "__class__": "Datastore", "name": "Database", "inScope": false }
+            ],
+            "flows": [{
+                "name": "Login",
+                "source": "User",
+                "sink": "Web",
+                "protocol": "HTTPS",
+                "data": ["Credentials"],
+                "controls": { "isEncrypted": true }
+            }],
+            "findings": [
+                {
+                    "threat_id": "INP02",
+                    "description": "Overflow Buffers",
+                    "target": "Web",
+                    "severity": "High",
+                    "references": "https://cwe.mitre.org/data/definitions/120.html, CAPEC-100"
+                },
+                { "threat_id": "INP02", "description": "Overflow Buffers", "target": "Login" }
+            ]
+        })
+    }
+
+    #[test]
+    fn elements_become_nodes() {
+        let project = from_pytm(&model()).unwrap();
+        assert_eq!(project.config.title, "Shop");
+        assert_eq!(project.config.trust_boundaries[0].name, "Internet");
+        assert_eq!(project.config.assets[0].name, "Credentials");
+
+        let nodes = &project.diagrams[0].nodes;
+        let node = |name: &str| nodes.iter().find(|node| node.name == name).unwrap();
+        assert_eq!(node("User").type_node, TypeNode::Actor);
+        assert_eq!(node("User").trust_boundary.as_deref(), Some("Internet"));
+        assert_eq!(node("Web").type_node, TypeNode::Process);
+        assert_eq!(node("Database").type_node, TypeNode::Store);
+        assert_eq!(node("Database").out_of_scope, Some(true));
+
+        let login = node("Login");
+        assert_eq!(login.type_node, TypeNode::Flow);
+        assert_eq!(login.source.as_deref(), Some("User"));
+        assert_eq!(login.destination.as_deref(), Some("Web"));
+        assert_eq!(login.asset.as_deref(), Some("Credentials"));
+        assert_eq!(login.protocol.as_deref(), Some("HTTPS"));
+        assert_eq!(login.is_encrypted, Some(true));
+    }
+
+    #[test]
+    fn findings_of_a_threat_id_share_one_threat() {
+        let project = from_pytm(&model()).unwrap();
+        assert_eq!(project.threats.len(), 1);
+        let threat = &project.threats[0];
+        assert_eq!(threat.title, "INP02 Overflow Buffers");
+        assert_eq!(threat.severity, Severity::High);
+        let references = threat.references.as_ref().unwrap();
+        assert_eq!(references.cwe, vec![120]);
+        assert_eq!(references.capec, vec![100]);
+
+        for name in ["Web", "Login"] {
+            let node = project.diagrams[0]
+                .nodes
+                .iter()
+                .find(|node| node.name == name)
+                .unwrap();
+            assert_eq!(
+                node.threat_titles().collect::<Vec<_>>(),
+                vec![&threat.title]
+            );
+        }
+    }
+
+    #[test]
+    fn flow_to_unknown_element_is_an_error() {
+        let mut model = model();
+        model["flows"][0]["sink"] = json!("Nowhere");
+        assert!(matches!(from_pytm(&model), Err(ImportError::Model(_))));
+    }
+}
@@ -0,0 +1,520 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use roxmltree::{Document, Node as XmlNode};
+
+use crate::{
+    config::config::{Config, TrustBoundary},
+    input::{
+        input_diagram::{InputDiagram, Node, ThreatReference, TypeNode},
+        threat::{Methodology, Threat},
+    },
+    project::project::Project,
+};
+
+use super::{import_error::ImportError, mapping};
+
+/// Read a Microsoft Threat Modeling Tool model.
+pub fn import(path: &Path) -> Result<Project, ImportError> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| ImportError::Read(format!("{}: {}", path.display(), e)))?;
+    from_tm7(content.trim_start_matches('\u{feff}')).map_err(|e| match e {
+        ImportError::Parse(e) => ImportError::Parse(format!("{}: {}", path.display(), e)),
+        e => e,
+    })
+}
+
+/// Stencil or line of a drawing surface.
+struct Element<'a, 'input> {
+    guid: String,
+    generic_type: String,
+    xml: XmlNode<'a, 'input>,
+}
+
+/// Map a tm7 model to a project, one diagram per drawing surface. Stencils
+/// become nodes, connectors become flows, border boundaries become the trust
+/// boundary of the stencils they contain, and threat instances become threats
+/// attached to their flow. The XML namespaces are ignored.
+pub fn from_tm7(content: &str) -> Result<Project, ImportError> {
+    let document = Document::parse(content).map_err(|e| ImportError::Parse(format!("{}", e)))?;
+    let root = document.root_element();
+    if root.tag_name().name() != "ThreatModel" {
+        return Err(ImportError::Model(
+            "the root element is not a ThreatModel".to_string(),
+        ));
+    }
+
+    let meta = child(root, "MetaInformation");
+    let meta_text = |name: &str| meta.and_then(|meta| child_text(meta, name));
+    let title = meta_text("ThreatModelName").unwrap_or_else(|| "Threat model".to_string());
+
+    let mut trust_boundaries: Vec<TrustBoundary> = Vec::new();
+    let mut diagrams = Vec::new();
+    // Name of the nodes by stencil or line guid, for the threat instances.
+    let mut names: HashMap<(String, String), String> = HashMap::new();
+
+    let surfaces = child(root, "DrawingSurfaceList")
+        .map(|list| children(list, "DrawingSurfaceModel"))
+        .unwrap_or_default();
+    for (index, surface) in surfaces.iter().enumerate() {
+        let surface_guid = child_text(*surface, "Guid").unwrap_or_default();
+        let diagram_title =
+            child_text(*surface, "Header").unwrap_or_else(|| format!("Diagram {}", index + 1));
+        let borders = elements(*surface, "Borders");
+        let lines = elements(*surface, "Lines");
+
+        let border_boundaries: Vec<&Element> = borders
+            .iter()
+            .filter(|element| element.generic_type == "GE.TB.B")
+            .collect();
+        let line_boundaries: Vec<&Element> = lines
+            .iter()
+            .filter(|element| element.generic_type == "GE.TB.L")
+            .collect();
+        for boundary in border_boundaries.iter().chain(line_boundaries.iter()) {
+            let name = element_name(boundary);
+            if !trust_boundaries.iter().any(|known| known.name == name) {
+                trust_boundaries.push(TrustBoundary {
+                    name,
+                    description: header(boundary).unwrap_or_default(),
+                    limit_of_access: String::new(),
+                    level_of_authorization: String::new(),
+                });
+            }
+        }
+
+        let mut nodes: Vec<Node> = Vec::new();
+        for stencil in borders.iter() {
+            let type_node = match stencil.generic_type.as_str() {
+                "GE.P" => TypeNode::Process,
+                "GE.EI" => TypeNode::Actor,
+                "GE.DS" => TypeNode::Store,
+                _ => continue,
+            };
+            let name = mapping::unique_name(&nodes, element_name(stencil));
+            names.insert((surface_guid.clone(), stencil.guid.clone()), name.clone());
+            nodes.push(Node {
+                type_node,
+                description: header(stencil).unwrap_or_default(),
+                out_of_scope: out_of_scope(stencil),
+                trust_boundary: containing_boundary(stencil, &border_boundaries),
+                trust_level: None,
+                source: None,
+                destination: None,
+                asset: None,
+                protocol: None,
+                is_encrypted: None,
+                is_public_network: None,
+                threats: Vec::new(),
+                name,
+            });
+        }
+
+        for line in lines.iter().filter(|line| line.generic_type == "GE.DF") {
+            let end = |name: &str| {
+                child_text(line.xml, name)
+                    .and_then(|guid| names.get(&(surface_guid.clone(), guid)).cloned())
+            };
+            // Connectors which are not attached at both ends are skipped.
+            let (Some(source), Some(destination)) = (end("SourceGuid"), end("TargetGuid")) else {
+                continue;
+            };
+            let name = mapping::unique_name(&nodes, element_name(line));
+            names.insert((surface_guid.clone(), line.guid.clone()), name.clone());
+            let protocol = header(line).filter(|header| header != "Generic Data Flow");
+            nodes.push(Node {
+                type_node: TypeNode::Flow,
+                description: String::new(),
+                out_of_scope: out_of_scope(line),
+                trust_boundary: crossed_boundary(line, &line_boundaries),
+                trust_level: None,
+                source: Some(source),
+                destination: Some(destination),
+                asset: None,
+                protocol,
+                is_encrypted: None,
+                is_public_network: None,
+                threats: Vec::new(),
+                name,
+            });
+        }
+
+        diagrams.push((
+            surface_guid,
+            InputDiagram {
+                title: diagram_title,
+                description: String::new(),
+                methodology: Some(Methodology::Stride),
+                nodes,
+            },
+        ));
+    }
+
+    let mut threats: Vec<Threat> = Vec::new();
+    let instances: Vec<XmlNode> = child(root, "ThreatInstances")
+        .map(|list| list.children().filter(|node| node.is_element()).collect())
+        .unwrap_or_default();
+    for instance in instances {
+        let Some(value) = child(instance, "Value") else {
+            continue;
+        };
+        let properties = threat_properties(value);
+        let property = |key: &str| {
+            properties
+                .get(key)
+                .filter(|value| !value.is_empty())
+                .cloned()
+        };
+        let id = child_text(value, "Id").unwrap_or_default();
+        let mut title = property("Title")
+            .or_else(|| child_text(value, "Title"))
+            .unwrap_or_else(|| format!("Threat {}", id));
+        if threats.iter().any(|threat| threat.title == title) {
+            title = format!("{} (#{})", title, id);
+        }
+        let category = property("UserThreatCategory")
+            .or_else(|| child_text(value, "UserThreatCategory"))
+            .unwrap_or_default();
+        let description = property("UserThreatDescription").unwrap_or_default();
+        let state_information =
+            property("StateInformation").or_else(|| child_text(value, "StateInformation"));
+
+        threats.push(Threat {
+            title: title.clone(),
+            status: mapping::status(&child_text(value, "State").unwrap_or_default()),
+            severity: mapping::severity_from_name(
+                &property("Priority")
+                    .or_else(|| child_text(value, "Priority"))
+                    .unwrap_or_default(),
+            ),
+            type_field: mapping::threat_type(&[category], &format!("{} {}", title, description)),
+            mitigation: state_information
+                .or_else(|| property("PossibleMitigations"))
+                .unwrap_or_default(),
+            vector: property("UserThreatShortDescription").unwrap_or_default(),
+            references: mapping::references(&description),
+            description,
+        });
+
+        // The threat goes to its flow, or to its target for threats on an
+        // element.
+        let surface_guid = child_text(value, "DrawingSurfaceGuid").unwrap_or_default();
+        let target = ["FlowGuid", "TargetGuid"].iter().find_map(|name| {
+            child_text(value, name).and_then(|guid| names.get(&(surface_guid.clone(), guid)))
+        });
+        let diagram = diagrams
+            .iter_mut()
+            .find(|(guid, _)| *guid == surface_guid)
+            .map(|(_, diagram)| diagram);
+        if let (Some(target), Some(diagram)) = (target, diagram) {
+            if let Some(node) = diagram.nodes.iter_mut().find(|node| node.name == *target) {
+                node.threats.push(ThreatReference::Title(title));
+            }
+        }
+    }
+
+    Ok(Project {
+        config: Config {
+            threat_dragon_version: mapping::DEFAULT_THREAT_DRAGON_VERSION.to_string(),
+            title,
+            owner: meta_text("Owner").unwrap_or_default(),
+            description: meta_text("HighLevelSystemDescription").unwrap_or_default(),
+            trust_boundaries,
+            assets: Vec::new(),
+            diagrams: Vec::new(),
+            components: Vec::new(),
+        },
+        threats,
+        diagrams: diagrams.into_iter().map(|(_, diagram)| diagram).collect(),
+        diagram_paths: HashMap::new(),
+        node_paths: HashMap::new(),
+        component_overrides: HashMap::new(),
+        included_paths: Vec::new(),
+    })
+}
+
+/// Stencils of `Borders` or lines of `Lines`, stored as guid and value pairs.
+fn elements<'a, 'input>(surface: XmlNode<'a, 'input>, list: &str) -> Vec<Element<'a, 'input>> {
+    let Some(list) = child(surface, list) else {
+        return Vec::new();
+    };
+    list.children()
+        .filter(|pair| pair.is_element())
+        .filter_map(|pair| {
+            let xml = child(pair, "Value")?;
+            Some(Element {
+                guid: child_text(xml, "Guid").or_else(|| child_text(pair, "Key"))?,
+                generic_type: child_text(xml, "GenericTypeId").unwrap_or_default(),
+                xml,
+            })
+        })
+        .collect()
+}
+
+/// Display name and value of the properties of a stencil or a line.
+fn properties(element: &Element) -> Vec<(String, Option<String>)> {
+    child(element.xml, "Properties")
+        .map(|properties| {
+            properties
+                .children()
+                .filter(|property| property.is_element())
+                .map(|property| {
+                    (
+                        child_text(property, "DisplayName").unwrap_or_default(),
+                        child_text(property, "Value"),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn property(element: &Element, display_name: &str) -> Option<String> {
+    properties(element)
+        .into_iter()
+        .find(|(name, _)| name == display_name)
+        .and_then(|(_, value)| value)
+}
+
+fn element_name(element: &Element) -> String {
+    property(element, "Name")
+        .or_else(|| header(element))
+        .unwrap_or_else(|| element.guid.clone())
+}
+
+/// Name of the stencil type, such as `Web Application` or `HTTPS`.
+fn header(element: &Element) -> Option<String> {
+    properties(element)
+        .into_iter()
+        .next()
+        .map(|(name, _)| name)
+        .filter(|name| !name.is_empty() && name != "Name")
+}
+
+fn out_of_scope(element: &Element) -> Option<bool> {
+    property(element, "Out Of Scope").map(|value| value.eq_ignore_ascii_case("true"))
+}
+
+/// Smallest border boundary containing the center of the stencil.
+fn containing_boundary(stencil: &Element, boundaries: &[&Element]) -> Option<String> {
+    let (left, top, width, height) = bounds(stencil)?;
+    let (x, y) = (left + width / 2., top + height / 2.);
+    boundaries
+        .iter()
+        .filter_map(|boundary| Some((boundary, bounds(boundary)?)))
+        .filter(|(_, (left, top, width, height))| {
+            x >= *left && x <= left + width && y >= *top && y <= top + height
+        })
+        .min_by(|(_, a), (_, b)| (a.2 * a.3).total_cmp(&(b.2 * b.3)))
+        .map(|(boundary, _)| element_name(boundary))
+}
+
+/// First line boundary crossed by the flow, both lines taken as straight
+/// segments between their ends.
+fn crossed_boundary(flow: &Element, boundaries: &[&Element]) -> Option<String> {
+    let flow_segment = segment(flow)?;
+    boundaries
+        .iter()
+        .find(|boundary| segment(boundary).is_some_and(|other| intersect(flow_segment, other)))
+        .map(|boundary| element_name(boundary))
+}
+
+type Point = (f64, f64);
+
+fn segment(line: &Element) -> Option<(Point, Point)> {
+    let number = |name: &str| child_text(line.xml, name)?.parse::<f64>().ok();
+    Some((
+        (number("SourceX")?, number("SourceY")?),
+        (number("TargetX")?, number("TargetY")?),
+    ))
+}
+
+fn intersect((a, b): (Point, Point), (c, d): (Point, Point)) -> bool {
+    let side = |(p, q): (Point, Point), r: Point| {
+        ((q.0 - p.0) * (r.1 - p.1) - (q.1 - p.1) * (r.0 - p.0)).signum()
+    };
+    side((a, b), c) != side((a, b), d) && side((c, d), a) != side((c, d), b)
+}
+
+fn bounds(element: &Element) -> Option<(f64, f64, f64, f64)> {
+    let number = |name: &str| child_text(element.xml, name)?.parse::<f64>().ok();
+    Some((
+        number("Left")?,
+        number("Top")?,
+        number("Width")?,
+        number("Height")?,
+    ))
+}
+
+fn threat_properties(value: XmlNode) -> HashMap<String, String> {
+    child(value, "Properties")
+        .map(|properties| {
+            properties
+                .children()
+                .filter(|pair| pair.is_element())
+                .filter_map(|pair| {
+                    Some((
+                        child_text(pair, "Key")?,
+                        child_text(pair, "Value").unwrap_or_default(),
+                    ))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn child<'a, 'input>(node: XmlNode<'a, 'input>, name: &str) -> Option<XmlNode<'a, 'input>> {
+    node.children()
+        .find(|child| child.is_element() && child.tag_name().name() == name)
+}
+
+fn children<'a, 'input>(node: XmlNode<'a, 'input>, name: &str) -> Vec<XmlNode<'a, 'input>> {
+    node.children()
+        .filter(|child| child.is_element() && child.tag_name().name() == name)
+        .collect()
+}
+
+fn child_text(node: XmlNode, name: &str) -> Option<String> {
+    child(node, name)
+        .and_then(|child| child.text())
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::threat::{Severity, Status};
+
+    const MODEL: &str = r#"<ThreatModel xmlns="http://schemas.datacontract.org/2004/07/ThreatModeling.Model">
+  <DrawingSurfaceList>
+    <DrawingSurfaceModel>
+      <Guid>surface</Guid>
+      <Header>Shop</Header>
+      <Borders>
+        <KeyValueOfguidanyType><Key>user</Key><Value>
+          <Guid>user</Guid><GenericTypeId>GE.EI</GenericTypeId>
+          <Left>0</Left><Top>0</Top><Width>100</Width><Height>100</Height>
+          <Properties>
+            <anyType><DisplayName>Browser</DisplayName></anyType>
+            <anyType><DisplayName>Name</DisplayName><Value>User</Value></anyType>
+          </Properties>
+        </Value></KeyValueOfguidanyType>
+        <KeyValueOfguidanyType><Key>web</Key><Value>
+          <Guid>web</Guid><GenericTypeId>GE.P</GenericTypeId>
+          <Left>300</Left><Top>0</Top><Width>100</Width><Height>100</Height>
+          <Properties>
+            <anyType><DisplayName>Web Application</DisplayName></anyType>
+            <anyType><DisplayName>Name</DisplayName><Value>Web</Value></anyType>
+          </Properties>
+        </Value></KeyValueOfguidanyType>
+        <KeyValueOfguidanyType><Key>dmz</Key><Value>
+          <Guid>dmz</Guid><GenericTypeId>GE.TB.B</GenericTypeId>
+          <Left>250</Left><Top>-50</Top><Width>200</Width><Height>200</Height>
+          <Properties>
+            <anyType><DisplayName>Name</DisplayName><Value>DMZ</Value></anyType>
+          </Properties>
+        </Value></KeyValueOfguidanyType>
+      </Borders>
+      <Lines>
+        <KeyValueOfguidanyType><Key>login</Key><Value>
+          <Guid>login</Guid><GenericTypeId>GE.DF</GenericTypeId>
+          <SourceGuid>user</SourceGuid><TargetGuid>web</TargetGuid>
+          <SourceX>100</SourceX><SourceY>50</SourceY><TargetX>300</TargetX><TargetY>50</TargetY>
+          <Properties>
+            <anyType><DisplayName>HTTPS</DisplayName></anyType>
+            <anyType><DisplayName>Name</DisplayName><Value>Login</Value></anyType>
+          </Properties>
+        </Value></KeyValueOfguidanyType>
+        <KeyValueOfguidanyType><Key>internet</Key><Value>
+          <Guid>internet</Guid><GenericTypeId>GE.TB.L</GenericTypeId>
+          <SourceX>200</SourceX><SourceY>-100</SourceY><TargetX>200</TargetX><TargetY>200</TargetY>
+          <Properties>
+            <anyType><DisplayName>Name</DisplayName><Value>Internet Boundary</Value></anyType>
+          </Properties>
+        </Value></KeyValueOfguidanyType>
+      </Lines>
+    </DrawingSurfaceModel>
+  </DrawingSurfaceList>
+  <MetaInformation><ThreatModelName>Shop model</ThreatModelName><Owner>Team</Owner></MetaInformation>
+  <ThreatInstances>
+    <KeyValueOfstringThreatpc_P0_PhOB><Key>1</Key><Value>
+      <DrawingSurfaceGuid>surface</DrawingSurfaceGuid>
+      <FlowGuid>login</FlowGuid>
+      <Id>1</Id>
+      <State>Mitigated</State>
+      <Properties>
+        <KeyValueOfstringstring><Key>Title</Key><Value>Spoofing of the user</Value></KeyValueOfstringstring>
+        <KeyValueOfstringstring><Key>UserThreatCategory</Key><Value>Spoofing</Value></KeyValueOfstringstring>
+        <KeyValueOfstringstring><Key>Priority</Key><Value>High</Value></KeyValueOfstringstring>
+        <KeyValueOfstringstring><Key>StateInformation</Key><Value>Multi-factor authentication</Value></KeyValueOfstringstring>
+      </Properties>
+    </Value></KeyValueOfstringThreatpc_P0_PhOB>
+  </ThreatInstances>
+</ThreatModel>"#;
+
+    #[test]
+    fn surfaces_become_diagrams() {
+        let project = from_tm7(MODEL).unwrap();
+        assert_eq!(project.config.title, "Shop model");
+        assert_eq!(project.config.owner, "Team");
+        let diagram = &project.diagrams[0];
+        assert_eq!(diagram.title, "Shop");
+
+        let node = |name: &str| diagram.nodes.iter().find(|node| node.name == name).unwrap();
+        assert_eq!(node("User").type_node, TypeNode::Actor);
+        assert_eq!(node("User").description, "Browser");
+        assert_eq!(node("User").trust_boundary, None);
+        assert_eq!(node("Web").type_node, TypeNode::Process);
+        assert_eq!(node("Web").trust_boundary.as_deref(), Some("DMZ"));
+
+        let login = node("Login");
+        assert_eq!(login.type_node, TypeNode::Flow);
+        assert_eq!(login.source.as_deref(), Some("User"));
+        assert_eq!(login.destination.as_deref(), Some("Web"));
+        assert_eq!(login.protocol.as_deref(), Some("HTTPS"));
+    }
+
+    #[test]
+    fn line_boundaries_are_imported() {
+        let project = from_tm7(MODEL).unwrap();
+        let names: Vec<&str> = project
+            .config
+            .trust_boundaries
+            .iter()
+            .map(|boundary| boundary.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["DMZ", "Internet Boundary"]);
+
+        let login = project.diagrams[0]
+            .nodes
+            .iter()
+            .find(|node| node.name == "Login")
+            .unwrap();
+        assert_eq!(login.trust_boundary.as_deref(), Some("Internet Boundary"));
+    }
+
+    #[test]
+    fn threat_instances_are_attached_to_their_flow() {
+        let project = from_tm7(MODEL).unwrap();
+        let threat = &project.threats[0];
+        assert_eq!(threat.title, "Spoofing of the user");
+        assert_eq!(threat.status, Status::Mitigated);
+        assert_eq!(threat.severity, Severity::High);
+        assert_eq!(threat.mitigation, "Multi-factor authentication");
+
+        let login = project.diagrams[0]
+            .nodes
+            .iter()
+            .find(|node| node.name == "Login")
+            .unwrap();
+        assert_eq!(
+            login.threat_titles().collect::<Vec<_>>(),
+            vec![&threat.title]
+        );
+    }
+
+    #[test]
+    fn other_roots_are_rejected() {
+        assert!(matches!(from_tm7("<Model/>"), Err(ImportError::Model(_))));
+    }
+}
@@ -1,18 +1,24 @@
 use std::fmt::Display;
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::config::config::Config;
+use crate::{
+    config::config::Config,
+    input::threat::{Methodology, Severity, Status},
+};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct InputDiagram {
     pub title: String,
     pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub methodology: Option<Methodology>,
     pub nodes: Vec<Node>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Node {
     pub name: String,
@@ -31,26 +37,76 @@ pub struct Node {
     pub destination: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub asset: Option<String>,
-    pub threats: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_encrypted: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_public_network: Option<bool>,
+    pub threats: Vec<ThreatReference>,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+impl Node {
+    /// Titles of the threats of the node. Template references are replaced by
+    /// titles when the threat library resolves the diagram.
+    pub fn threat_titles(&self) -> impl Iterator<Item = &String> {
+        self.threats.iter().filter_map(|threat| match threat {
+            ThreatReference::Title(title) => Some(title),
+            ThreatReference::Template(_) => None,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(untagged)]
+pub enum ThreatReference {
+    Title(String),
+    Template(TemplateReference),
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateReference {
+    pub template: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<Status>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mitigation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub element: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asset: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub enum TypeNode {
+    Actor,
     Process,
+    Store,
     Flow,
 }
 
 impl Display for TypeNode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match *self {
+            TypeNode::Actor => write!(f, "actor"),
             TypeNode::Process => write!(f, "process"),
+            TypeNode::Store => write!(f, "store"),
             TypeNode::Flow => write!(f, "flow"),
         }
     }
 }
 
 impl InputDiagram {
+    pub fn methodology(&self) -> Methodology {
+        self.methodology.unwrap_or_default()
+    }
+
     pub fn create_child_diagrams(&self, config: &Config) -> Vec<Self> {
         let mut child_list = Vec::new();
 
@@ -63,7 +119,7 @@ impl InputDiagram {
                     .nodes
                     .iter()
                     .filter(|node_process| config_diagram.nodes.contains(&node_process.name))
-                    .map(|node| node.clone())
+                    .cloned()
                     .collect();
                 let nodes_flows: Vec<Node> = self
                     .nodes
@@ -71,13 +127,8 @@ impl InputDiagram {
                     .filter(|node| {
                         if let Some(node_source) = node.source.clone() {
                             if let Some(node_dest) = node.destination.clone() {
-                                if config_diagram.nodes.contains(&node_source)
+                                config_diagram.nodes.contains(&node_source)
                                     && config_diagram.nodes.contains(&node_dest)
-                                {
-                                    true
-                                } else {
-                                    false
-                                }
                             } else {
                                 false
                             }
@@ -85,12 +136,13 @@ impl InputDiagram {
                             false
                         }
                     })
-                    .map(|node| node.clone())
+                    .cloned()
                     .collect();
                 nodes.append(&mut nodes_flows.clone());
                 let child_diagram = InputDiagram {
                     title: config_diagram.name.clone(),
                     description: config_diagram.description.clone(),
+                    methodology: self.methodology,
                     nodes,
                 };
                 child_list.push(child_diagram);
@@ -1,8 +1,12 @@
 use std::fmt::Display;
 
-use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use serde::{
+    de::{self, IntoDeserializer},
+    Deserialize, Deserializer, Serialize,
+};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 pub enum Status {
     Open,
     NotApplicable,
@@ -19,7 +23,7 @@ impl Display for Status {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 pub enum Severity {
     Low,
     Medium,
@@ -36,8 +40,165 @@ impl Display for Severity {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Methodology {
+    #[default]
+    #[serde(rename = "STRIDE")]
+    Stride,
+    #[serde(rename = "LINDDUN")]
+    Linddun,
+    #[serde(rename = "CIA")]
+    Cia,
+    #[serde(rename = "DIE")]
+    Die,
+}
+
+impl Display for Methodology {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Methodology::Stride => write!(f, "STRIDE"),
+            Methodology::Linddun => write!(f, "LINDDUN"),
+            Methodology::Cia => write!(f, "CIA"),
+            Methodology::Die => write!(f, "DIE"),
+        }
+    }
+}
+
+impl Methodology {
+    pub fn thumbnail(&self) -> String {
+        format!(
+            "./public/content/images/thumbnail.{}.jpg",
+            self.to_string().to_lowercase()
+        )
+    }
+}
+
+#[derive(Serialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(untagged)]
 pub enum TypeThreat {
+    Stride(StrideThreat),
+    Linddun(LinddunThreat),
+    Cia(CiaThreat),
+    Die(DieThreat),
+}
+
+impl TypeThreat {
+    pub fn methodology(&self) -> Methodology {
+        match *self {
+            TypeThreat::Stride(_) => Methodology::Stride,
+            TypeThreat::Linddun(_) => Methodology::Linddun,
+            TypeThreat::Cia(_) => Methodology::Cia,
+            TypeThreat::Die(_) => Methodology::Die,
+        }
+    }
+
+    /// Threat type from its name, as written in the YAML files or displayed,
+    /// ignoring case, spaces, dashes and underscores.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let normalize = |value: &str| {
+            value
+                .chars()
+                .filter(|c| c.is_alphanumeric())
+                .collect::<String>()
+                .to_lowercase()
+        };
+        let name = normalize(name);
+        Self::all()
+            .into_iter()
+            .find(|type_threat| normalize(&type_threat.to_string()) == name)
+    }
+
+    /// Threat types of all the methodologies.
+    fn all() -> Vec<Self> {
+        [
+            StrideThreat::Spoofing,
+            StrideThreat::Tampering,
+            StrideThreat::Repudiation,
+            StrideThreat::InformationDisclosure,
+            StrideThreat::DenialOfService,
+            StrideThreat::ElevationOfPrivilege,
+        ]
+        .into_iter()
+        .map(TypeThreat::Stride)
+        .chain(
+            [
+                LinddunThreat::Linkability,
+                LinddunThreat::Identifiability,
+                LinddunThreat::NonRepudiation,
+                LinddunThreat::Detectability,
+                LinddunThreat::DisclosureOfInformation,
+                LinddunThreat::Unawareness,
+                LinddunThreat::NonCompliance,
+            ]
+            .into_iter()
+            .map(TypeThreat::Linddun),
+        )
+        .chain(
+            [
+                CiaThreat::Confidentiality,
+                CiaThreat::Integrity,
+                CiaThreat::Availability,
+            ]
+            .into_iter()
+            .map(TypeThreat::Cia),
+        )
+        .chain(
+            [
+                DieThreat::Distributed,
+                DieThreat::Immutable,
+                DieThreat::Ephemeral,
+            ]
+            .into_iter()
+            .map(TypeThreat::Die),
+        )
+        .collect()
+    }
+}
+
+impl Display for TypeThreat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypeThreat::Stride(type_threat) => type_threat.fmt(f),
+            TypeThreat::Linddun(type_threat) => type_threat.fmt(f),
+            TypeThreat::Cia(type_threat) => type_threat.fmt(f),
+            TypeThreat::Die(type_threat) => type_threat.fmt(f),
+        }
+    }
+}
+
+/// Threat type as written in the YAML files, a type unknown to every
+/// methodology being reported with the list of the valid types.
+impl<'de> Deserialize<'de> for TypeThreat {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        let value = || name.as_str().into_deserializer();
+        StrideThreat::deserialize(value())
+            .map(TypeThreat::Stride)
+            .or_else(|_: de::value::Error| {
+                LinddunThreat::deserialize(value()).map(TypeThreat::Linddun)
+            })
+            .or_else(|_| CiaThreat::deserialize(value()).map(TypeThreat::Cia))
+            .or_else(|_| DieThreat::deserialize(value()).map(TypeThreat::Die))
+            .map_err(|_| {
+                let names: Vec<String> = TypeThreat::all()
+                    .iter()
+                    .filter_map(|type_threat| {
+                        serde_json::to_value(type_threat)
+                            .ok()
+                            .and_then(|value| value.as_str().map(str::to_string))
+                    })
+                    .collect();
+                de::Error::custom(format!(
+                    "unknown threat type `{}`, expected one of {}",
+                    name,
+                    names.join(", ")
+                ))
+            })
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub enum StrideThreat {
     Spoofing,
     Tampering,
     Repudiation,
@@ -46,20 +207,79 @@ pub enum TypeThreat {
     ElevationOfPrivilege,
 }
 
-impl Display for TypeThreat {
+impl Display for StrideThreat {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match *self {
-            TypeThreat::Spoofing => write!(f, "Spoofing"),
-            TypeThreat::Tampering => write!(f, "Tampering"),
-            TypeThreat::Repudiation => write!(f, "Repudiation"),
-            TypeThreat::InformationDisclosure => write!(f, "Information disclosure"),
-            TypeThreat::DenialOfService => write!(f, "Denial of service"),
-            TypeThreat::ElevationOfPrivilege => write!(f, "Elevation of privilege"),
+            StrideThreat::Spoofing => write!(f, "Spoofing"),
+            StrideThreat::Tampering => write!(f, "Tampering"),
+            StrideThreat::Repudiation => write!(f, "Repudiation"),
+            StrideThreat::InformationDisclosure => write!(f, "Information disclosure"),
+            StrideThreat::DenialOfService => write!(f, "Denial of service"),
+            StrideThreat::ElevationOfPrivilege => write!(f, "Elevation of privilege"),
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub enum LinddunThreat {
+    Linkability,
+    Identifiability,
+    NonRepudiation,
+    Detectability,
+    DisclosureOfInformation,
+    Unawareness,
+    NonCompliance,
+}
+
+impl Display for LinddunThreat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            LinddunThreat::Linkability => write!(f, "Linkability"),
+            LinddunThreat::Identifiability => write!(f, "Identifiability"),
+            LinddunThreat::NonRepudiation => write!(f, "Non-repudiation"),
+            LinddunThreat::Detectability => write!(f, "Detectability"),
+            LinddunThreat::DisclosureOfInformation => write!(f, "Disclosure of information"),
+            LinddunThreat::Unawareness => write!(f, "Unawareness"),
+            LinddunThreat::NonCompliance => write!(f, "Non-compliance"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub enum CiaThreat {
+    Confidentiality,
+    Integrity,
+    Availability,
+}
+
+impl Display for CiaThreat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            CiaThreat::Confidentiality => write!(f, "Confidentiality"),
+            CiaThreat::Integrity => write!(f, "Integrity"),
+            CiaThreat::Availability => write!(f, "Availability"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub enum DieThreat {
+    Distributed,
+    Immutable,
+    Ephemeral,
+}
+
+impl Display for DieThreat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            DieThreat::Distributed => write!(f, "Distributed"),
+            DieThreat::Immutable => write!(f, "Immutable"),
+            DieThreat::Ephemeral => write!(f, "Ephemeral"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 pub struct Threat {
     pub title: String,
     pub status: Status,
@@ -69,4 +289,140 @@ pub struct Threat {
     pub description: String,
     pub mitigation: String,
     pub vector: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub references: Option<References>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct References {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cwe: Vec<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub capec: Vec<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub asvs: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub urls: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReferenceKind {
+    Cwe,
+    Capec,
+    Asvs,
+    Url,
+}
+
+impl Display for ReferenceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            ReferenceKind::Cwe => write!(f, "CWE"),
+            ReferenceKind::Capec => write!(f, "CAPEC"),
+            ReferenceKind::Asvs => write!(f, "ASVS"),
+            ReferenceKind::Url => write!(f, "URL"),
+        }
+    }
+}
+
+/// One reference of a threat, with the label displayed in reports and the
+/// link to the reference when one exists.
+#[derive(Debug, Clone)]
+pub struct Reference {
+    pub kind: ReferenceKind,
+    pub label: String,
+    pub url: Option<String>,
+}
+
+impl References {
+    pub fn entries(&self) -> Vec<Reference> {
+        let cwe = self.cwe.iter().map(|id| Reference {
+            kind: ReferenceKind::Cwe,
+            label: format!("CWE-{}", id),
+            url: Some(format!(
+                "https://cwe.mitre.org/data/definitions/{}.html",
+                id
+            )),
+        });
+        let capec = self.capec.iter().map(|id| Reference {
+            kind: ReferenceKind::Capec,
+            label: format!("CAPEC-{}", id),
+            url: Some(format!(
+                "https://capec.mitre.org/data/definitions/{}.html",
+                id
+            )),
+        });
+        let asvs = self.asvs.iter().map(|id| Reference {
+            kind: ReferenceKind::Asvs,
+            label: format!("ASVS V{}", id.trim_start_matches(['V', 'v'])),
+            url: None,
+        });
+        let urls = self.urls.iter().map(|url| Reference {
+            kind: ReferenceKind::Url,
+            label: url.clone(),
+            url: Some(url.clone()),
+        });
+        cwe.chain(capec).chain(asvs).chain(urls).collect()
+    }
+
+    pub fn entries_of_kind(&self, kind: ReferenceKind) -> Vec<Reference> {
+        self.entries()
+            .into_iter()
+            .filter(|reference| reference.kind == kind)
+            .collect()
+    }
+}
+
+impl Threat {
+    /// Threat description followed by its references, one per line, as
+    /// exported to Threat Dragon.
+    pub fn description_with_references(&self) -> String {
+        let entries = self
+            .references
+            .as_ref()
+            .map(|references| references.entries())
+            .unwrap_or_default();
+        if entries.is_empty() {
+            return self.description.clone();
+        }
+        let lines: Vec<String> = entries
+            .iter()
+            .map(|reference| match &reference.url {
+                Some(url) if *url != reference.label => format!("- {}: {}", reference.label, url),
+                _ => format!("- {}", reference.label),
+            })
+            .collect();
+        format!("{}\n\nReferences:\n{}", self.description, lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn type_threat_of_each_methodology() {
+        let types: Vec<TypeThreat> =
+            serde_yaml::from_str("[InformationDisclosure, Linkability, Integrity, Ephemeral]")
+                .unwrap();
+        assert_eq!(
+            types,
+            vec![
+                TypeThreat::Stride(StrideThreat::InformationDisclosure),
+                TypeThreat::Linddun(LinddunThreat::Linkability),
+                TypeThreat::Cia(CiaThreat::Integrity),
+                TypeThreat::Die(DieThreat::Ephemeral),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_type_threat_lists_valid_types() {
+        let error = serde_yaml::from_str::<TypeThreat>("Spofing")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("unknown threat type `Spofing`"), "{}", error);
+        assert!(error.contains("Spoofing, Tampering"), "{}", error);
+        assert!(error.contains("Ephemeral"), "{}", error);
+    }
 }
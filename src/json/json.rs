@@ -0,0 +1,34 @@
+use serde_json::Value;
+
+/// Keys of two JSON objects whose values differ, sorted.
+pub fn changed_fields(old: &Value, new: &Value) -> Vec<String> {
+    let empty = serde_json::Map::new();
+    let old = old.as_object().unwrap_or(&empty);
+    let new = new.as_object().unwrap_or(&empty);
+    let mut fields: Vec<String> = old
+        .keys()
+        .chain(new.keys().filter(|key| !old.contains_key(*key)))
+        .filter(|key| old.get(*key) != new.get(*key))
+        .cloned()
+        .collect();
+    fields.sort();
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn changed_added_and_removed_keys() {
+        assert_eq!(
+            changed_fields(
+                &json!({ "name": "Web", "description": "Shop", "asset": "Orders" }),
+                &json!({ "name": "Web", "description": "Front", "protocol": "HTTPS" }),
+            ),
+            vec!["asset", "description", "protocol"]
+        );
+    }
+}
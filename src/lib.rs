@@ -0,0 +1,65 @@
+//! Generate OWASP Threat Dragon models, reports and exports from a
+//! docs-as-code threat model written in YAML.
+//!
+//! A project is a `config.yaml` file, a `threats.yaml` file and a folder of
+//! diagram files. [`Project`] loads and validates it and builds the
+//! [`ThreatModeling`] read by Threat Dragon. Each report can be written to a
+//! file of an output folder (`create_*` functions) or to any [`std::io::Write`]
+//! sink (`write_*` functions), a `Vec<u8>` keeping it in memory:
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! use threat_dragon_generator::{reports::xls::xls_reports, IssueLevel, Project};
+//!
+//! let project = Project::load_folder(Path::new("my-project"), None).unwrap();
+//! let issues = project.validate();
+//! assert!(issues.iter().all(|issue| issue.level != IssueLevel::Error));
+//!
+//! let threat_modeling = project.threat_modeling();
+//! let json = serde_json::to_vec_pretty(&threat_modeling).unwrap();
+//!
+//! let mut workbook = Vec::new();
+//! let diagram = &project.diagrams[0];
+//! xls_reports::write_report(&mut workbook, diagram, &project.config, &project.threats, None)
+//!     .unwrap();
+//! # let _ = json;
+//! ```
+//!
+//! The formats of the `generate` command implement
+//! [`reports::report_generator::ReportGenerator`], writing every file of the
+//! format to a folder or to a [`reports::output_sink::MemorySink`].
+//!
+//! The modules follow the pipeline: [`input`] and [`config`] hold the YAML
+//! types, [`project`] loads them, [`validation`] checks them,
+//! [`threat_dragon_modeling`] maps them to the Threat Dragon format,
+//! [`render`] draws the diagrams and [`reports`] and [`exports`] write the
+//! outputs, [`xml`] escaping their markup. [`json`] compares the nodes, for
+//! the validation and the diffs. The items re-exported at the root are the
+//! entry points.
+
+#![allow(clippy::module_inception)]
+
+pub mod config;
+pub mod diff;
+pub mod exports;
+pub mod history;
+pub mod imports;
+pub mod input;
+pub mod json;
+pub mod library;
+pub mod lsp;
+pub mod otm;
+pub mod process;
+pub mod project;
+pub mod render;
+pub mod reports;
+pub mod scaffold;
+pub mod schema;
+pub mod threat_dragon_modeling;
+pub mod validation;
+pub mod xml;
+
+pub use project::{project::Project, project_error::ProjectError};
+pub use threat_dragon_modeling::general::ThreatModeling;
+pub use validation::validator::{IssueLevel, ValidationIssue};
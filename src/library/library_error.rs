@@ -0,0 +1,22 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone)]
+pub enum LibraryError {
+    Read(String),
+    Parse(String),
+    UnknownTemplate(String),
+    ElementType(String),
+    TitleCollision(String),
+}
+
+impl Display for LibraryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LibraryError::Read(e) => write!(f, "cannot read threat templates: {}", e),
+            LibraryError::Parse(e) => write!(f, "cannot parse threat templates: {}", e),
+            LibraryError::UnknownTemplate(e) => write!(f, "unknown threat template: {}", e),
+            LibraryError::ElementType(e) => write!(f, "template not applicable: {}", e),
+            LibraryError::TitleCollision(e) => write!(f, "threat title already used: {}", e),
+        }
+    }
+}
@@ -0,0 +1,2 @@
+pub mod library_error;
+pub mod threat_library;
@@ -0,0 +1,221 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::input::{
+    input_diagram::{InputDiagram, Node, TemplateReference, ThreatReference, TypeNode},
+    threat::{References, Severity, Status, Threat, TypeThreat},
+};
+
+use super::library_error::LibraryError;
+
+/// Templates shipped with the binary, see `templates.yaml`.
+const BUILTIN_TEMPLATES: &str = include_str!("templates.yaml");
+
+/// Threat template, the `{element}`, `{protocol}` and `{asset}` parameters of
+/// the text fields are replaced when the template is instantiated on a node.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreatTemplate {
+    pub id: String,
+    pub element_types: Vec<TypeNode>,
+    pub title: String,
+    pub severity: Severity,
+    #[serde(rename = "type")]
+    pub type_field: TypeThreat,
+    pub description: String,
+    pub mitigation: String,
+    pub vector: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub references: Option<References>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ThreatLibrary {
+    templates: BTreeMap<String, ThreatTemplate>,
+}
+
+impl ThreatLibrary {
+    pub fn builtin() -> Self {
+        let templates: Vec<ThreatTemplate> =
+            serde_yaml::from_str(BUILTIN_TEMPLATES).expect("built-in threat templates are valid");
+        let mut library = Self {
+            templates: BTreeMap::new(),
+        };
+        library.extend(templates);
+        library
+    }
+
+    /// Add the templates of a project file, a project template replaces the
+    /// built-in template with the same id.
+    pub fn extend_from_file(&mut self, path: &Path) -> Result<(), LibraryError> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| LibraryError::Read(format!("{}: {}", path.display(), e)))?;
+        let templates: Vec<ThreatTemplate> = serde_yaml::from_str(&content)
+            .map_err(|e| LibraryError::Parse(format!("{}: {}", path.display(), e)))?;
+        self.extend(templates);
+        Ok(())
+    }
+
+    pub fn extend(&mut self, templates: Vec<ThreatTemplate>) {
+        templates.into_iter().for_each(|template| {
+            self.templates.insert(template.id.clone(), template);
+        });
+    }
+
+    pub fn instantiate(
+        &self,
+        reference: &TemplateReference,
+        node: &Node,
+    ) -> Result<Threat, LibraryError> {
+        let template = self
+            .templates
+            .get(&reference.template)
+            .ok_or_else(|| LibraryError::UnknownTemplate(reference.template.clone()))?;
+        if !template.element_types.contains(&node.type_node) {
+            return Err(LibraryError::ElementType(format!(
+                "{} cannot be used on {} {}",
+                template.id, node.type_node, node.name
+            )));
+        }
+
+        let element = reference.element.clone().unwrap_or(node.name.clone());
+        let protocol = reference
+            .protocol
+            .clone()
+            .or(node.protocol.clone())
+            .unwrap_or("an unspecified protocol".to_string());
+        let asset = reference
+            .asset
+            .clone()
+            .or(node.asset.clone())
+            .unwrap_or("the data".to_string());
+        let fill = |text: &String| {
+            text.replace("{element}", &element)
+                .replace("{protocol}", &protocol)
+                .replace("{asset}", &asset)
+        };
+
+        Ok(Threat {
+            title: fill(&template.title),
+            status: reference.status.clone().unwrap_or(Status::Open),
+            severity: reference
+                .severity
+                .clone()
+                .unwrap_or(template.severity.clone()),
+            type_field: template.type_field.clone(),
+            description: fill(&template.description),
+            mitigation: reference
+                .mitigation
+                .clone()
+                .unwrap_or(fill(&template.mitigation)),
+            vector: fill(&template.vector),
+            references: template.references.clone(),
+        })
+    }
+
+    /// Replace the template references of the diagram nodes by the title of
+    /// the instantiated threats, which are added to the threat list. A threat
+    /// of the list with the same title but another content is an error.
+    pub fn resolve(
+        &self,
+        input_diagram: &mut InputDiagram,
+        threats: &mut Vec<Threat>,
+    ) -> Result<(), LibraryError> {
+        for node in input_diagram.nodes.iter_mut() {
+            let mut references = Vec::new();
+            for reference in node.threats.iter() {
+                match reference {
+                    ThreatReference::Title(_) => references.push(reference.clone()),
+                    ThreatReference::Template(template_reference) => {
+                        let threat = self.instantiate(template_reference, node)?;
+                        references.push(ThreatReference::Title(threat.title.clone()));
+                        match threats.iter().find(|known| known.title == threat.title) {
+                            None => threats.push(threat),
+                            // The same threat instantiated on another diagram.
+                            Some(known) if *known == threat => {}
+                            Some(_) => {
+                                return Err(LibraryError::TitleCollision(format!(
+                                "template {} on {} gives \"{}\", the title of a different threat",
+                                template_reference.template, node.name, threat.title
+                            )))
+                            }
+                        }
+                    }
+                }
+            }
+            node.threats = references;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagram(nodes: &str) -> InputDiagram {
+        serde_yaml::from_str(&format!(
+            "title: Diagram\ndescription: \"\"\nnodes:\n{}",
+            nodes
+        ))
+        .unwrap()
+    }
+
+    const OPERATOR: &str = "  - name: Operator
+    type: actor
+    description: \"\"
+    threats:
+      - template: spoofing-external-entity
+";
+
+    #[test]
+    fn builtin_templates_parse() {
+        let library = ThreatLibrary::builtin();
+        assert!(library.templates.contains_key("spoofing-external-entity"));
+        assert!(library
+            .templates
+            .values()
+            .all(|template| !template.element_types.is_empty()));
+    }
+
+    #[test]
+    fn resolve_instantiates_templates() {
+        let library = ThreatLibrary::builtin();
+        let mut threats = Vec::new();
+        let mut input_diagram = diagram(OPERATOR);
+        library.resolve(&mut input_diagram, &mut threats).unwrap();
+        assert_eq!(threats.len(), 1);
+        assert_eq!(threats[0].title, "Spoofing of external entity Operator");
+        assert!(matches!(
+            &input_diagram.nodes[0].threats[0],
+            ThreatReference::Title(title) if *title == threats[0].title
+        ));
+
+        // The same template on another diagram reuses the threat.
+        let mut other_diagram = diagram(OPERATOR);
+        library.resolve(&mut other_diagram, &mut threats).unwrap();
+        assert_eq!(threats.len(), 1);
+    }
+
+    #[test]
+    fn resolve_rejects_title_collision() {
+        let library = ThreatLibrary::builtin();
+        let mut threats: Vec<Threat> = serde_yaml::from_str(
+            "- title: Spoofing of external entity Operator
+  status: Open
+  severity: Low
+  type: Spoofing
+  description: Written by hand
+  mitigation: \"\"
+  vector: \"\"
+",
+        )
+        .unwrap();
+        let error = library
+            .resolve(&mut diagram(OPERATOR), &mut threats)
+            .unwrap_err();
+        assert!(matches!(error, LibraryError::TitleCollision(_)));
+        assert_eq!(threats[0].description, "Written by hand");
+    }
+}
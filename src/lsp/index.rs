@@ -0,0 +1,407 @@
+use std::path::{Path, PathBuf};
+
+use crate::project::include::{normalize, INCLUDE_KEY};
+
+/// Names defined in one file and referenced from another.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SymbolKind {
+    Threat,
+    TrustBoundary,
+    Asset,
+    Node,
+    Component,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileKind {
+    Config,
+    Threats,
+    Diagram,
+}
+
+/// Definition or reference of a name in a YAML file. Positions are zero
+/// based, columns are counted in UTF-16 code units as in LSP.
+#[derive(Debug, Clone)]
+pub struct Occurrence {
+    pub kind: SymbolKind,
+    pub name: String,
+    pub definition: bool,
+    /// Title of the diagram of a node, nodes of different diagrams being
+    /// different symbols, or of the diagram referencing a component.
+    pub scope: Option<String>,
+    pub path: PathBuf,
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Occurrence {
+    pub fn contains(&self, line: usize, character: usize) -> bool {
+        self.line == line && self.start <= character && character <= self.end
+    }
+
+    /// Same name and kind, and same diagram for nodes.
+    pub fn same_symbol(&self, other: &Occurrence) -> bool {
+        self.kind == other.kind
+            && self.name == other.name
+            && (self.kind != SymbolKind::Node || self.scope == other.scope)
+    }
+}
+
+/// File included by a project file, with the place of the include: the kind
+/// of the including file, the section and the list the included items belong
+/// to, and the diagram of its nodes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Include {
+    pub path: PathBuf,
+    pub file_kind: FileKind,
+    section: String,
+    list: Option<SymbolKind>,
+    scope: Option<String>,
+}
+
+/// List of names under a key, such as the `threats` of a node.
+struct List {
+    kind: SymbolKind,
+    key_column: usize,
+    item_column: Option<usize>,
+}
+
+/// Find the definitions and references of a project file. The scan works on
+/// lines, so that it keeps working on files that are being edited and do not
+/// parse yet.
+pub fn scan(path: &Path, file_kind: FileKind, text: &str) -> Vec<Occurrence> {
+    scan_from(path, file_kind, text, None).0
+}
+
+/// Files included by a project file.
+pub fn includes(path: &Path, file_kind: FileKind, text: &str) -> Vec<Include> {
+    scan_from(path, file_kind, text, None).1
+}
+
+/// Scan an included file as if its items were written at the place of the
+/// include, with the files it includes in turn.
+pub fn scan_included(include: &Include, text: &str) -> (Vec<Occurrence>, Vec<Include>) {
+    scan_from(&include.path, include.file_kind, text, Some(include))
+}
+
+fn scan_from(
+    path: &Path,
+    file_kind: FileKind,
+    text: &str,
+    context: Option<&Include>,
+) -> (Vec<Occurrence>, Vec<Include>) {
+    let lines: Vec<&str> = text.lines().collect();
+    let title = match context {
+        Some(context) => context.scope.clone(),
+        None => (file_kind == FileKind::Diagram)
+            .then(|| {
+                lines.iter().enumerate().find_map(|(index, line)| {
+                    let (key, value) = key_value(line, 0)?;
+                    (key == "title").then(|| value_at(index, line, value).0)
+                })
+            })
+            .flatten(),
+    };
+    let folder = path.parent().unwrap_or(Path::new(""));
+    let mut includes = Vec::new();
+    let mut include = |files: Vec<Value>, section: &str, list: Option<SymbolKind>| {
+        for (file, ..) in files {
+            let found = Include {
+                path: normalize(&folder.join(file)),
+                file_kind,
+                section: section.to_string(),
+                list,
+                scope: title.clone(),
+            };
+            if !includes.contains(&found) {
+                includes.push(found);
+            }
+        }
+    };
+
+    let mut occurrences = Vec::new();
+    let mut push = |kind: SymbolKind,
+                    definition: bool,
+                    scope: Option<String>,
+                    (name, line, start, end): Value| {
+        occurrences.push(Occurrence {
+            kind,
+            name,
+            definition,
+            scope,
+            path: path.to_path_buf(),
+            line,
+            start,
+            end,
+        })
+    };
+
+    let mut section = context.map_or("", |context| context.section.as_str());
+    let mut list: Option<List> = context.and_then(|context| {
+        Some(List {
+            kind: context.list?,
+            key_column: 0,
+            item_column: None,
+        })
+    });
+    // Block list of included files, with the column of its key and the
+    // section and list of the included items.
+    let mut include_list: Option<(usize, String, Option<SymbolKind>)> = None;
+    // Child diagram of the configuration: its parent and its node references,
+    // scoped once the whole item is read.
+    let mut child_item: Option<usize> = None;
+    let mut child_parent: Option<String> = None;
+    let mut child_nodes: Vec<Value> = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let column = line.len() - trimmed.len();
+        let dash = trimmed.starts_with("- ") || trimmed == "-";
+        let content_column = if trimmed == "-" {
+            line.len()
+        } else {
+            item_content_column(line)
+        };
+
+        if let Some((key_column, include_section, include_kind)) = &include_list {
+            if dash && column >= *key_column {
+                let file = value_at(index, line, content_column);
+                include(vec![file], include_section, *include_kind);
+                continue;
+            }
+            include_list = None;
+        }
+
+        if let Some(current) = list.as_mut() {
+            let item_column = *current.item_column.get_or_insert(column);
+            if dash && column >= current.key_column && column == item_column {
+                if let Some((INCLUDE_KEY, value)) = key_value(line, content_column) {
+                    include(
+                        include_files(index, line, value),
+                        section,
+                        Some(current.kind),
+                    );
+                } else if key_value(line, content_column).is_none() {
+                    let value = value_at(index, line, content_column);
+                    if current.kind == SymbolKind::Node && section == "diagrams" {
+                        child_nodes.push(value);
+                    } else {
+                        let scope = (current.kind == SymbolKind::Node)
+                            .then(|| title.clone())
+                            .flatten();
+                        push(current.kind, false, scope, value);
+                    }
+                }
+                continue;
+            }
+            if column > item_column {
+                continue;
+            }
+            list = None;
+        }
+
+        if let Some((INCLUDE_KEY, value)) = key_value(line, content_column) {
+            // The keys of a file included at the top level make its sections.
+            let include_section = if column == 0 && !dash { "" } else { section };
+            if line[value..].trim().is_empty() {
+                include_list = Some((content_column, include_section.to_string(), None));
+            } else {
+                include(include_files(index, line, value), include_section, None);
+            }
+            continue;
+        }
+
+        if column == 0 && !dash {
+            if section == "diagrams" {
+                flush_child(&mut child_parent, &mut child_nodes, &mut push);
+            }
+            section = key_value(line, 0).map(|(key, _)| key).unwrap_or("");
+            continue;
+        }
+        if section == "diagrams" && dash {
+            let item = *child_item.get_or_insert(column);
+            if column == item {
+                flush_child(&mut child_parent, &mut child_nodes, &mut push);
+            }
+        }
+
+        let Some((key, value)) = key_value(line, content_column) else {
+            continue;
+        };
+        let inline = line[value..].trim_start().starts_with('[');
+        let symbol = match (file_kind, section, key) {
+            (FileKind::Threats, _, "title") => Some((SymbolKind::Threat, true)),
+            (FileKind::Config, "trustBoundaries", "name") => {
+                Some((SymbolKind::TrustBoundary, true))
+            }
+            (FileKind::Config, "assets", "name") => Some((SymbolKind::Asset, true)),
+            (FileKind::Config, "components", "name") => Some((SymbolKind::Component, true)),
+            (FileKind::Config, "components", "trustBoundary") => {
+                Some((SymbolKind::TrustBoundary, false))
+            }
+            (FileKind::Config, "components", "asset") => Some((SymbolKind::Asset, false)),
+            (FileKind::Config, "components", "threats") => Some((SymbolKind::Threat, false)),
+            (FileKind::Config, "diagrams", "parent") => {
+                child_parent = Some(value_at(index, line, value).0);
+                None
+            }
+            (FileKind::Config, "diagrams", "nodes") => Some((SymbolKind::Node, false)),
+            (FileKind::Diagram, "nodes", "name") => Some((SymbolKind::Node, true)),
+            (FileKind::Diagram, "nodes", "component") => Some((SymbolKind::Component, false)),
+            (FileKind::Diagram, "nodes", "trustBoundary") => {
+                Some((SymbolKind::TrustBoundary, false))
+            }
+            (FileKind::Diagram, "nodes", "asset") => Some((SymbolKind::Asset, false)),
+            (FileKind::Diagram, "nodes", "source" | "destination") => {
+                Some((SymbolKind::Node, false))
+            }
+            (FileKind::Diagram, "nodes", "threats") => Some((SymbolKind::Threat, false)),
+            _ => None,
+        };
+        let Some((kind, definition)) = symbol else {
+            continue;
+        };
+        let is_list = matches!(key, "threats" | "nodes");
+        let scope = matches!(kind, SymbolKind::Node | SymbolKind::Component)
+            .then(|| title.clone())
+            .flatten();
+        if is_list && inline {
+            for value in inline_values(index, line, value) {
+                if section == "diagrams" {
+                    child_nodes.push(value);
+                } else {
+                    push(kind, false, scope.clone(), value);
+                }
+            }
+        } else if is_list {
+            list = Some(List {
+                kind,
+                key_column: content_column,
+                item_column: None,
+            });
+        } else {
+            push(kind, definition, scope, value_at(index, line, value));
+        }
+    }
+    if section == "diagrams" {
+        flush_child(&mut child_parent, &mut child_nodes, &mut push);
+    }
+    (occurrences, includes)
+}
+
+/// Files of an `include` value, a file or an inline list of files.
+fn include_files(index: usize, line: &str, value: usize) -> Vec<Value> {
+    if line[value..].trim_start().starts_with('[') {
+        inline_values(index, line, value)
+    } else {
+        vec![value_at(index, line, value)]
+    }
+}
+
+type Value = (String, usize, usize, usize);
+
+/// Scope the node references of a child diagram with its parent.
+fn flush_child(
+    parent: &mut Option<String>,
+    nodes: &mut Vec<Value>,
+    push: &mut impl FnMut(SymbolKind, bool, Option<String>, Value),
+) {
+    for node in nodes.drain(..) {
+        push(SymbolKind::Node, false, parent.clone(), node);
+    }
+    *parent = None;
+}
+
+/// Column of the content of a line, after the indentation and the dashes of
+/// sequence items.
+fn item_content_column(line: &str) -> usize {
+    let mut column = line.len() - line.trim_start().len();
+    while line[column..].starts_with("- ") {
+        column += 2;
+        column += line[column..].len() - line[column..].trim_start().len();
+    }
+    column
+}
+
+/// Key of a `key: value` line from `column`, with the byte offset of the
+/// value.
+fn key_value(line: &str, column: usize) -> Option<(&str, usize)> {
+    let content = line.get(column..)?;
+    let colon = content.find(':')?;
+    let key = &content[..colon];
+    if key.is_empty()
+        || !key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return None;
+    }
+    let rest = &content[colon + 1..];
+    if !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+    let value = column + colon + 1;
+    Some((
+        key,
+        value + (line[value..].len() - line[value..].trim_start().len()),
+    ))
+}
+
+/// Scalar starting at byte `start`, without comment and quotes, with its
+/// position.
+fn value_at(index: usize, line: &str, start: usize) -> Value {
+    let rest = &line[start..];
+    let raw = match rest.chars().next() {
+        Some(quote @ ('"' | '\'')) => match rest[1..].find(quote) {
+            Some(end) => &rest[..end + 2],
+            None => rest,
+        },
+        _ => rest.split(" #").next().unwrap_or_default(),
+    }
+    .trim_end();
+    let (offset, value) = match raw.chars().next() {
+        Some(quote @ ('"' | '\'')) => (1, raw[1..].trim_end_matches(quote)),
+        _ => (0, raw),
+    };
+    let begin = start + offset;
+    (
+        value.to_string(),
+        index,
+        utf16_column(line, begin),
+        utf16_column(line, begin + value.len()),
+    )
+}
+
+/// Items of a flow sequence such as `["a", "b"]` starting at byte `start`.
+fn inline_values(index: usize, line: &str, start: usize) -> Vec<Value> {
+    let Some(end) = line[start..].find(']') else {
+        return Vec::new();
+    };
+    let mut values = Vec::new();
+    let mut item = start + 1;
+    for part in line[start + 1..start + end].split(',') {
+        let value = part.trim();
+        if !value.is_empty() {
+            let begin = item + (part.len() - part.trim_start().len());
+            let (offset, value) = match value.chars().next() {
+                Some(quote @ ('"' | '\'')) => (1, value[1..].trim_end_matches(quote)),
+                _ => (0, value),
+            };
+            values.push((
+                value.to_string(),
+                index,
+                utf16_column(line, begin + offset),
+                utf16_column(line, begin + offset + value.len()),
+            ));
+        }
+        item += part.len() + 1;
+    }
+    values
+}
+
+fn utf16_column(line: &str, byte: usize) -> usize {
+    line[..byte.min(line.len())].encode_utf16().count()
+}
@@ -0,0 +1,1088 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::{
+    config::config::Config,
+    input::{input_diagram::InputDiagram, threat::Threat},
+    project::{
+        component,
+        include::{self, normalize},
+        project::{Project, CONFIG_FILE_NAME, DIAGRAMS_DIRECTORY_NAME, THREATS_FILE_NAME},
+    },
+    validation::validator::IssueLevel,
+};
+
+use super::index::{self, FileKind, Include, Occurrence, SymbolKind};
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const SEVERITY_ERROR: u8 = 1;
+const SEVERITY_WARNING: u8 = 2;
+const COMPLETION_VALUE: u8 = 12;
+
+/// Language server of a project folder, over stdio. The open documents are
+/// used in place of the files on disk.
+pub struct Server {
+    root: PathBuf,
+    template_path: Option<PathBuf>,
+    documents: HashMap<PathBuf, String>,
+    published: HashSet<PathBuf>,
+    shutdown: bool,
+}
+
+impl Server {
+    pub fn new(root: PathBuf, template_path: Option<PathBuf>) -> Self {
+        Self {
+            root: absolute(&root),
+            template_path,
+            documents: HashMap::new(),
+            published: HashSet::new(),
+            shutdown: false,
+        }
+    }
+
+    /// Serve over stdio until the `exit` notification, returning whether
+    /// `shutdown` was requested before.
+    pub fn run(&mut self) -> io::Result<bool> {
+        self.serve(&mut io::stdin().lock(), &mut io::stdout().lock())
+    }
+
+    /// Serve the messages of `input` until the `exit` notification, writing
+    /// the responses and the notifications to `output`.
+    pub fn serve(&mut self, input: &mut impl BufRead, output: &mut impl Write) -> io::Result<bool> {
+        while let Some(message) = read_message(input)? {
+            // A malformed message has no id to answer, the server goes on
+            // with the next one.
+            let message = match message {
+                Ok(message) => message,
+                Err(error) => {
+                    write_message(
+                        output,
+                        &json!({
+                            "jsonrpc": "2.0",
+                            "id": Value::Null,
+                            "error": { "code": PARSE_ERROR, "message": format!("{}", error) },
+                        }),
+                    )?;
+                    continue;
+                }
+            };
+            let method = message["method"].as_str().unwrap_or_default();
+            if method == "exit" {
+                return Ok(self.shutdown);
+            }
+            match message.get("id") {
+                Some(id) => {
+                    let response = match self.request(method, &message["params"]) {
+                        Some(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                        None => json!({
+                            "jsonrpc": "2.0",
+                            "id": id,
+                            "error": { "code": METHOD_NOT_FOUND, "message": format!("unknown method {}", method) },
+                        }),
+                    };
+                    write_message(output, &response)?;
+                }
+                None => self.notification(method, &message["params"], output)?,
+            }
+        }
+        Ok(self.shutdown)
+    }
+
+    fn request(&mut self, method: &str, params: &Value) -> Option<Value> {
+        match method {
+            "initialize" => {
+                let root = params["rootUri"]
+                    .as_str()
+                    .and_then(path_from_uri)
+                    .or_else(|| params["rootPath"].as_str().map(PathBuf::from));
+                if let Some(root) = root {
+                    self.root = root;
+                }
+                Some(json!({
+                    "capabilities": {
+                        "textDocumentSync": 1,
+                        "definitionProvider": true,
+                        "referencesProvider": true,
+                        "renameProvider": true,
+                        "completionProvider": { "triggerCharacters": [" ", "\"", "["] },
+                    },
+                    "serverInfo": { "name": env!("CARGO_PKG_NAME"), "version": env!("CARGO_PKG_VERSION") },
+                }))
+            }
+            "shutdown" => {
+                self.shutdown = true;
+                Some(Value::Null)
+            }
+            "textDocument/definition" => Some(self.definition(params)),
+            "textDocument/references" => Some(self.references(params)),
+            "textDocument/rename" => Some(self.rename(params)),
+            "textDocument/completion" => Some(self.completion(params)),
+            _ => None,
+        }
+    }
+
+    fn notification(
+        &mut self,
+        method: &str,
+        params: &Value,
+        output: &mut impl Write,
+    ) -> io::Result<()> {
+        let path = params["textDocument"]["uri"]
+            .as_str()
+            .and_then(path_from_uri);
+        match (method, path) {
+            ("initialized", _) => return self.publish_diagnostics(output),
+            ("textDocument/didOpen", Some(path)) => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.documents.insert(path, text.to_string());
+            }
+            ("textDocument/didChange", Some(path)) => {
+                // Full synchronisation, the last change holds the document.
+                if let Some(text) = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str())
+                {
+                    self.documents.insert(path, text.to_string());
+                }
+            }
+            ("textDocument/didClose", Some(path)) => {
+                self.documents.remove(&path);
+            }
+            ("textDocument/didSave", _) => {}
+            _ => return Ok(()),
+        }
+        self.publish_diagnostics(output)
+    }
+
+    fn file_kind(&self, path: &Path) -> Option<FileKind> {
+        if path == self.root.join(CONFIG_FILE_NAME) {
+            Some(FileKind::Config)
+        } else if path == self.root.join(THREATS_FILE_NAME) {
+            Some(FileKind::Threats)
+        } else if path.parent() == Some(&self.root.join(DIAGRAMS_DIRECTORY_NAME)) {
+            Some(FileKind::Diagram)
+        } else {
+            None
+        }
+    }
+
+    /// Files of the project with their kind, diagrams sorted by file name.
+    fn files(&self) -> Vec<(PathBuf, FileKind)> {
+        let mut diagrams: Vec<PathBuf> = fs::read_dir(self.root.join(DIAGRAMS_DIRECTORY_NAME))
+            .map(|entries| {
+                entries
+                    .filter_map(Result::ok)
+                    .map(|entry| entry.path())
+                    .filter(|path| path.is_file())
+                    .collect()
+            })
+            .unwrap_or_default();
+        diagrams.extend(
+            self.documents
+                .keys()
+                .filter(|path| self.file_kind(path) == Some(FileKind::Diagram))
+                .filter(|path| !path.exists())
+                .cloned(),
+        );
+        diagrams.sort();
+        let mut files = vec![
+            (self.root.join(CONFIG_FILE_NAME), FileKind::Config),
+            (self.root.join(THREATS_FILE_NAME), FileKind::Threats),
+        ];
+        files.extend(diagrams.into_iter().map(|path| (path, FileKind::Diagram)));
+        files
+    }
+
+    /// Files included by the project files, followed through the included
+    /// files, with the place of their include.
+    fn included(&self) -> Vec<Include> {
+        let mut pending: Vec<Include> = self
+            .files()
+            .into_iter()
+            .flat_map(|(path, kind)| {
+                index::includes(&path, kind, &self.text(&path).unwrap_or_default())
+            })
+            .collect();
+        let mut included: Vec<Include> = Vec::new();
+        while !pending.is_empty() {
+            let include = pending.remove(0);
+            if included.contains(&include) {
+                continue;
+            }
+            let text = self.text(&include.path).unwrap_or_default();
+            pending.extend(index::scan_included(&include, &text).1);
+            included.push(include);
+        }
+        included
+    }
+
+    fn text(&self, path: &Path) -> Option<String> {
+        self.documents
+            .get(path)
+            .cloned()
+            .or_else(|| fs::read_to_string(path).ok())
+    }
+
+    /// Occurrences of the project files and of the files they include.
+    fn scanned_occurrences(&self) -> Vec<Occurrence> {
+        let mut occurrences: Vec<Occurrence> = self
+            .files()
+            .into_iter()
+            .flat_map(|(path, kind)| {
+                let text = self.text(&path).unwrap_or_default();
+                index::scan(&path, kind, &text)
+            })
+            .collect();
+        occurrences.extend(self.included().iter().flat_map(|include| {
+            let text = self.text(&include.path).unwrap_or_default();
+            index::scan_included(include, &text).0
+        }));
+        occurrences
+    }
+
+    fn occurrences(&self) -> Vec<Occurrence> {
+        let mut occurrences = self.scanned_occurrences();
+        // A node expanded from a component has no definition in its diagram,
+        // the references to it are references to the component.
+        let expanded: Vec<(Option<String>, String)> = occurrences
+            .iter()
+            .filter(|occurrence| occurrence.kind == SymbolKind::Component)
+            .filter(|occurrence| occurrence.scope.is_some())
+            .map(|occurrence| (occurrence.scope.clone(), occurrence.name.clone()))
+            .collect();
+        let defined: Vec<(Option<String>, String)> = occurrences
+            .iter()
+            .filter(|occurrence| occurrence.kind == SymbolKind::Node && occurrence.definition)
+            .map(|occurrence| (occurrence.scope.clone(), occurrence.name.clone()))
+            .collect();
+        occurrences
+            .iter_mut()
+            .filter(|occurrence| occurrence.kind == SymbolKind::Node && !occurrence.definition)
+            .for_each(|occurrence| {
+                let node = (occurrence.scope.clone(), occurrence.name.clone());
+                if expanded.contains(&node) && !defined.contains(&node) {
+                    occurrence.kind = SymbolKind::Component;
+                }
+            });
+        occurrences
+    }
+
+    /// Occurrences of the project and the index of the one at the position of
+    /// the request.
+    fn at_position(&self, params: &Value) -> (Vec<Occurrence>, Option<usize>) {
+        let occurrences = self.occurrences();
+        let path = params["textDocument"]["uri"]
+            .as_str()
+            .and_then(path_from_uri);
+        let line = params["position"]["line"].as_u64().unwrap_or_default() as usize;
+        let character = params["position"]["character"].as_u64().unwrap_or_default() as usize;
+        let found = occurrences.iter().position(|occurrence| {
+            Some(&occurrence.path) == path.as_ref() && occurrence.contains(line, character)
+        });
+        (occurrences, found)
+    }
+
+    fn definition(&self, params: &Value) -> Value {
+        let (occurrences, found) = self.at_position(params);
+        let Some(found) = found else {
+            return Value::Null;
+        };
+        let symbol = &occurrences[found];
+        json!(occurrences
+            .iter()
+            .filter(|occurrence| occurrence.definition && occurrence.same_symbol(symbol))
+            .map(location)
+            .collect::<Vec<_>>())
+    }
+
+    fn references(&self, params: &Value) -> Value {
+        let (occurrences, found) = self.at_position(params);
+        let Some(found) = found else {
+            return Value::Null;
+        };
+        let symbol = &occurrences[found];
+        let declaration = params["context"]["includeDeclaration"]
+            .as_bool()
+            .unwrap_or(true);
+        json!(occurrences
+            .iter()
+            .filter(|occurrence| occurrence.same_symbol(symbol))
+            .filter(|occurrence| declaration || !occurrence.definition)
+            .map(location)
+            .collect::<Vec<_>>())
+    }
+
+    /// Rename the definition and every reference, in all the project files.
+    fn rename(&self, params: &Value) -> Value {
+        let (occurrences, found) = self.at_position(params);
+        let (Some(found), Some(new_name)) = (found, params["newName"].as_str()) else {
+            return Value::Null;
+        };
+        let symbol = &occurrences[found];
+        let mut changes: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+        occurrences
+            .iter()
+            .filter(|occurrence| occurrence.same_symbol(symbol))
+            .for_each(|occurrence| {
+                changes
+                    .entry(uri_from_path(&occurrence.path))
+                    .or_default()
+                    .push(json!({ "range": range(occurrence), "newText": new_name }));
+            });
+        json!({ "changes": changes })
+    }
+
+    /// Names defined for the reference at the position, nodes being limited
+    /// to the ones of the same diagram, including the components it
+    /// references.
+    fn completion(&self, params: &Value) -> Value {
+        let (occurrences, found) = self.at_position(params);
+        let Some(found) = found else {
+            return json!([]);
+        };
+        let slot = &occurrences[found];
+        if slot.definition {
+            return json!([]);
+        }
+        let mut names: Vec<&String> = occurrences
+            .iter()
+            .filter(|occurrence| {
+                let defined = occurrence.definition
+                    && occurrence.kind == slot.kind
+                    && (slot.kind != SymbolKind::Node || occurrence.scope == slot.scope);
+                let expanded = slot.kind == SymbolKind::Node
+                    && occurrence.kind == SymbolKind::Component
+                    && occurrence.scope == slot.scope;
+                defined || expanded
+            })
+            .map(|occurrence| &occurrence.name)
+            .collect();
+        names.sort();
+        names.dedup();
+        json!(names
+            .into_iter()
+            .map(|name| json!({
+                "label": name,
+                "kind": COMPLETION_VALUE,
+                "detail": kind_label(slot.kind),
+            }))
+            .collect::<Vec<_>>())
+    }
+
+    /// Parse errors of the files, or the findings of the validator once every
+    /// file parses.
+    fn diagnostics(&self) -> HashMap<PathBuf, Vec<Value>> {
+        let files = self.files();
+        let mut diagnostics: HashMap<PathBuf, Vec<Value>> = files
+            .iter()
+            .map(|(path, _)| (path.clone(), Vec::new()))
+            .collect();
+        let texts: Vec<(PathBuf, FileKind, String)> = files
+            .into_iter()
+            .filter_map(|(path, kind)| self.text(&path).map(|text| (path, kind, text)))
+            .collect();
+
+        let mut parse_errors = false;
+        for (path, kind, text) in texts.iter() {
+            let error = match kind {
+                FileKind::Config => parse_error::<Config>(text),
+                FileKind::Threats => parse_error::<Vec<Threat>>(text),
+                FileKind::Diagram => parse_error::<InputDiagram>(text),
+            };
+            if let Some(diagnostic) = error {
+                parse_errors = true;
+                diagnostics
+                    .entry(path.clone())
+                    .or_default()
+                    .push(diagnostic);
+            }
+        }
+        let source = |kind: FileKind| {
+            texts
+                .iter()
+                .find(|(_, file_kind, _)| *file_kind == kind)
+                .map(|(path, _, text)| (path.as_path(), text.as_str()))
+        };
+        let (Some(config), Some(threats)) = (source(FileKind::Config), source(FileKind::Threats))
+        else {
+            return diagnostics;
+        };
+        if parse_errors {
+            return diagnostics;
+        }
+
+        let diagrams: Vec<(PathBuf, String)> = texts
+            .iter()
+            .filter(|(_, kind, _)| *kind == FileKind::Diagram)
+            .map(|(path, _, text)| (path.clone(), text.clone()))
+            .collect();
+        let project =
+            match Project::parse(config, threats, &diagrams, self.template_path.as_deref()) {
+                Ok(project) => project,
+                Err(error) => {
+                    let message = error.to_string();
+                    let path = diagrams
+                        .iter()
+                        .map(|(path, _)| path)
+                        .find(|path| message.contains(&path.display().to_string()))
+                        .cloned()
+                        .unwrap_or_else(|| config.0.to_path_buf());
+                    diagnostics.entry(path).or_default().push(diagnostic(
+                        0,
+                        0,
+                        0,
+                        SEVERITY_ERROR,
+                        None,
+                        &message,
+                    ));
+                    return diagnostics;
+                }
+            };
+
+        let occurrences = self.scanned_occurrences();
+        let issues = project.findings();
+        for issue in issues {
+            let path = match &issue.node {
+                Some(node) => project.node_path(&issue.diagram, node),
+                None => project.diagram_path(&issue.diagram),
+            };
+            let Some(path) = path.map(normalize) else {
+                continue;
+            };
+            // Nodes expanded from a component are found at their reference.
+            let node = occurrences.iter().find(|occurrence| {
+                occurrence.path == path
+                    && (occurrence.kind == SymbolKind::Node && occurrence.definition
+                        || occurrence.kind == SymbolKind::Component)
+                    && Some(&occurrence.name) == issue.node.as_ref()
+            });
+            let (line, start, end) = node
+                .map(|node| (node.line, node.start, node.end))
+                .unwrap_or_default();
+            let severity = match issue.level {
+                IssueLevel::Error => SEVERITY_ERROR,
+                IssueLevel::Warning => SEVERITY_WARNING,
+            };
+            // Child diagrams are reported in the file of their parent, and
+            // the nodes of an included file may belong to several diagrams.
+            let message = if issue.diagram == project_title(&project, &path) {
+                issue.message.clone()
+            } else {
+                format!("{}: {}", issue.diagram, issue.message)
+            };
+            diagnostics
+                .entry(path.to_path_buf())
+                .or_default()
+                .push(diagnostic(
+                    line,
+                    start,
+                    end,
+                    severity,
+                    Some(&issue.rule),
+                    &message,
+                ));
+        }
+        diagnostics
+    }
+
+    fn publish_diagnostics(&mut self, output: &mut impl Write) -> io::Result<()> {
+        let mut diagnostics = self.diagnostics();
+        // Files that had diagnostics and left the project are cleared.
+        for path in self.published.drain() {
+            diagnostics.entry(path).or_default();
+        }
+        for (path, items) in diagnostics {
+            if !items.is_empty() {
+                self.published.insert(path.clone());
+            }
+            write_message(
+                output,
+                &json!({
+                    "jsonrpc": "2.0",
+                    "method": "textDocument/publishDiagnostics",
+                    "params": { "uri": uri_from_path(&path), "diagnostics": items },
+                }),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Title of the diagram defined in a file.
+fn project_title<'a>(project: &'a Project, path: &Path) -> &'a str {
+    project
+        .diagram_paths
+        .iter()
+        .find(|(_, diagram_path)| diagram_path.as_path() == path)
+        .map(|(title, _)| title.as_str())
+        .unwrap_or_default()
+}
+
+fn parse_error<T: DeserializeOwned>(text: &str) -> Option<Value> {
+    // Files with includes or component references are only checked once
+    // resolved, when the project is parsed.
+    let error = match serde_yaml::from_str::<serde_yaml::Value>(text) {
+        Ok(document)
+            if include::has_include(&document) || component::has_component_reference(&document) =>
+        {
+            return None
+        }
+        Ok(_) => serde_yaml::from_str::<T>(text).err()?,
+        Err(error) => error,
+    };
+    let (line, column) = error
+        .location()
+        .map(|location| {
+            (
+                location.line().saturating_sub(1),
+                location.column().saturating_sub(1),
+            )
+        })
+        .unwrap_or_default();
+    Some(diagnostic(
+        line,
+        column,
+        column,
+        SEVERITY_ERROR,
+        None,
+        &error.to_string(),
+    ))
+}
+
+fn diagnostic(
+    line: usize,
+    start: usize,
+    end: usize,
+    severity: u8,
+    code: Option<&str>,
+    message: &str,
+) -> Value {
+    let mut diagnostic = json!({
+        "range": {
+            "start": { "line": line, "character": start },
+            "end": { "line": line, "character": end },
+        },
+        "severity": severity,
+        "source": env!("CARGO_PKG_NAME"),
+        "message": message,
+    });
+    if let Some(code) = code {
+        diagnostic["code"] = json!(code);
+    }
+    diagnostic
+}
+
+fn kind_label(kind: SymbolKind) -> &'static str {
+    match kind {
+        SymbolKind::Threat => "threat",
+        SymbolKind::TrustBoundary => "trust boundary",
+        SymbolKind::Asset => "asset",
+        SymbolKind::Node => "node",
+        SymbolKind::Component => "component",
+    }
+}
+
+fn range(occurrence: &Occurrence) -> Value {
+    json!({
+        "start": { "line": occurrence.line, "character": occurrence.start },
+        "end": { "line": occurrence.line, "character": occurrence.end },
+    })
+}
+
+fn location(occurrence: &Occurrence) -> Value {
+    json!({ "uri": uri_from_path(&occurrence.path), "range": range(occurrence) })
+}
+
+/// Read a message framed by a `Content-Length` header, `None` at the end of
+/// the input. A body that is not JSON is an error of the message only.
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<serde_json::Result<Value>>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let Some(length) = length else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "missing Content-Length header",
+        ));
+    };
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    Ok(Some(serde_json::from_slice(&body)))
+}
+
+fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn path_from_uri(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match (bytes[index], path.get(index + 1..index + 3)) {
+            (b'%', Some(hex)) if u8::from_str_radix(hex, 16).is_ok() => {
+                decoded.push(u8::from_str_radix(hex, 16).ok()?);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).ok().map(PathBuf::from)
+}
+
+fn uri_from_path(path: &Path) -> String {
+    let mut uri = "file://".to_string();
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    const ROOT: &str = "/threat-model";
+
+    const CONFIG: &str = r#"threatDragonVersion: "2.2.0"
+title: "Shop"
+owner: "Team"
+description: ""
+trustBoundaries:
+  - name: Internet
+    description: ""
+    limitOfAccess: ""
+    levelOfAuthorization: ""
+assets: []
+components:
+  - name: Broker
+    type: process
+    description: "Message broker"
+    threats: []
+diagrams: []
+"#;
+
+    const THREATS: &str = r#"- title: "Spoofing of the user"
+  status: "Open"
+  severity: "High"
+  type: "Spoofing"
+  description: ""
+  mitigation: ""
+  vector: ""
+"#;
+
+    const DIAGRAM: &str = r#"title: "Shop"
+description: ""
+nodes:
+  - name: User
+    type: actor
+    description: ""
+    trustBoundary: Internet
+    threats: []
+  - name: Web
+    type: process
+    description: ""
+    threats: []
+  - name: Login
+    type: flow
+    description: ""
+    source: User
+    destination: Web
+    threats:
+      - "Spoofing of the user"
+"#;
+
+    const ORDERS: &str = r#"title: "Orders"
+description: ""
+nodes:
+  - name: Web
+    type: process
+    description: ""
+    threats: []
+  - component: Broker
+  - name: Publish
+    type: flow
+    description: ""
+    source: Web
+    destination: Broker
+    threats: []
+"#;
+
+    fn uri(file: &str) -> String {
+        uri_from_path(&Path::new(ROOT).join(file))
+    }
+
+    fn did_open(file: &str, text: &str) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": { "textDocument": { "uri": uri(file), "languageId": "yaml", "version": 1, "text": text } },
+        })
+    }
+
+    /// Position of the first occurrence of `needle` in `text`.
+    fn position(text: &str, needle: &str) -> Value {
+        let (line, character) = text
+            .lines()
+            .enumerate()
+            .find_map(|(line, content)| content.find(needle).map(|column| (line, column)))
+            .unwrap();
+        json!({ "line": line, "character": character })
+    }
+
+    /// Open the project files in memory, send the requests and return the
+    /// messages written by the server.
+    fn exchange(diagram: &str, requests: Vec<Value>) -> Vec<Value> {
+        let mut messages = vec![
+            did_open("config.yaml", CONFIG),
+            did_open("threats.yaml", THREATS),
+            did_open("diagrams/shop.yaml", diagram),
+        ];
+        messages.extend(requests);
+        serve_messages(Path::new(ROOT), messages)
+    }
+
+    fn serve_messages(root: &Path, mut messages: Vec<Value>) -> Vec<Value> {
+        messages.push(json!({ "jsonrpc": "2.0", "id": 0, "method": "shutdown" }));
+        messages.push(json!({ "jsonrpc": "2.0", "method": "exit" }));
+        let bodies: Vec<String> = messages.iter().map(Value::to_string).collect();
+        serve_bodies(root, &bodies)
+    }
+
+    fn serve_bodies(root: &Path, bodies: &[String]) -> Vec<Value> {
+        let input: String = bodies
+            .iter()
+            .map(|body| format!("Content-Length: {}\r\n\r\n{}", body.len(), body))
+            .collect();
+
+        let mut server = Server::new(root.to_path_buf(), None);
+        let mut output = Vec::new();
+        assert!(server.serve(&mut input.as_bytes(), &mut output).unwrap());
+        let mut output = output.as_slice();
+        let mut responses = Vec::new();
+        while let Some(message) = read_message(&mut output).unwrap() {
+            responses.push(message.unwrap());
+        }
+        responses
+    }
+
+    fn request(id: u64, method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+    }
+
+    fn result(messages: &[Value], id: u64) -> Value {
+        messages
+            .iter()
+            .find(|message| message["id"] == id)
+            .map(|message| message["result"].clone())
+            .unwrap()
+    }
+
+    fn at(file: &str, text: &str, needle: &str) -> Value {
+        json!({ "textDocument": { "uri": uri(file) }, "position": position(text, needle) })
+    }
+
+    #[test]
+    fn definition_of_a_threat_reference() {
+        let messages = exchange(
+            DIAGRAM,
+            vec![request(
+                1,
+                "textDocument/definition",
+                at("diagrams/shop.yaml", DIAGRAM, "Spoofing"),
+            )],
+        );
+        assert_eq!(
+            result(&messages, 1),
+            json!([{
+                "uri": uri("threats.yaml"),
+                "range": {
+                    "start": { "line": 0, "character": 10 },
+                    "end": { "line": 0, "character": 30 },
+                },
+            }])
+        );
+    }
+
+    #[test]
+    fn references_and_rename_of_a_node() {
+        let mut params = at("diagrams/shop.yaml", DIAGRAM, "User");
+        params["context"] = json!({ "includeDeclaration": false });
+        let mut rename = at("diagrams/shop.yaml", DIAGRAM, "User");
+        rename["newName"] = json!("Customer");
+        let messages = exchange(
+            DIAGRAM,
+            vec![
+                request(1, "textDocument/references", params),
+                request(2, "textDocument/rename", rename),
+            ],
+        );
+
+        let references = result(&messages, 1);
+        assert_eq!(references.as_array().unwrap().len(), 1);
+        assert_eq!(
+            references[0]["range"]["start"],
+            json!({ "line": 15, "character": 12 })
+        );
+
+        let edits = &result(&messages, 2)["changes"][uri("diagrams/shop.yaml")];
+        let lines: Vec<&Value> = edits
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|edit| &edit["range"]["start"]["line"])
+            .collect();
+        assert_eq!(lines, vec![&json!(3), &json!(15)]);
+        assert!(edits
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|edit| edit["newText"] == "Customer"));
+    }
+
+    #[test]
+    fn completion_of_a_trust_boundary() {
+        let messages = exchange(
+            DIAGRAM,
+            vec![request(
+                1,
+                "textDocument/completion",
+                at("diagrams/shop.yaml", DIAGRAM, "Internet"),
+            )],
+        );
+        let items = result(&messages, 1);
+        let labels: Vec<&Value> = items
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| &item["label"])
+            .collect();
+        assert_eq!(labels, vec!["Internet"]);
+    }
+
+    /// Last diagnostics published for a file.
+    fn diagnostics(messages: &[Value], file: &str) -> Vec<Value> {
+        messages
+            .iter()
+            .rfind(|message| {
+                message["method"] == "textDocument/publishDiagnostics"
+                    && message["params"]["uri"] == uri(file)
+            })
+            .map(|message| message["params"]["diagnostics"].as_array().unwrap().clone())
+            .unwrap()
+    }
+
+    #[test]
+    fn diagnostics_of_the_findings() {
+        let messages = exchange(DIAGRAM, Vec::new());
+        let web_line = position(DIAGRAM, "Web")["line"].clone();
+        assert!(diagnostics(&messages, "diagrams/shop.yaml")
+            .iter()
+            .any(|diagnostic| diagnostic["code"] == "stride-coverage"
+                && diagnostic["range"]["start"]["line"] == web_line
+                && diagnostic["severity"] == SEVERITY_WARNING));
+    }
+
+    #[test]
+    fn diagnostics_of_a_parse_error() {
+        let messages = exchange("title: \"Shop\"\nnodes: 3\n", Vec::new());
+        let diagnostics = diagnostics(&messages, "diagrams/shop.yaml");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0]["severity"], SEVERITY_ERROR);
+    }
+
+    #[test]
+    fn node_references_of_a_component_go_to_the_catalog() {
+        let destination = json!({
+            "textDocument": { "uri": uri("diagrams/shop.yaml") },
+            "position": { "line": 12, "character": 17 },
+        });
+        let mut rename = destination.clone();
+        rename["newName"] = json!("Queue");
+        let messages = exchange(
+            ORDERS,
+            vec![
+                request(1, "textDocument/definition", destination),
+                request(
+                    2,
+                    "textDocument/references",
+                    at("config.yaml", CONFIG, "Broker"),
+                ),
+                request(3, "textDocument/rename", rename),
+            ],
+        );
+
+        let definition = result(&messages, 1);
+        assert_eq!(definition[0]["uri"], uri("config.yaml"));
+        assert_eq!(definition[0]["range"]["start"], position(CONFIG, "Broker"));
+
+        let references = result(&messages, 2);
+        let lines: Vec<(&str, u64)> = references
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|location| {
+                (
+                    location["uri"].as_str().unwrap(),
+                    location["range"]["start"]["line"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        let config_line = position(CONFIG, "Broker")["line"].as_u64().unwrap();
+        let (config, diagram) = (uri("config.yaml"), uri("diagrams/shop.yaml"));
+        assert_eq!(
+            lines,
+            vec![
+                (config.as_str(), config_line),
+                (diagram.as_str(), 7),
+                (diagram.as_str(), 12)
+            ]
+        );
+
+        let changes = &result(&messages, 3)["changes"];
+        assert_eq!(changes[&config].as_array().unwrap().len(), 1);
+        assert_eq!(changes[&diagram].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn completion_of_a_node_includes_the_referenced_components() {
+        let source = json!({
+            "textDocument": { "uri": uri("diagrams/shop.yaml") },
+            "position": { "line": 11, "character": 12 },
+        });
+        let messages = exchange(ORDERS, vec![request(1, "textDocument/completion", source)]);
+        let items = result(&messages, 1);
+        let labels: Vec<&Value> = items
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| &item["label"])
+            .collect();
+        assert_eq!(labels, vec!["Broker", "Publish", "Web"]);
+    }
+
+    #[test]
+    fn included_files_are_indexed() {
+        let root = std::env::temp_dir().join(format!("threat-dragon-lsp-{}", Uuid::new_v4()));
+        let diagram = r#"title: "Shop"
+description: ""
+nodes:
+  - include: ../shared/nodes.yaml
+  - name: Login
+    type: flow
+    description: ""
+    source: User
+    destination: Web
+    threats:
+      - "Spoofing of the user"
+"#;
+        let nodes = r#"- name: User
+  type: actor
+  description: ""
+  threats: []
+- name: Web
+  type: process
+  description: ""
+  threats: []
+"#;
+        for (file, text) in [
+            ("config.yaml", CONFIG),
+            ("threats.yaml", THREATS),
+            ("diagrams/shop.yaml", diagram),
+            ("shared/nodes.yaml", nodes),
+        ] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, text).unwrap();
+        }
+        let (diagram_uri, nodes_uri) = (
+            uri_from_path(&root.join("diagrams/shop.yaml")),
+            uri_from_path(&root.join("shared/nodes.yaml")),
+        );
+        let mut rename = json!({
+            "textDocument": { "uri": diagram_uri },
+            "position": position(diagram, "User"),
+        });
+        rename["newName"] = json!("Customer");
+        let messages = serve_messages(
+            &root,
+            vec![
+                json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }),
+                request(1, "textDocument/rename", rename),
+            ],
+        );
+        fs::remove_dir_all(&root).unwrap();
+
+        let changes = &result(&messages, 1)["changes"];
+        assert_eq!(changes[&diagram_uri].as_array().unwrap().len(), 1);
+        assert_eq!(
+            changes[&nodes_uri][0]["range"]["start"],
+            position(nodes, "User")
+        );
+
+        let published = messages
+            .iter()
+            .find(|message| message["params"]["uri"] == nodes_uri)
+            .unwrap();
+        let web_line = position(nodes, "Web")["line"].clone();
+        assert!(published["params"]["diagnostics"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|diagnostic| diagnostic["code"] == "stride-coverage"
+                && diagnostic["range"]["start"]["line"] == web_line));
+    }
+
+    #[test]
+    fn malformed_messages_get_a_parse_error() {
+        let bodies = [
+            "{\"jsonrpc\": \"2.0\", \"id\": 1,".to_string(),
+            request(2, "shutdown", Value::Null).to_string(),
+            json!({ "jsonrpc": "2.0", "method": "exit" }).to_string(),
+        ];
+        let messages = serve_bodies(Path::new(ROOT), &bodies);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0]["id"], Value::Null);
+        assert_eq!(messages[0]["error"]["code"], PARSE_ERROR);
+        assert_eq!(result(&messages, 2), Value::Null);
+    }
+}
@@ -1,85 +1,21 @@
-use std::{
-    env,
-    fs::{self, File},
-    io::{BufReader, BufWriter, ErrorKind},
-    path::{self, Path},
-    result,
-};
+#![allow(clippy::module_inception)]
 
-use input::threat::Threat;
+use cli::cli::{Cli, Command};
 
-use crate::{
-    config::config::Config, input::input_diagram::InputDiagram,
-    threat_dragon_modeling::general::ThreatModeling,
-};
-
-use reports::xls::xls_reports;
-
-mod config;
-mod input;
-mod process;
-mod reports;
-mod threat_dragon_modeling;
+mod cli;
+mod commands;
 
 fn main() {
-    let config_path = env::var("CONFIG_PATH");
-    let threat_path = env::var("THREAT_PATH");
-    let diagram_path = env::var("DIAGRAM_PATH");
-    let output_path = env::var("OUTPUT_PATH");
-
-    let file = File::open(config_path.unwrap()).unwrap();
-    let reader = BufReader::new(file);
-    let config: Config = serde_yaml::from_reader(reader).unwrap();
-    // println!("{:?}", config);
-
-    let file = File::open(threat_path.unwrap()).unwrap();
-    let reader = BufReader::new(file);
-    let threat_list: Vec<Threat> = serde_yaml::from_reader(reader).unwrap();
-    // println!("{:?}", threat_list);
-
-    let entries = fs::read_dir(&diagram_path.unwrap())
-        .unwrap()
-        .filter_map(Result::ok) // Filtrer les erreurs
-        .filter(|e| e.path().is_file()) // Filtrer uniquement les fichiers
-        .collect::<Vec<_>>();
-
-    let mut threat_model_diagram_list: Vec<InputDiagram> = Vec::new();
-    for entry in entries {
-        let content = fs::read_to_string(&entry.path()).unwrap();
-        let threat_model_yaml: InputDiagram = serde_yaml::from_str(&content).unwrap();
-        // println!("{:#?}", threat_model_yaml);
-        let childs = threat_model_yaml.create_child_diagrams(&config);
-        threat_model_diagram_list.push(threat_model_yaml);
-        childs
-            .iter()
-            .for_each(|child| threat_model_diagram_list.push(child.clone()));
-        // println!("{:#?}", childs);
+    let cli = Cli::parse_with_default_command();
+    match &cli.command {
+        Command::Generate(args) => commands::generate::run(args),
+        Command::Watch(args) => commands::watch::run(args),
+        Command::Init(args) => commands::init::run(args),
+        Command::Schema(args) => commands::schema::run(args),
+        Command::Lsp(args) => commands::lsp::run(args),
+        Command::Import(args) => commands::import::run(args),
+        Command::Gate(args) => commands::gate::run(args),
+        Command::Diff(args) => commands::diff::run(args),
+        Command::History(args) => commands::history::run(args),
     }
-
-    let model_output = output_path.unwrap();
-    let new_threat_modeling =
-        ThreatModeling::new(&threat_model_diagram_list, &config, &threat_list);
-
-    let output_folder_path = Path::new(&model_output);
-    let result = fs::create_dir_all(output_folder_path);
-    if let Err(result) = result {
-        if result.kind() != ErrorKind::AlreadyExists {
-            panic!("{}", result);
-        }
-    }
-
-    if let Some(directory) = output_folder_path.file_name() {
-        if let Some(directory_str) = directory.to_str() {
-            let mut json_model_fullpath = output_folder_path.join(directory_str);
-            json_model_fullpath.set_extension("json");
-            let json_model_file = File::create(json_model_fullpath).unwrap();
-            let writer = BufWriter::new(json_model_file);
-            serde_json::to_writer_pretty(writer, &new_threat_modeling).unwrap();
-
-            threat_model_diagram_list.iter().for_each(|diagram| {
-                xls_reports::create_reports(output_folder_path, &diagram, &config, &threat_list)
-                    .unwrap()
-            });
-        }
-    };
 }
@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Version of the Open Threat Model specification written by the exporter.
+pub const OTM_VERSION: &str = "0.2.0";
+
+/// Open Threat Model document, see
+/// <https://github.com/iriusrisk/OpenThreatModel>. Only the fields used by the
+/// import and the export are described, the other ones are ignored.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Otm {
+    pub otm_version: String,
+    pub project: OtmProject,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub representations: Vec<OtmRepresentation>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assets: Vec<OtmAsset>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trust_zones: Vec<OtmTrustZone>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub components: Vec<OtmComponent>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dataflows: Vec<OtmDataflow>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub threats: Vec<OtmThreat>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mitigations: Vec<OtmMitigation>,
+}
+
+pub type OtmAttributes = BTreeMap<String, Value>;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OtmProject {
+    pub name: String,
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: OtmAttributes,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OtmRepresentation {
+    pub name: String,
+    pub id: String,
+    #[serde(rename = "type")]
+    pub type_field: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: OtmAttributes,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OtmRepresentationElement {
+    pub representation: String,
+    pub id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OtmAsset {
+    pub name: String,
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OtmTrustZone {
+    pub id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<OtmParent>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: OtmAttributes,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OtmParent {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trust_zone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub component: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OtmComponent {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_field: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub parent: OtmParent,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub representations: Vec<OtmRepresentationElement>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assets: Option<OtmComponentAssets>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub threats: Vec<OtmThreatInstance>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: OtmAttributes,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OtmComponentAssets {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub processed: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stored: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OtmDataflow {
+    pub id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub bidirectional: bool,
+    pub source: String,
+    pub destination: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assets: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub threats: Vec<OtmThreatInstance>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: OtmAttributes,
+}
+
+/// Threat of the threat library attached to a component or a dataflow.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OtmThreatInstance {
+    pub threat: String,
+    pub state: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mitigations: Vec<OtmMitigationInstance>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OtmMitigationInstance {
+    pub mitigation: String,
+    pub state: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OtmThreat {
+    pub name: String,
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cwes: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub risk: Option<OtmThreatRisk>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: OtmAttributes,
+}
+
+/// Likelihood and impact of a threat, from 0 to 100.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OtmThreatRisk {
+    pub likelihood: f64,
+    pub impact: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OtmMitigation {
+    pub name: String,
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub risk_reduction: f64,
+}
+
+/// Trust zone of the components outside of any trust boundary, OTM requires
+/// a parent trust zone for each component.
+pub const DEFAULT_TRUST_ZONE: &str = "default-trust-zone";
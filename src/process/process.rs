@@ -11,15 +11,15 @@ pub trait MappingFromInputDiagram {
         index: usize,
         input_diagram: &InputDiagram,
         config: &Config,
-        threats: &Vec<Threat>,
+        threats: &[Threat],
     ) -> Self;
 }
 
 pub trait MappingFromVecInputDiagram {
     fn from_input_diagram(
-        input_diagram: &Vec<InputDiagram>,
+        input_diagram: &[InputDiagram],
         config: &Config,
-        threats: &Vec<Threat>,
+        threats: &[Threat],
     ) -> Self;
 }
 
@@ -28,7 +28,7 @@ pub trait MappingFromInputNode {
 }
 
 pub trait MappingFromInputNodeAndThreats {
-    fn from_input_diagram(input_node: &Node, config: &Config, threats: &Vec<Threat>) -> Self;
+    fn from_input_diagram(input_node: &Node, config: &Config, threats: &[Threat]) -> Self;
 }
 
 pub trait MappingFromInputThreat {
@@ -0,0 +1,84 @@
+use std::{collections::HashMap, path::Path};
+
+use serde_yaml::Value;
+
+use crate::input::input_diagram::Node;
+
+use super::project_error::ProjectError;
+
+pub const COMPONENT_KEY: &str = "component";
+
+/// Fields written next to the `component:` reference of the nodes expanded
+/// from the catalog, by diagram title and node name.
+pub type ComponentOverrides = HashMap<(String, String), Vec<String>>;
+
+/// Whether a node of a diagram references a component of the catalog.
+pub fn has_component_reference(diagram: &Value) -> bool {
+    diagram
+        .get("nodes")
+        .and_then(Value::as_sequence)
+        .is_some_and(|nodes| nodes.iter().any(|node| node.get(COMPONENT_KEY).is_some()))
+}
+
+/// Replace the nodes of a diagram referencing a component of the catalog,
+/// `component: <name>`, by the component. The fields written next to the
+/// reference take precedence over the ones of the catalog. Returns the name
+/// of each node referencing a component with the fields written next to the
+/// reference.
+pub fn expand_components(
+    diagram: &mut Value,
+    components: &[Node],
+    path: &Path,
+) -> Result<Vec<(String, Vec<String>)>, ProjectError> {
+    let Some(Value::Sequence(nodes)) = diagram.get_mut("nodes") else {
+        return Ok(Vec::new());
+    };
+    let mut expanded = Vec::new();
+    for node in nodes.iter_mut() {
+        let Some(reference) = node
+            .as_mapping_mut()
+            .and_then(|mapping| mapping.remove(COMPONENT_KEY))
+        else {
+            continue;
+        };
+        let name = reference.as_str().ok_or_else(|| {
+            ProjectError::Parse(format!(
+                "{}: {} takes the name of a component",
+                path.display(),
+                COMPONENT_KEY
+            ))
+        })?;
+        let component = components
+            .iter()
+            .find(|component| component.name == name)
+            .ok_or_else(|| {
+                ProjectError::Parse(format!(
+                    "{}: component \"{}\" is not defined in the configuration",
+                    path.display(),
+                    name
+                ))
+            })?;
+        let Value::Mapping(mut merged) = serde_yaml::to_value(component)
+            .map_err(|e| ProjectError::Parse(format!("{}: {}", path.display(), e)))?
+        else {
+            unreachable!("a node serializes to a mapping");
+        };
+        let mut fields = Vec::new();
+        if let Value::Mapping(overrides) = node.clone() {
+            fields = overrides
+                .keys()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect();
+            merged.extend(overrides);
+        }
+        let name = merged
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or(name)
+            .to_string();
+        *node = Value::Mapping(merged);
+        expanded.push((name, fields));
+    }
+    Ok(expanded)
+}
@@ -0,0 +1,497 @@
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+    rc::Rc,
+};
+
+use serde::de::DeserializeOwned;
+use serde_path_to_error::Segment;
+use serde_yaml::{Mapping, Value};
+
+use super::project_error::ProjectError;
+
+pub const INCLUDE_KEY: &str = "include";
+
+/// Project file with its includes resolved, and the files it included.
+pub struct Resolved<T> {
+    pub value: T,
+    pub included: Vec<PathBuf>,
+    origins: Vec<Origin>,
+}
+
+impl<T> Resolved<T> {
+    /// Included file where the item `index` of the list under the root key
+    /// `key` is written, `None` for an item of the file itself.
+    pub fn item_file(&self, key: &str, index: usize) -> Option<&Path> {
+        let item = [PathSegment::Key(key.to_string()), PathSegment::Index(index)];
+        innermost_origin(&self.origins, &item)
+    }
+}
+
+/// Read a project file whose lists and mappings may include other files,
+/// given relative to the including file:
+///
+/// - a list item `include: <file>` is replaced by the items of the file, or
+///   by the file itself when it is not a list;
+/// - the keys of the files given by the `include` key of a mapping are added
+///   to the mapping, their lists after the items of the mapping, the other
+///   values of the mapping taking precedence.
+///
+/// `include` takes a file or a list of files. Errors name the file where the
+/// faulty element is written.
+pub fn parse_yaml<T: DeserializeOwned>(
+    path: &Path,
+    content: &str,
+) -> Result<Resolved<T>, ProjectError> {
+    parse_yaml_with(path, content, |_| Ok(false))
+}
+
+/// [`parse_yaml`], `expand` changing the document once its includes are
+/// resolved and returning whether it changed it.
+pub fn parse_yaml_with<T: DeserializeOwned>(
+    path: &Path,
+    content: &str,
+    expand: impl FnOnce(&mut Value) -> Result<bool, ProjectError>,
+) -> Result<Resolved<T>, ProjectError> {
+    let mut document: Value = serde_yaml::from_str(content)
+        .map_err(|e| ProjectError::Parse(format!("{}: {}", path.display(), e)))?;
+    let mut resolver = Resolver::default();
+    let included = has_include(&document);
+    if included {
+        let source = Rc::new(Source {
+            file: path.to_path_buf(),
+            chain: vec![canonical(path)],
+        });
+        document = resolver.resolve(document, &source, path, &mut Vec::new())?;
+    }
+    let expanded = expand(&mut document)?;
+    if !included && !expanded {
+        // Keep the line numbers of the error messages.
+        let value = serde_yaml::from_str(content)
+            .map_err(|e| ProjectError::Parse(format!("{}: {}", path.display(), e)))?;
+        return Ok(Resolved {
+            value,
+            included: Vec::new(),
+            origins: Vec::new(),
+        });
+    }
+
+    let value = serde_path_to_error::deserialize(document).map_err(|e| {
+        let error_path: Vec<PathSegment> = e
+            .path()
+            .iter()
+            .filter_map(|segment| match segment {
+                Segment::Seq { index } => Some(PathSegment::Index(*index)),
+                Segment::Map { key } => Some(PathSegment::Key(key.clone())),
+                Segment::Enum { .. } | Segment::Unknown => None,
+            })
+            .collect();
+        let file = innermost_origin(&resolver.origins, &error_path).unwrap_or(path);
+        ProjectError::Parse(format!("{}: {}", file.display(), e))
+    })?;
+    Ok(Resolved {
+        value,
+        included: resolver.included,
+        origins: resolver.origins,
+    })
+}
+
+/// File of the innermost included element containing the element at `path`.
+fn innermost_origin<'a>(origins: &'a [Origin], path: &[PathSegment]) -> Option<&'a Path> {
+    origins
+        .iter()
+        .filter(|origin| path.starts_with(&origin.path))
+        .max_by_key(|origin| origin.path.len())
+        .map(|origin| origin.file.as_path())
+}
+
+/// Whether a document includes other files.
+pub fn has_include(value: &Value) -> bool {
+    match value {
+        Value::Sequence(items) => items.iter().any(has_include),
+        Value::Mapping(mapping) => {
+            mapping.contains_key(INCLUDE_KEY) || mapping.values().any(has_include)
+        }
+        Value::Tagged(tagged) => has_include(&tagged.value),
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PathSegment {
+    Index(usize),
+    Key(String),
+}
+
+/// Element of the resolved document written in an included file.
+struct Origin {
+    path: Vec<PathSegment>,
+    file: PathBuf,
+}
+
+/// File of a value, with the files including it to detect cycles.
+struct Source {
+    file: PathBuf,
+    chain: Vec<PathBuf>,
+}
+
+type Pending = (Value, Rc<Source>);
+
+/// Value of a mapping key before its includes are resolved, the items of a
+/// list coming from several files.
+enum Entry {
+    Value(Pending),
+    Items(Vec<Pending>),
+}
+
+#[derive(Default)]
+struct Resolver {
+    origins: Vec<Origin>,
+    included: Vec<PathBuf>,
+}
+
+impl Resolver {
+    /// Resolve the includes of `value`, placed at `path` of the resolved
+    /// document in an element written in `parent`.
+    fn resolve(
+        &mut self,
+        value: Value,
+        source: &Rc<Source>,
+        parent: &Path,
+        path: &mut Vec<PathSegment>,
+    ) -> Result<Value, ProjectError> {
+        if source.file != parent {
+            self.origins.push(Origin {
+                path: path.clone(),
+                file: source.file.clone(),
+            });
+        }
+        match value {
+            Value::Sequence(items) => {
+                let items = items
+                    .into_iter()
+                    .map(|item| (item, source.clone()))
+                    .collect();
+                let mut resolved = Vec::new();
+                self.resolve_items(items, &source.file, path, &mut resolved)?;
+                Ok(Value::Sequence(resolved))
+            }
+            Value::Mapping(mapping) => {
+                let mut entries = Vec::new();
+                self.collect_entries(mapping, source, &mut entries)?;
+                let mut resolved = Mapping::new();
+                for (key, entry) in entries {
+                    path.push(key_segment(&key));
+                    let value = match entry {
+                        Entry::Value((value, value_source)) => {
+                            self.resolve(value, &value_source, &source.file, path)?
+                        }
+                        Entry::Items(items) => {
+                            let mut resolved_items = Vec::new();
+                            self.resolve_items(items, &source.file, path, &mut resolved_items)?;
+                            Value::Sequence(resolved_items)
+                        }
+                    };
+                    path.pop();
+                    resolved.insert(key, value);
+                }
+                Ok(Value::Mapping(resolved))
+            }
+            Value::Tagged(mut tagged) => {
+                tagged.value = self.resolve(tagged.value, source, &source.file, path)?;
+                Ok(Value::Tagged(tagged))
+            }
+            value => Ok(value),
+        }
+    }
+
+    /// Resolve the items of a list written in `parent`, splicing the included
+    /// lists.
+    fn resolve_items(
+        &mut self,
+        items: Vec<Pending>,
+        parent: &Path,
+        path: &mut Vec<PathSegment>,
+        resolved: &mut Vec<Value>,
+    ) -> Result<(), ProjectError> {
+        for (item, source) in items {
+            let is_include = item
+                .as_mapping()
+                .is_some_and(|mapping| mapping.len() == 1 && mapping.contains_key(INCLUDE_KEY));
+            if !is_include {
+                path.push(PathSegment::Index(resolved.len()));
+                let value = self.resolve(item, &source, parent, path)?;
+                path.pop();
+                resolved.push(value);
+                continue;
+            }
+            for included_file in include_files(&item, &source.file)? {
+                let (value, included_source) = self.read(&included_file, &source)?;
+                match value {
+                    Value::Sequence(items) => {
+                        let items = items
+                            .into_iter()
+                            .map(|item| (item, included_source.clone()))
+                            .collect();
+                        self.resolve_items(items, parent, path, resolved)?;
+                    }
+                    value => {
+                        path.push(PathSegment::Index(resolved.len()));
+                        let value = self.resolve(value, &included_source, parent, path)?;
+                        path.pop();
+                        resolved.push(value);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Keys of a mapping followed by the keys of its included mappings.
+    fn collect_entries(
+        &mut self,
+        mapping: Mapping,
+        source: &Rc<Source>,
+        entries: &mut Vec<(Value, Entry)>,
+    ) -> Result<(), ProjectError> {
+        let included = match mapping.get(INCLUDE_KEY) {
+            Some(_) => include_files(&Value::Mapping(mapping.clone()), &source.file)?,
+            None => Vec::new(),
+        };
+        for (key, value) in mapping {
+            if key.as_str() == Some(INCLUDE_KEY) {
+                continue;
+            }
+            let entry = match value {
+                Value::Sequence(items) => Entry::Items(
+                    items
+                        .into_iter()
+                        .map(|item| (item, source.clone()))
+                        .collect(),
+                ),
+                value => Entry::Value((value, source.clone())),
+            };
+            match entries.iter_mut().find(|(existing, _)| *existing == key) {
+                Some((_, Entry::Items(items))) => {
+                    if let Entry::Items(included_items) = entry {
+                        items.extend(included_items);
+                    }
+                }
+                Some(_) => {}
+                None => entries.push((key, entry)),
+            }
+        }
+        for included_file in included {
+            let (value, included_source) = self.read(&included_file, source)?;
+            let Value::Mapping(mapping) = value else {
+                return Err(ProjectError::Parse(format!(
+                    "{}: included in a mapping but is not a mapping (included from {})",
+                    included_file.display(),
+                    source.file.display()
+                )));
+            };
+            self.collect_entries(mapping, &included_source, entries)?;
+        }
+        Ok(())
+    }
+
+    /// Content of a file included from `source`.
+    fn read(
+        &mut self,
+        included_file: &Path,
+        source: &Rc<Source>,
+    ) -> Result<(Value, Rc<Source>), ProjectError> {
+        let canonical_path = canonical(included_file);
+        if let Some(start) = source.chain.iter().position(|file| *file == canonical_path) {
+            let mut cycle: Vec<String> = source.chain[start..]
+                .iter()
+                .map(|file| file.display().to_string())
+                .collect();
+            cycle.push(canonical_path.display().to_string());
+            return Err(ProjectError::Parse(format!(
+                "include cycle: {}",
+                cycle.join(" -> ")
+            )));
+        }
+        let content = fs::read_to_string(included_file).map_err(|e| {
+            ProjectError::Read(format!(
+                "{}: {} (included from {})",
+                included_file.display(),
+                e,
+                source.file.display()
+            ))
+        })?;
+        let value: Value = serde_yaml::from_str(&content)
+            .map_err(|e| ProjectError::Parse(format!("{}: {}", included_file.display(), e)))?;
+        if !self.included.contains(&included_file.to_path_buf()) {
+            self.included.push(included_file.to_path_buf());
+        }
+        let mut chain = source.chain.clone();
+        chain.push(canonical_path);
+        Ok((
+            value,
+            Rc::new(Source {
+                file: included_file.to_path_buf(),
+                chain,
+            }),
+        ))
+    }
+}
+
+/// Files named by the `include` key of a mapping, relative to `file`.
+fn include_files(value: &Value, file: &Path) -> Result<Vec<PathBuf>, ProjectError> {
+    let invalid = || {
+        ProjectError::Parse(format!(
+            "{}: {} takes a file or a list of files",
+            file.display(),
+            INCLUDE_KEY
+        ))
+    };
+    let names = match value.get(INCLUDE_KEY) {
+        Some(Value::String(name)) => vec![name.as_str()],
+        Some(Value::Sequence(names)) => names
+            .iter()
+            .map(Value::as_str)
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(invalid)?,
+        _ => return Err(invalid()),
+    };
+    let folder = file.parent().unwrap_or(Path::new(""));
+    Ok(names.into_iter().map(|name| folder.join(name)).collect())
+}
+
+fn key_segment(key: &Value) -> PathSegment {
+    match key {
+        Value::String(key) => PathSegment::Key(key.clone()),
+        key => PathSegment::Key(
+            serde_yaml::to_string(key)
+                .unwrap_or_default()
+                .trim()
+                .to_string(),
+        ),
+    }
+}
+
+/// Path without its `.` components and with its `..` components applied,
+/// without reading the file system, such as the path of a file included from
+/// another folder.
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                Some(Component::RootDir | Component::Prefix(_)) => {}
+                _ => normalized.push(component),
+            },
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::input::threat::Threat;
+
+    /// Temporary folder with the files, removed by the caller.
+    fn folder(files: &[(&str, &str)]) -> PathBuf {
+        let folder = std::env::temp_dir().join(format!("threat-dragon-include-{}", Uuid::new_v4()));
+        for (name, content) in files {
+            let path = folder.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        folder
+    }
+
+    fn parse(folder: &Path, name: &str) -> Result<Resolved<Value>, ProjectError> {
+        let path = folder.join(name);
+        parse_yaml(&path, &fs::read_to_string(&path).unwrap())
+    }
+
+    #[test]
+    fn includes_are_resolved() {
+        let folder = folder(&[
+            (
+                "main.yaml",
+                "include: base.yaml\nnodes:\n  - a\n  - include: more.yaml\n",
+            ),
+            ("base.yaml", "title: Base\nnodes: [b]\n"),
+            ("more.yaml", "[c, d]\n"),
+        ]);
+        let resolved = parse(&folder, "main.yaml");
+        fs::remove_dir_all(&folder).unwrap();
+
+        let resolved = resolved.unwrap();
+        let expected: Value = serde_yaml::from_str("{nodes: [a, c, d, b], title: Base}").unwrap();
+        assert_eq!(resolved.value, expected);
+        assert_eq!(
+            resolved.included,
+            vec![folder.join("base.yaml"), folder.join("more.yaml")]
+        );
+        assert_eq!(resolved.item_file("nodes", 0), None);
+        assert_eq!(
+            resolved.item_file("nodes", 2),
+            Some(folder.join("more.yaml").as_path())
+        );
+        assert_eq!(
+            resolved.item_file("nodes", 3),
+            Some(folder.join("base.yaml").as_path())
+        );
+    }
+
+    #[test]
+    fn include_cycles_are_errors() {
+        let folder = folder(&[
+            ("main.yaml", "nodes:\n  - include: shared/a.yaml\n"),
+            ("shared/a.yaml", "- include: b.yaml\n"),
+            ("shared/b.yaml", "- include: a.yaml\n"),
+            ("self.yaml", "include: self.yaml\n"),
+        ]);
+        let cycle = parse(&folder, "main.yaml").err();
+        let self_include = parse(&folder, "self.yaml").err();
+        fs::remove_dir_all(&folder).unwrap();
+
+        match cycle {
+            Some(ProjectError::Parse(message)) => {
+                assert!(message.starts_with("include cycle: "), "{}", message);
+                assert_eq!(message.matches("a.yaml").count(), 2, "{}", message);
+            }
+            error => panic!("expected an include cycle, got {:?}", error),
+        }
+        assert!(
+            matches!(self_include, Some(ProjectError::Parse(message)) if message.starts_with("include cycle: ")),
+        );
+    }
+
+    #[test]
+    fn errors_name_the_included_file() {
+        let folder = folder(&[
+            ("main.yaml", "- include: threats.yaml\n"),
+            ("threats.yaml", "- title: 3\n  severity: [High]\n"),
+        ]);
+        let path = folder.join("main.yaml");
+        let error = parse_yaml::<Vec<Threat>>(&path, &fs::read_to_string(&path).unwrap()).err();
+        fs::remove_dir_all(&folder).unwrap();
+
+        match error {
+            Some(ProjectError::Parse(message)) => assert!(
+                message.starts_with(&folder.join("threats.yaml").display().to_string()),
+                "{}",
+                message
+            ),
+            error => panic!("expected a parse error, got {:?}", error),
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub mod component;
+pub mod include;
+pub mod project;
+pub mod project_error;
+pub mod revision;
@@ -0,0 +1,272 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    config::config::Config,
+    input::{input_diagram::InputDiagram, threat::Threat},
+    library::threat_library::ThreatLibrary,
+    threat_dragon_modeling::general::ThreatModeling,
+    validation::validator::{self, ValidationIssue},
+};
+
+use super::{
+    component::{self, ComponentOverrides},
+    include,
+    project_error::ProjectError,
+};
+
+pub const CONFIG_FILE_NAME: &str = "config.yaml";
+pub const THREATS_FILE_NAME: &str = "threats.yaml";
+pub const DIAGRAMS_DIRECTORY_NAME: &str = "diagrams";
+
+/// Docs-as-code threat model: the configuration, the threats and the diagrams
+/// written by hand. Child diagrams are built from the configuration on demand.
+#[derive(Debug, Clone)]
+pub struct Project {
+    pub config: Config,
+    pub threats: Vec<Threat>,
+    pub diagrams: Vec<InputDiagram>,
+    /// File of each diagram loaded from disk, by diagram title.
+    pub diagram_paths: HashMap<String, PathBuf>,
+    /// File of the nodes written in a file included by their diagram, by
+    /// diagram title and node name.
+    pub node_paths: HashMap<(String, String), PathBuf>,
+    /// Nodes referencing a component of the catalog.
+    pub component_overrides: ComponentOverrides,
+    /// Files included by the project files.
+    pub included_paths: Vec<PathBuf>,
+}
+
+impl Project {
+    /// Load the project files and resolve the threat templates used by the
+    /// diagrams, the diagrams are sorted by file name.
+    pub fn load(
+        config_path: &Path,
+        threat_path: &Path,
+        diagram_path: &Path,
+        template_path: Option<&Path>,
+    ) -> Result<Self, ProjectError> {
+        let mut entries = fs::read_dir(diagram_path)
+            .map_err(|e| ProjectError::Read(format!("{}: {}", diagram_path.display(), e)))?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .collect::<Vec<_>>();
+        entries.sort();
+
+        let diagrams = entries
+            .into_iter()
+            .map(|entry| read_file(&entry).map(|content| (entry, content)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::parse(
+            (config_path, &read_file(config_path)?),
+            (threat_path, &read_file(threat_path)?),
+            &diagrams,
+            template_path,
+        )
+    }
+
+    /// Build the project from the content of its files, given with their path
+    /// for the error messages and [`Project::diagram_path`].
+    pub fn parse(
+        (config_path, config): (&Path, &str),
+        (threat_path, threats): (&Path, &str),
+        diagrams: &[(PathBuf, String)],
+        template_path: Option<&Path>,
+    ) -> Result<Self, ProjectError> {
+        let mut included_paths = Vec::new();
+        let config: Config = parse_yaml(config_path, config, &mut included_paths)?;
+        let mut threats: Vec<Threat> = parse_yaml(threat_path, threats, &mut included_paths)?;
+
+        let mut threat_library = ThreatLibrary::builtin();
+        if let Some(template_path) = template_path {
+            threat_library
+                .extend_from_file(template_path)
+                .map_err(|e| ProjectError::Library(format!("{}", e)))?;
+        }
+
+        let mut input_diagrams = Vec::new();
+        let mut diagram_paths = HashMap::new();
+        let mut node_paths = HashMap::new();
+        let mut component_overrides = ComponentOverrides::new();
+        for (path, content) in diagrams {
+            let mut references = Vec::new();
+            let resolved = include::parse_yaml_with::<InputDiagram>(path, content, |document| {
+                references = component::expand_components(document, &config.components, path)?;
+                Ok(!references.is_empty())
+            })?;
+            for (name, fields) in references {
+                component_overrides.insert((resolved.value.title.clone(), name), fields);
+            }
+            for (index, node) in resolved.value.nodes.iter().enumerate() {
+                if let Some(file) = resolved.item_file("nodes", index) {
+                    node_paths.insert(
+                        (resolved.value.title.clone(), node.name.clone()),
+                        file.to_path_buf(),
+                    );
+                }
+            }
+            add_included(&mut included_paths, resolved.included);
+            let mut diagram = resolved.value;
+            threat_library
+                .resolve(&mut diagram, &mut threats)
+                .map_err(|e| ProjectError::Library(format!("{}: {}", path.display(), e)))?;
+            diagram_paths.insert(diagram.title.clone(), path.clone());
+            input_diagrams.push(diagram);
+        }
+
+        Ok(Self {
+            config,
+            threats,
+            diagrams: input_diagrams,
+            diagram_paths,
+            node_paths,
+            component_overrides,
+            included_paths,
+        })
+    }
+
+    /// Load a project laid out as written by [`Project::save`].
+    pub fn load_folder(folder: &Path, template_path: Option<&Path>) -> Result<Self, ProjectError> {
+        Self::load(
+            &folder.join(CONFIG_FILE_NAME),
+            &folder.join(THREATS_FILE_NAME),
+            &folder.join(DIAGRAMS_DIRECTORY_NAME),
+            template_path,
+        )
+    }
+
+    /// Diagrams of the project, each one followed by its child diagrams.
+    pub fn diagrams_with_children(&self) -> Vec<InputDiagram> {
+        self.diagrams
+            .iter()
+            .flat_map(|diagram| {
+                let mut diagrams = vec![diagram.clone()];
+                diagrams.append(&mut diagram.create_child_diagrams(&self.config));
+                diagrams
+            })
+            .collect()
+    }
+
+    /// Validation issues of the diagrams, the outputs should not be generated
+    /// when one of them is an error.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        validator::validate(
+            &self.diagrams_with_children(),
+            &self.config,
+            &self.threats,
+            &self.component_overrides,
+        )
+    }
+
+    /// Validation issues, STRIDE coverage gaps and policy violations.
+    pub fn findings(&self) -> Vec<ValidationIssue> {
+        validator::findings(
+            &self.diagrams_with_children(),
+            &self.config,
+            &self.threats,
+            &self.component_overrides,
+        )
+    }
+
+    /// Threat Dragon model of the diagrams and their child diagrams.
+    pub fn threat_modeling(&self) -> ThreatModeling {
+        ThreatModeling::new(&self.diagrams_with_children(), &self.config, &self.threats)
+    }
+
+    /// File defining the diagram, the file of the parent diagram for a child
+    /// diagram.
+    pub fn diagram_path(&self, title: &str) -> Option<&Path> {
+        let title = self
+            .config
+            .diagrams
+            .iter()
+            .find(|child| child.name == title)
+            .map(|child| child.parent.as_str())
+            .unwrap_or(title);
+        self.diagram_paths.get(title).map(PathBuf::as_path)
+    }
+
+    /// File defining a node of a diagram: the included file it is written in,
+    /// or the file of the diagram.
+    pub fn node_path(&self, diagram: &str, node: &str) -> Option<&Path> {
+        let title = self
+            .config
+            .diagrams
+            .iter()
+            .find(|child| child.name == diagram)
+            .map(|child| child.parent.as_str())
+            .unwrap_or(diagram);
+        self.node_paths
+            .get(&(title.to_string(), node.to_string()))
+            .map(PathBuf::as_path)
+            .or_else(|| self.diagram_path(title))
+    }
+
+    /// Write `config.yaml`, `threats.yaml` and one file per diagram in
+    /// `diagrams`. Nothing is written when one of the files already exists.
+    pub fn save(&self, output_folder: &Path) -> Result<(), ProjectError> {
+        let files = self.files(output_folder)?;
+        if let Some((path, _)) = files.iter().find(|(path, _)| path.exists()) {
+            return Err(ProjectError::AlreadyExists(format!("{}", path.display())));
+        }
+        let diagrams_folder = output_folder.join(DIAGRAMS_DIRECTORY_NAME);
+        fs::create_dir_all(&diagrams_folder)
+            .map_err(|e| ProjectError::Save(format!("{}: {}", diagrams_folder.display(), e)))?;
+        for (path, content) in files {
+            fs::write(&path, content)
+                .map_err(|e| ProjectError::Save(format!("{}: {}", path.display(), e)))?;
+        }
+        Ok(())
+    }
+
+    /// Path and YAML content of the files written by [`Project::save`].
+    pub fn files(&self, output_folder: &Path) -> Result<Vec<(PathBuf, String)>, ProjectError> {
+        let diagrams_folder = output_folder.join(DIAGRAMS_DIRECTORY_NAME);
+        let mut files = vec![
+            (output_folder.join(CONFIG_FILE_NAME), to_yaml(&self.config)?),
+            (
+                output_folder.join(THREATS_FILE_NAME),
+                to_yaml(&self.threats)?,
+            ),
+        ];
+        for diagram in self.diagrams.iter() {
+            let mut diagram_path = diagrams_folder.join(&diagram.title);
+            diagram_path.set_extension("yaml");
+            files.push((diagram_path, to_yaml(diagram)?));
+        }
+        Ok(files)
+    }
+}
+
+fn read_file(path: &Path) -> Result<String, ProjectError> {
+    fs::read_to_string(path).map_err(|e| ProjectError::Read(format!("{}: {}", path.display(), e)))
+}
+
+/// Parse a project file, adding the files it includes to `included_paths`.
+fn parse_yaml<T: DeserializeOwned>(
+    path: &Path,
+    content: &str,
+    included_paths: &mut Vec<PathBuf>,
+) -> Result<T, ProjectError> {
+    let resolved = include::parse_yaml(path, content)?;
+    add_included(included_paths, resolved.included);
+    Ok(resolved.value)
+}
+
+fn add_included(included_paths: &mut Vec<PathBuf>, included: Vec<PathBuf>) {
+    for included in included {
+        if !included_paths.contains(&included) {
+            included_paths.push(included);
+        }
+    }
+}
+
+fn to_yaml<T: Serialize>(value: &T) -> Result<String, ProjectError> {
+    serde_yaml::to_string(value).map_err(|e| ProjectError::Save(format!("{}", e)))
+}
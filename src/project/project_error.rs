@@ -0,0 +1,22 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone)]
+pub enum ProjectError {
+    Read(String),
+    Parse(String),
+    Library(String),
+    AlreadyExists(String),
+    Save(String),
+}
+
+impl Display for ProjectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProjectError::Read(e) => write!(f, "cannot read project file: {}", e),
+            ProjectError::Parse(e) => write!(f, "cannot parse project file: {}", e),
+            ProjectError::Library(e) => write!(f, "{}", e),
+            ProjectError::AlreadyExists(e) => write!(f, "file already exists: {}", e),
+            ProjectError::Save(e) => write!(f, "cannot save project file: {}", e),
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use uuid::Uuid;
+
+use super::{
+    project::{Project, CONFIG_FILE_NAME, DIAGRAMS_DIRECTORY_NAME, THREATS_FILE_NAME},
+    project_error::ProjectError,
+};
+
+/// Load the project folder as it was at a git revision. The files are
+/// extracted with `git show` to a temporary folder, removed once loaded.
+pub fn load_revision(
+    folder: &Path,
+    revision: &str,
+    template_path: Option<&Path>,
+) -> Result<Project, ProjectError> {
+    let checkout = std::env::temp_dir().join(format!("threat-dragon-generator-{}", Uuid::new_v4()));
+    let project = extract(folder, revision, &checkout)
+        .and_then(|_| Project::load_folder(&checkout, template_path));
+    let _ = fs::remove_dir_all(&checkout);
+    project
+}
+
+fn extract(folder: &Path, revision: &str, checkout: &Path) -> Result<(), ProjectError> {
+    fs::create_dir_all(checkout.join(DIAGRAMS_DIRECTORY_NAME))
+        .map_err(|e| ProjectError::Save(format!("{}: {}", checkout.display(), e)))?;
+
+    // Every YAML file of the folder, the project files including files of
+    // other subfolders.
+    let listing = git(folder, &["ls-tree", "-r", "--name-only", revision, "./"])?;
+    let mut files: Vec<PathBuf> = String::from_utf8_lossy(&listing)
+        .lines()
+        .map(PathBuf::from)
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "yaml" || extension == "yml")
+        })
+        .collect();
+    // Missing project files are reported by `git show`.
+    for file_name in [CONFIG_FILE_NAME, THREATS_FILE_NAME] {
+        if !files.iter().any(|file| file == Path::new(file_name)) {
+            files.push(PathBuf::from(file_name));
+        }
+    }
+
+    for file in files {
+        let path = file.to_string_lossy().replace('\\', "/");
+        let content = git(folder, &["show", &format!("{}:./{}", revision, path)])?;
+        let target = checkout.join(&file);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| ProjectError::Save(format!("{}: {}", parent.display(), e)))?;
+        }
+        fs::write(&target, content)
+            .map_err(|e| ProjectError::Save(format!("{}: {}", file.display(), e)))?;
+    }
+    Ok(())
+}
+
+fn git(folder: &Path, args: &[&str]) -> Result<Vec<u8>, ProjectError> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(folder)
+        .args(args)
+        .output()
+        .map_err(|e| ProjectError::Read(format!("git: {}", e)))?;
+    if !output.status.success() {
+        return Err(ProjectError::Read(format!(
+            "git {}: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+/// Commit of the project history.
+#[derive(Debug, Clone)]
+pub struct Commit {
+    pub hash: String,
+    /// Author date, seconds since the Unix epoch.
+    pub timestamp: i64,
+    /// Author date, `YYYY-MM-DD`.
+    pub date: String,
+    pub author: String,
+    pub subject: String,
+}
+
+/// Commits changing a YAML file of the project folder, oldest first: the
+/// configuration, the threats, the diagrams and the files they include, as
+/// extracted by [`load_revision`].
+pub fn log(folder: &Path) -> Result<Vec<Commit>, ProjectError> {
+    // Wildcards of git pathspecs also match the subfolders.
+    let output = git(
+        folder,
+        &[
+            "log",
+            "--reverse",
+            "--date=short",
+            "--format=%H%x09%at%x09%ad%x09%an%x09%s",
+            "--",
+            "./*.yaml",
+            "./*.yml",
+        ],
+    )?;
+    Ok(String::from_utf8_lossy(&output)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(5, '\t');
+            Some(Commit {
+                hash: fields.next()?.to_string(),
+                timestamp: fields.next()?.parse().ok()?,
+                date: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                subject: fields.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect())
+}
@@ -0,0 +1,3 @@
+pub mod png;
+pub mod render_error;
+pub mod svg;
@@ -0,0 +1,51 @@
+use resvg::{tiny_skia, usvg};
+
+use super::render_error::RenderError;
+
+/// Font embedded in the binary, so that text is drawn in images without
+/// installed fonts, as in the Docker image.
+const FONT: &[u8] = include_bytes!("fonts/DejaVuSans.ttf");
+const FONT_FAMILY: &str = "DejaVu Sans";
+
+/// Rasterise an SVG document to PNG. Text is drawn with the fonts installed
+/// on the system, or with the embedded font.
+pub fn render_png(svg: &str) -> Result<Vec<u8>, RenderError> {
+    let mut options = usvg::Options {
+        font_family: FONT_FAMILY.to_string(),
+        ..usvg::Options::default()
+    };
+    let fontdb = options.fontdb_mut();
+    fontdb.load_font_data(FONT.to_vec());
+    fontdb.load_system_fonts();
+    fontdb.set_sans_serif_family(FONT_FAMILY);
+    let tree =
+        usvg::Tree::from_str(svg, &options).map_err(|e| RenderError::Parse(format!("{}", e)))?;
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| RenderError::Pixmap(format!("{}x{}", size.width(), size.height())))?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    pixmap
+        .encode_png()
+        .map_err(|e| RenderError::Encode(format!("{}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_is_drawn_with_the_embedded_font() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="120" height="40"><rect width="120" height="40" fill="white"/><text x="10" y="28" font-family="sans-serif" font-size="20" fill="black">Shop</text></svg>"#;
+        let png = render_png(svg).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+
+        let pixmap = tiny_skia::Pixmap::decode_png(&png).unwrap();
+        assert_eq!((pixmap.width(), pixmap.height()), (120, 40));
+        assert!(pixmap.pixels().iter().any(|pixel| pixel.red() < 128));
+    }
+
+    #[test]
+    fn invalid_documents_are_errors() {
+        assert!(matches!(render_png("<svg"), Err(RenderError::Parse(_))));
+    }
+}
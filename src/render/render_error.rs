@@ -0,0 +1,18 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone)]
+pub enum RenderError {
+    Parse(String),
+    Pixmap(String),
+    Encode(String),
+}
+
+impl Display for RenderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RenderError::Parse(e) => write!(f, "cannot parse svg: {}", e),
+            RenderError::Pixmap(e) => write!(f, "cannot allocate image: {}", e),
+            RenderError::Encode(e) => write!(f, "cannot encode png: {}", e),
+        }
+    }
+}
@@ -0,0 +1,410 @@
+use std::collections::HashMap;
+
+use crate::{
+    threat_dragon_modeling::diagram::{Cell, Diagram},
+    xml::xml::escape,
+};
+
+const PADDING: f64 = 40.;
+const FONT_SIZE: f64 = 14.;
+const DEFAULT_STROKE: &str = "#333333";
+
+/// Render the cells of a Threat Dragon diagram to a standalone SVG document,
+/// with the positions and sizes computed for the Threat Dragon JSON.
+///
+/// Each node and flow is a `<g class="td-cell">` group carrying the cell name
+/// in `data-name`, so the SVG can be made interactive when embedded.
+pub fn render_svg(diagram: &Diagram) -> String {
+    let cells_by_id: HashMap<&String, &Cell> =
+        diagram.cells.iter().map(|cell| (&cell.id, cell)).collect();
+    let (min_x, min_y, max_x, max_y) = bounds(diagram);
+    let width = max_x - min_x + 2. * PADDING;
+    let height = max_y - min_y + 2. * PADDING;
+    let prefix = format!("td-{}", diagram.id);
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" class=\"td-diagram\" width=\"{:.0}\" height=\"{:.0}\" viewBox=\"{:.1} {:.1} {:.1} {:.1}\" font-family=\"Helvetica, Arial, DejaVu Sans, sans-serif\" font-size=\"{}\">\n",
+        width,
+        height,
+        min_x - PADDING,
+        min_y - PADDING,
+        width,
+        height,
+        FONT_SIZE
+    );
+    svg.push_str(&format!("<title>{}</title>\n", escape(&diagram.title)));
+    svg.push_str("<defs>\n");
+    [("arrow", DEFAULT_STROKE), ("arrow-red", "red")]
+        .iter()
+        .for_each(|(id, color)| {
+            svg.push_str(&format!(
+                "<marker id=\"{}-{}\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" markerWidth=\"8\" markerHeight=\"8\" markerUnits=\"userSpaceOnUse\" orient=\"auto\"><path d=\"M 0 0 L 10 5 L 0 10 z\" fill=\"{}\"/></marker>\n",
+                prefix, id, color
+            ))
+        });
+    svg.push_str("</defs>\n");
+    svg.push_str(&format!(
+        "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"white\"/>\n",
+        min_x - PADDING,
+        min_y - PADDING,
+        width,
+        height
+    ));
+
+    let mut ordered_cells: Vec<&Cell> = diagram.cells.iter().collect();
+    ordered_cells.sort_by_key(|cell| match cell.shape.as_str() {
+        "trust-boundary-box" => 0,
+        "flow" => 1,
+        _ => 2,
+    });
+    ordered_cells.iter().for_each(|cell| {
+        let element = match cell.shape.as_str() {
+            "trust-boundary-box" => render_boundary(cell),
+            "flow" => render_flow(cell, &cells_by_id, &prefix),
+            _ => render_node(cell),
+        };
+        if let Some(element) = element {
+            svg.push_str(&element);
+        }
+    });
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn bounds(diagram: &Diagram) -> (f64, f64, f64, f64) {
+    let boxes: Vec<(f64, f64, f64, f64)> = diagram.cells.iter().filter_map(geometry).collect();
+    if boxes.is_empty() {
+        return (0., 0., 0., 0.);
+    }
+    boxes.iter().fold(
+        (f64::MAX, f64::MAX, f64::MIN, f64::MIN),
+        |(min_x, min_y, max_x, max_y), (x, y, w, h)| {
+            (
+                min_x.min(*x),
+                min_y.min(*y),
+                max_x.max(x + w),
+                max_y.max(y + h),
+            )
+        },
+    )
+}
+
+/// Top left corner, width and height of a cell.
+fn geometry(cell: &Cell) -> Option<(f64, f64, f64, f64)> {
+    match (&cell.position, &cell.size) {
+        (Some(position), Some(size)) => Some((
+            position.x,
+            position.y,
+            size.width as f64,
+            size.height as f64,
+        )),
+        _ => None,
+    }
+}
+
+/// Stroke color, width and dash pattern of a node.
+fn node_stroke(cell: &Cell) -> (String, f64, Option<String>) {
+    let body = cell.attrs.as_ref().and_then(|attrs| {
+        attrs.body.as_ref().or(attrs.top_line.as_ref()).map(|body| {
+            (
+                body.stroke.clone(),
+                body.stroke_width,
+                body.stroke_dasharray.clone(),
+            )
+        })
+    });
+    body.unwrap_or((DEFAULT_STROKE.to_string(), 3., None))
+}
+
+fn stroke_attributes(stroke: &str, stroke_width: f64, dasharray: &Option<String>) -> String {
+    let mut attributes = format!(
+        "stroke=\"{}\" stroke-width=\"{}\"",
+        escape(stroke),
+        stroke_width
+    );
+    if let Some(dasharray) = dasharray {
+        attributes.push_str(&format!(" stroke-dasharray=\"{}\"", escape(dasharray)));
+    }
+    attributes
+}
+
+fn open_group(cell: &Cell) -> String {
+    format!(
+        "<g class=\"td-cell td-{}\" data-name=\"{}\" data-cell=\"{}\"><title>{}</title>\n",
+        escape(&cell.shape),
+        escape(&cell.data.name),
+        escape(&cell.id),
+        escape(&cell.data.name)
+    )
+}
+
+fn render_node(cell: &Cell) -> Option<String> {
+    let (x, y, w, h) = geometry(cell)?;
+    let (stroke, stroke_width, dasharray) = node_stroke(cell);
+    let stroke = stroke_attributes(&stroke, stroke_width, &dasharray);
+    let (cx, cy) = (x + w / 2., y + h / 2.);
+    let shape = match cell.shape.as_str() {
+        "process" => format!(
+            "<ellipse cx=\"{:.1}\" cy=\"{:.1}\" rx=\"{:.1}\" ry=\"{:.1}\" fill=\"white\" {}/>\n",
+            cx,
+            cy,
+            w / 2.,
+            h / 2.,
+            stroke
+        ),
+        "store" => format!(
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"white\" stroke=\"none\"/>\n<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" {}/>\n<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" {}/>\n",
+            x,
+            y + h / 4.,
+            w,
+            h / 2.,
+            x,
+            y + h / 4.,
+            x + w,
+            y + h / 4.,
+            stroke,
+            x,
+            y + 3. * h / 4.,
+            x + w,
+            y + 3. * h / 4.,
+            stroke
+        ),
+        _ => format!(
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"white\" {}/>\n",
+            x, y, w, h, stroke
+        ),
+    };
+    Some(format!(
+        "{}{}{}</g>\n",
+        open_group(cell),
+        shape,
+        text(cx, cy, &cell.data.name, "middle")
+    ))
+}
+
+fn render_boundary(cell: &Cell) -> Option<String> {
+    let (x, y, w, h) = geometry(cell)?;
+    Some(format!(
+        "<g class=\"td-boundary\" data-name=\"{}\"><rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"none\" stroke=\"#333333\" stroke-width=\"1\" stroke-dasharray=\"10 5\"/>\n{}</g>\n",
+        escape(&cell.data.name),
+        x,
+        y,
+        w,
+        h,
+        text(x + 10., y + FONT_SIZE + 6., &cell.data.name, "start")
+    ))
+}
+
+fn render_flow(cell: &Cell, cells_by_id: &HashMap<&String, &Cell>, prefix: &str) -> Option<String> {
+    let source = cells_by_id.get(&cell.source.as_ref()?.cell)?;
+    let target = cells_by_id.get(&cell.target.as_ref()?.cell)?;
+    let (sx, sy, sw, sh) = geometry(source)?;
+    let (tx, ty, tw, th) = geometry(target)?;
+    let (scx, scy) = (sx + sw / 2., sy + sh / 2.);
+    let (tcx, tcy) = (tx + tw / 2., ty + th / 2.);
+    let length = ((tcx - scx).powi(2) + (tcy - scy).powi(2)).sqrt();
+    if length == 0. {
+        return None;
+    }
+    let (dx, dy) = ((tcx - scx) / length, (tcy - scy) / length);
+    let start = edge_distance(source, dx, dy);
+    let end = edge_distance(target, dx, dy);
+    let (x1, y1) = (scx + dx * start, scy + dy * start);
+    let (x2, y2) = (tcx - dx * end, tcy - dy * end);
+
+    let line = cell.attrs.as_ref().and_then(|attrs| attrs.line.as_ref());
+    let (stroke, stroke_width, dasharray) = line
+        .map(|line| {
+            (
+                line.stroke.clone(),
+                line.stroke_width,
+                line.stroke_dasharray.clone(),
+            )
+        })
+        .unwrap_or((DEFAULT_STROKE.to_string(), 3., None));
+    let marker = if stroke == "red" {
+        "arrow-red"
+    } else {
+        "arrow"
+    };
+    let label = cell
+        .labels
+        .as_ref()
+        .and_then(|labels| labels.first())
+        .unwrap_or(&cell.data.name);
+
+    Some(format!(
+        "{}<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" {} marker-end=\"url(#{}-{})\"/>\n<line class=\"td-hit\" x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"transparent\" stroke-width=\"12\"/>\n{}</g>\n",
+        open_group(cell),
+        x1,
+        y1,
+        x2,
+        y2,
+        stroke_attributes(&stroke, stroke_width, &dasharray),
+        prefix,
+        marker,
+        x1,
+        y1,
+        x2,
+        y2,
+        label_text((x1 + x2) / 2., (y1 + y2) / 2., label)
+    ))
+}
+
+/// Distance from the center of a cell to its border in the direction (dx, dy).
+fn edge_distance(cell: &Cell, dx: f64, dy: f64) -> f64 {
+    let (_, _, w, h) = geometry(cell).unwrap_or((0., 0., 0., 0.));
+    if cell.shape == "process" {
+        return w.min(h) / 2.;
+    }
+    let horizontal = if dx.abs() > f64::EPSILON {
+        (w / 2.) / dx.abs()
+    } else {
+        f64::MAX
+    };
+    let vertical = if dy.abs() > f64::EPSILON {
+        (h / 2.) / dy.abs()
+    } else {
+        f64::MAX
+    };
+    horizontal.min(vertical)
+}
+
+fn text(x: f64, y: f64, content: &str, anchor: &str) -> String {
+    format!(
+        "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"{}\" dominant-baseline=\"middle\" fill=\"#333333\">{}</text>\n",
+        x,
+        y,
+        anchor,
+        escape(content)
+    )
+}
+
+/// Text with a white halo so flow labels stay readable over lines.
+fn label_text(x: f64, y: f64, content: &str) -> String {
+    format!(
+        "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\" dominant-baseline=\"middle\" fill=\"#333333\" stroke=\"white\" stroke-width=\"4\" paint-order=\"stroke\">{}</text>\n",
+        x,
+        y,
+        escape(content)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use roxmltree::Document;
+
+    use super::*;
+    use crate::project::project::Project;
+
+    const CONFIG: &str = r#"threatDragonVersion: "2.2.0"
+title: "Shop"
+owner: "Team"
+description: ""
+trustBoundaries:
+  - name: Internet
+    description: ""
+    limitOfAccess: ""
+    levelOfAuthorization: ""
+assets: []
+diagrams: []
+"#;
+
+    const DIAGRAM: &str = r#"title: "Shop & <Orders>"
+description: ""
+nodes:
+  - name: User
+    type: actor
+    description: ""
+    trustBoundary: Internet
+    threats: []
+  - name: Web "API"
+    type: process
+    description: ""
+    threats: []
+  - name: Orders
+    type: store
+    description: ""
+    threats: []
+  - name: Login
+    type: flow
+    description: ""
+    source: User
+    destination: Web "API"
+    threats: []
+  - name: Save
+    type: flow
+    description: ""
+    source: Web "API"
+    destination: Orders
+    threats: []
+"#;
+
+    fn svg() -> String {
+        let project = Project::parse(
+            (Path::new("config.yaml"), CONFIG),
+            (Path::new("threats.yaml"), "[]\n"),
+            &[(PathBuf::from("shop.yaml"), DIAGRAM.to_string())],
+            None,
+        )
+        .unwrap();
+        render_svg(&project.threat_modeling().detail.diagrams[0])
+    }
+
+    #[test]
+    fn nodes_flows_and_boundaries_are_drawn() {
+        let svg = svg();
+        let document = Document::parse(&svg).unwrap();
+        let groups = |class: &str| -> Vec<String> {
+            document
+                .descendants()
+                .filter(|node| node.attribute("class") == Some(class))
+                .map(|node| node.attribute("data-name").unwrap_or_default().to_string())
+                .collect()
+        };
+        let mut nodes = [
+            groups("td-cell td-actor"),
+            groups("td-cell td-process"),
+            groups("td-cell td-store"),
+        ]
+        .concat();
+        nodes.sort();
+        assert_eq!(nodes, vec!["Orders", "User", "Web \"API\""]);
+        let mut flows = groups("td-cell td-flow");
+        flows.sort();
+        assert_eq!(flows, vec!["Login", "Save"]);
+        assert_eq!(groups("td-boundary"), vec!["Internet"]);
+
+        // Each flow is a line ending with an arrow.
+        let arrows = document
+            .descendants()
+            .filter(|node| {
+                node.attribute("marker-end")
+                    .is_some_and(|marker| marker.starts_with("url(#td-"))
+            })
+            .count();
+        assert_eq!(arrows, 2);
+
+        // Boundaries are drawn first, below the flows and the nodes.
+        let first_group = document
+            .descendants()
+            .find(|node| node.tag_name().name() == "g")
+            .unwrap();
+        assert_eq!(first_group.attribute("class"), Some("td-boundary"));
+    }
+
+    #[test]
+    fn names_are_escaped() {
+        let svg = svg();
+        assert!(svg.contains("<title>Shop &amp; &lt;Orders&gt;</title>"));
+        assert!(svg.contains("data-name=\"Web &quot;API&quot;\""));
+        let document = Document::parse(&svg).unwrap();
+        let title = document
+            .descendants()
+            .find(|node| node.tag_name().name() == "title")
+            .and_then(|node| node.text());
+        assert_eq!(title, Some("Shop & <Orders>"));
+    }
+}
@@ -0,0 +1,305 @@
+use crate::{
+    exports::{dot, mermaid, otm, plantuml, sarif},
+    render::svg::render_svg,
+    reports::{html::html_reports, markdown::markdown_reports, xls::xls_reports},
+};
+
+use super::{
+    output_sink::OutputSink,
+    report_error::ReportError,
+    report_generator::{file_name, ReportGenerator, ResolvedModel},
+};
+
+/// `<name>.json`, the model opened by OWASP Threat Dragon.
+pub struct JsonModel;
+
+impl ReportGenerator for JsonModel {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn description(&self) -> &'static str {
+        "Threat Dragon model"
+    }
+
+    fn generate(
+        &self,
+        model: &ResolvedModel,
+        sink: &mut dyn OutputSink,
+    ) -> Result<(), ReportError> {
+        let content = serde_json::to_vec_pretty(&model.threat_modeling)
+            .map_err(|e| ReportError::Serialize(format!("{}", e)))?;
+        sink.write(&format!("{}.json", model.name), &content)
+    }
+}
+
+/// `<name>.otm.json`, the Open Threat Model of the project.
+pub struct OtmExport;
+
+impl ReportGenerator for OtmExport {
+    fn name(&self) -> &'static str {
+        "otm"
+    }
+
+    fn description(&self) -> &'static str {
+        "Open Threat Model"
+    }
+
+    fn generate(
+        &self,
+        model: &ResolvedModel,
+        sink: &mut dyn OutputSink,
+    ) -> Result<(), ReportError> {
+        let otm = otm::to_otm(model.project).map_err(ReportError::Export)?;
+        let content = serde_json::to_vec_pretty(&otm)
+            .map_err(|e| ReportError::Serialize(format!("{}", e)))?;
+        sink.write(&format!("{}.otm.json", model.name), &content)
+    }
+}
+
+/// `<name>.sarif`, the findings for code scanning.
+pub struct SarifExport;
+
+impl ReportGenerator for SarifExport {
+    fn name(&self) -> &'static str {
+        "sarif"
+    }
+
+    fn description(&self) -> &'static str {
+        "Findings as a SARIF log"
+    }
+
+    fn generate(
+        &self,
+        model: &ResolvedModel,
+        sink: &mut dyn OutputSink,
+    ) -> Result<(), ReportError> {
+        let content = serde_json::to_vec_pretty(&sarif::to_sarif(model.project, &model.findings))
+            .map_err(|e| ReportError::Serialize(format!("{}", e)))?;
+        sink.write(&format!("{}.sarif", model.name), &content)
+    }
+}
+
+/// `<name>.html`, the interactive report of all diagrams.
+pub struct HtmlReport;
+
+impl ReportGenerator for HtmlReport {
+    fn name(&self) -> &'static str {
+        "html"
+    }
+
+    fn description(&self) -> &'static str {
+        "Interactive HTML report"
+    }
+
+    fn generate(
+        &self,
+        model: &ResolvedModel,
+        sink: &mut dyn OutputSink,
+    ) -> Result<(), ReportError> {
+        let mut content = Vec::new();
+        html_reports::write_report(
+            &mut content,
+            &model.threat_modeling,
+            &model.diagrams,
+            &model.project.config,
+            &model.project.threats,
+        )
+        .map_err(ReportError::Html)?;
+        sink.write(&format!("{}.html", model.name), &content)
+    }
+}
+
+/// `<title>.svg` of each diagram.
+pub struct SvgImages;
+
+impl ReportGenerator for SvgImages {
+    fn name(&self) -> &'static str {
+        "svg"
+    }
+
+    fn description(&self) -> &'static str {
+        "SVG image of each diagram"
+    }
+
+    fn generate(
+        &self,
+        model: &ResolvedModel,
+        sink: &mut dyn OutputSink,
+    ) -> Result<(), ReportError> {
+        for diagram in model.affected_cells() {
+            sink.write(
+                &file_name(&diagram.title, "svg"),
+                render_svg(diagram).as_bytes(),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// `<title>.png` of each diagram.
+pub struct PngImages;
+
+impl ReportGenerator for PngImages {
+    fn name(&self) -> &'static str {
+        "png"
+    }
+
+    fn description(&self) -> &'static str {
+        "PNG image of each diagram"
+    }
+
+    fn generate(
+        &self,
+        model: &ResolvedModel,
+        sink: &mut dyn OutputSink,
+    ) -> Result<(), ReportError> {
+        for diagram in model.affected_cells() {
+            if let Some(png) = model.png(&diagram.title)? {
+                sink.write(&file_name(&diagram.title, "png"), png)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `<title>.xlsx` of each diagram, with its image.
+pub struct XlsxReport;
+
+impl ReportGenerator for XlsxReport {
+    fn name(&self) -> &'static str {
+        "xlsx"
+    }
+
+    fn description(&self) -> &'static str {
+        "Excel workbook of each diagram"
+    }
+
+    fn generate(
+        &self,
+        model: &ResolvedModel,
+        sink: &mut dyn OutputSink,
+    ) -> Result<(), ReportError> {
+        for diagram in model.affected_diagrams() {
+            let mut content = Vec::new();
+            xls_reports::write_report(
+                &mut content,
+                diagram,
+                &model.project.config,
+                &model.project.threats,
+                model.png(&diagram.title)?,
+            )
+            .map_err(ReportError::Excel)?;
+            sink.write(&file_name(&diagram.title, "xlsx"), &content)?;
+        }
+        Ok(())
+    }
+}
+
+/// `<title>.md` of each diagram and `index.md`.
+pub struct MarkdownReport;
+
+impl ReportGenerator for MarkdownReport {
+    fn name(&self) -> &'static str {
+        "md"
+    }
+
+    fn description(&self) -> &'static str {
+        "Markdown document of each diagram and index"
+    }
+
+    fn generate(
+        &self,
+        model: &ResolvedModel,
+        sink: &mut dyn OutputSink,
+    ) -> Result<(), ReportError> {
+        let config = &model.project.config;
+        let threats = &model.project.threats;
+        for diagram in model.affected_diagrams() {
+            let image = sink.contains(&file_name(&diagram.title, "png"));
+            let mut content = Vec::new();
+            markdown_reports::write_report(&mut content, diagram, config, threats, image)
+                .map_err(ReportError::Markdown)?;
+            sink.write(&file_name(&diagram.title, "md"), &content)?;
+        }
+        let mut content = Vec::new();
+        markdown_reports::write_index(&mut content, &model.diagrams, config, threats)
+            .map_err(ReportError::Markdown)?;
+        sink.write(markdown_reports::INDEX_FILE_NAME, &content)
+    }
+}
+
+/// `<title>.dot` of each diagram.
+pub struct DotExport;
+
+impl ReportGenerator for DotExport {
+    fn name(&self) -> &'static str {
+        "dot"
+    }
+
+    fn description(&self) -> &'static str {
+        "Graphviz description of each diagram"
+    }
+
+    fn generate(
+        &self,
+        model: &ResolvedModel,
+        sink: &mut dyn OutputSink,
+    ) -> Result<(), ReportError> {
+        for diagram in model.affected_diagrams() {
+            let content = dot::render_dot(diagram, &model.project.threats);
+            sink.write(&file_name(&diagram.title, "dot"), content.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+/// `<title>.mmd` of each diagram.
+pub struct MermaidExport;
+
+impl ReportGenerator for MermaidExport {
+    fn name(&self) -> &'static str {
+        "mermaid"
+    }
+
+    fn description(&self) -> &'static str {
+        "Mermaid flowchart of each diagram"
+    }
+
+    fn generate(
+        &self,
+        model: &ResolvedModel,
+        sink: &mut dyn OutputSink,
+    ) -> Result<(), ReportError> {
+        for diagram in model.affected_diagrams() {
+            let content = mermaid::render_mermaid(diagram, &model.project.config);
+            sink.write(&file_name(&diagram.title, "mmd"), content.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+/// `<title>.puml` of each diagram.
+pub struct PlantUmlExport;
+
+impl ReportGenerator for PlantUmlExport {
+    fn name(&self) -> &'static str {
+        "plantuml"
+    }
+
+    fn description(&self) -> &'static str {
+        "PlantUML diagram of each diagram"
+    }
+
+    fn generate(
+        &self,
+        model: &ResolvedModel,
+        sink: &mut dyn OutputSink,
+    ) -> Result<(), ReportError> {
+        for diagram in model.affected_diagrams() {
+            let content = plantuml::render_plantuml(diagram, &model.project.config);
+            sink.write(&file_name(&diagram.title, "puml"), content.as_bytes())?;
+        }
+        Ok(())
+    }
+}
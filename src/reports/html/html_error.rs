@@ -0,0 +1,16 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone)]
+pub enum HtmlError {
+    Serialize(String),
+    Save(String),
+}
+
+impl Display for HtmlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HtmlError::Serialize(e) => write!(f, "cannot serialize html report data: {}", e),
+            HtmlError::Save(e) => write!(f, "cannot save html report: {}", e),
+        }
+    }
+}
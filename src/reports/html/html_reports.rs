@@ -0,0 +1,483 @@
+use std::{collections::BTreeMap, io::Write};
+
+use serde_json::{json, Value};
+
+use crate::{
+    config::config::Config,
+    input::{
+        input_diagram::{InputDiagram, TypeNode},
+        threat::{Severity, Status, Threat},
+    },
+    render::svg::render_svg,
+    reports::sections::{self, Table},
+    threat_dragon_modeling::general::ThreatModeling,
+    xml::xml::escape,
+};
+
+use super::html_error::HtmlError;
+
+const STYLE: &str = r#"
+body { font-family: Helvetica, Arial, sans-serif; margin: 0 auto; max-width: 1400px; padding: 1em 2em; color: #222; }
+h1 { border-bottom: 2px solid #333; padding-bottom: .3em; }
+h2 { margin-top: 2em; border-bottom: 1px solid #ccc; }
+.stats { display: flex; flex-wrap: wrap; gap: 1em; }
+.stat { border: 1px solid #ccc; border-radius: 6px; padding: .6em 1.2em; min-width: 7em; }
+.stat strong { display: block; font-size: 1.8em; }
+table { border-collapse: collapse; margin: 1em 0; width: 100%; }
+th, td { border: 1px solid #ccc; padding: .35em .6em; text-align: left; vertical-align: top; }
+th { background: #f0f0f0; cursor: pointer; user-select: none; }
+th.sorted-asc::after { content: " \25B2"; }
+th.sorted-desc::after { content: " \25BC"; }
+tr:nth-child(even) td { background: #fafafa; }
+.td-view { display: flex; gap: 1em; align-items: flex-start; }
+.td-view svg { flex: 1 1 auto; max-width: 75%; height: auto; border: 1px solid #ccc; }
+.td-details { flex: 0 0 25%; border: 1px solid #ccc; border-radius: 6px; padding: .6em 1em; }
+.td-cell { cursor: pointer; }
+.td-cell.selected ellipse, .td-cell.selected rect, .td-cell.selected line:not(.td-hit) { stroke: #1f6feb; }
+.threat { border-left: 4px solid #999; padding: .2em .6em; margin: .6em 0; }
+.threat.High { border-color: #d73a49; }
+.threat.Medium { border-color: #e36209; }
+.threat.Low { border-color: #2188ff; }
+"#;
+
+const SCRIPT: &str = r#"
+function showThreats(section, name) {
+  var threats = (MODEL[section.dataset.diagram] || {})[name] || [];
+  var details = section.querySelector('.td-details');
+  var html = '<h3>' + escapeHtml(name) + '</h3>';
+  if (threats.length === 0) {
+    html += '<p>No threat.</p>';
+  }
+  threats.forEach(function (threat) {
+    html += '<div class="threat ' + escapeHtml(threat.severity) + '"><strong>' + escapeHtml(threat.title) + '</strong>'
+      + '<br>' + escapeHtml(threat.type) + ' | ' + escapeHtml(threat.severity) + ' | ' + escapeHtml(threat.status)
+      + '<p>' + escapeHtml(threat.description) + '</p><p><em>Mitigation:</em> ' + escapeHtml(threat.mitigation) + '</p></div>';
+  });
+  details.innerHTML = html;
+  section.querySelectorAll('.td-cell').forEach(function (cell) {
+    cell.classList.toggle('selected', cell.dataset.name === name);
+  });
+}
+function escapeHtml(text) {
+  var div = document.createElement('div');
+  div.textContent = text;
+  return div.innerHTML;
+}
+function sortTable(header) {
+  var table = header.closest('table');
+  var index = Array.prototype.indexOf.call(header.parentNode.children, header);
+  var ascending = !header.classList.contains('sorted-asc');
+  table.querySelectorAll('th').forEach(function (th) { th.classList.remove('sorted-asc', 'sorted-desc'); });
+  header.classList.add(ascending ? 'sorted-asc' : 'sorted-desc');
+  var body = table.tBodies[0];
+  var rows = Array.prototype.slice.call(body.rows);
+  rows.sort(function (a, b) {
+    var x = a.cells[index].textContent, y = b.cells[index].textContent;
+    var order = (!isNaN(x) && !isNaN(y) && x !== '' && y !== '') ? x - y : x.localeCompare(y);
+    return ascending ? order : -order;
+  });
+  rows.forEach(function (row) { body.appendChild(row); });
+}
+document.querySelectorAll('section.diagram').forEach(function (section) {
+  section.querySelectorAll('.td-cell').forEach(function (cell) {
+    cell.addEventListener('click', function () { showThreats(section, cell.dataset.name); });
+  });
+});
+document.querySelectorAll('th').forEach(function (th) {
+  th.addEventListener('click', function () { sortTable(th); });
+});
+"#;
+
+/// Write the HTML report of all diagrams to `writer`.
+pub fn write_report<W: Write>(
+    mut writer: W,
+    threat_modeling: &ThreatModeling,
+    input_diagrams: &[InputDiagram],
+    config: &Config,
+    threats: &[Threat],
+) -> Result<(), HtmlError> {
+    let mut sorted_diagrams: Vec<&InputDiagram> = input_diagrams.iter().collect();
+    sorted_diagrams.sort_by(|a, b| a.title.cmp(&b.title));
+
+    let mut body = format!("<h1>{}</h1>\n", escape(&config.title));
+    if !config.description.is_empty() {
+        body.push_str(&format!("<p>{}</p>\n", escape(&config.description)));
+    }
+    body.push_str(&format!("<p>Owner: {}</p>\n", escape(&config.owner)));
+    body.push_str(&summary(&sorted_diagrams, threats));
+
+    let mut model = BTreeMap::new();
+    sorted_diagrams
+        .iter()
+        .enumerate()
+        .for_each(|(index, input_diagram)| {
+            let diagram = threat_modeling
+                .detail
+                .diagrams
+                .iter()
+                .find(|diagram| diagram.title == input_diagram.title);
+            body.push_str(&format!(
+                "<section class=\"diagram\" id=\"diagram-{}\" data-diagram=\"{}\">\n<h2>{}</h2>\n",
+                index,
+                escape(&input_diagram.title),
+                escape(&input_diagram.title)
+            ));
+            if !input_diagram.description.is_empty() {
+                body.push_str(&format!("<p>{}</p>\n", escape(&input_diagram.description)));
+            }
+            body.push_str(&format!(
+                "<p>Methodology: {}</p>\n",
+                input_diagram.methodology()
+            ));
+            if let Some(diagram) = diagram {
+                body.push_str(&format!(
+                    "<div class=\"td-view\">\n{}<aside class=\"td-details\"><p>Click a node or a flow to display its threats.</p></aside>\n</div>\n",
+                    render_svg(diagram)
+                ));
+            }
+            body.push_str("<h3>Threats</h3>\n");
+            body.push_str(&table(&sections::threats(input_diagram, threats)));
+            [
+                (
+                    "Software components",
+                    sections::software_components(input_diagram),
+                ),
+                ("Entry points", sections::entry_points(input_diagram)),
+                (
+                    "Trust boundaries",
+                    sections::trust_boundaries(input_diagram, config),
+                ),
+                ("Assets", sections::assets(input_diagram, config)),
+                ("Vectors", sections::vectors(input_diagram, threats)),
+                ("References", sections::references(input_diagram, threats)),
+            ]
+            .iter()
+            .for_each(|(title, section)| {
+                body.push_str(&format!(
+                    "<details>\n<summary>{} ({})</summary>\n{}</details>\n",
+                    title,
+                    section.rows.len(),
+                    table(section)
+                ));
+            });
+            body.push_str("</section>\n");
+            model.insert(input_diagram.title.clone(), diagram_model(input_diagram, threats));
+        });
+
+    let model =
+        serde_json::to_string(&model).map_err(|e| HtmlError::Serialize(format!("{}", e)))?;
+    let document = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n{}<script>\nconst MODEL = {};\n{}</script>\n</body>\n</html>\n",
+        escape(&config.title),
+        STYLE,
+        body,
+        // Keep the JSON from closing the script element
+        model.replace("</", "<\\/"),
+        SCRIPT
+    );
+    writer
+        .write_all(document.as_bytes())
+        .and_then(|_| writer.flush())
+        .map_err(|e| HtmlError::Save(format!("{}", e)))
+}
+
+/// Threats of each node and flow of the diagram, displayed on click.
+fn diagram_model(input_diagram: &InputDiagram, threats: &[Threat]) -> Value {
+    let mut nodes: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+    input_diagram.nodes.iter().for_each(|node| {
+        nodes.insert(node.name.clone(), Vec::new());
+    });
+    sections::node_threats(input_diagram, threats)
+        .iter()
+        .for_each(|(node, threat)| {
+            nodes.entry(node.name.clone()).or_default().push(json!({
+                "title": threat.title,
+                "type": threat.type_field.to_string(),
+                "severity": threat.severity.to_string(),
+                "status": threat.status.to_string(),
+                "description": threat.description,
+                "mitigation": threat.mitigation,
+            }));
+        });
+    json!(nodes)
+}
+
+fn summary(input_diagrams: &[&InputDiagram], threats: &[Threat]) -> String {
+    let components = input_diagrams
+        .iter()
+        .flat_map(|input_diagram| input_diagram.nodes.iter())
+        .filter(|node| node.type_node != TypeNode::Flow)
+        .count();
+    let flows = input_diagrams
+        .iter()
+        .flat_map(|input_diagram| input_diagram.nodes.iter())
+        .filter(|node| node.type_node == TypeNode::Flow)
+        .count();
+    // Threats used by at least one diagram
+    let mut used_threats: BTreeMap<String, &Threat> = BTreeMap::new();
+    input_diagrams.iter().for_each(|input_diagram| {
+        sections::node_threats(input_diagram, threats)
+            .into_iter()
+            .for_each(|(_, threat)| {
+                used_threats.insert(threat.title.clone(), threat);
+            });
+    });
+    let open = used_threats
+        .values()
+        .filter(|threat| threat.status == Status::Open)
+        .count();
+
+    let mut html = String::from("<h2>Summary</h2>\n<div class=\"stats\">\n");
+    [
+        ("Diagrams", input_diagrams.len()),
+        ("Components", components),
+        ("Flows", flows),
+        ("Threats", used_threats.len()),
+        ("Open threats", open),
+    ]
+    .iter()
+    .for_each(|(label, value)| {
+        html.push_str(&format!(
+            "<div class=\"stat\"><strong>{}</strong>{}</div>\n",
+            value, label
+        ));
+    });
+    html.push_str("</div>\n");
+
+    let statuses = [Status::Open, Status::Mitigated, Status::NotApplicable];
+    let mut by_severity = Table {
+        headers: std::iter::once("Severity".to_string())
+            .chain(statuses.iter().map(|status| status.to_string()))
+            .chain(std::iter::once("Total".to_string()))
+            .collect(),
+        ..Default::default()
+    };
+    [Severity::High, Severity::Medium, Severity::Low]
+        .iter()
+        .for_each(|severity| {
+            let of_severity: Vec<&&Threat> = used_threats
+                .values()
+                .filter(|threat| threat.severity == *severity)
+                .collect();
+            let mut row = vec![severity.to_string()];
+            statuses.iter().for_each(|status| {
+                row.push(
+                    of_severity
+                        .iter()
+                        .filter(|threat| threat.status == *status)
+                        .count()
+                        .to_string(),
+                )
+            });
+            row.push(of_severity.len().to_string());
+            by_severity.rows.push(row);
+        });
+    html.push_str(&table(&by_severity));
+
+    let mut by_diagram = Table {
+        headers: [
+            "Diagram",
+            "Methodology",
+            "Components",
+            "Flows",
+            "Threats",
+            "Open",
+        ]
+        .iter()
+        .map(|header| header.to_string())
+        .collect(),
+        ..Default::default()
+    };
+    input_diagrams
+        .iter()
+        .enumerate()
+        .for_each(|(index, input_diagram)| {
+            let node_threats = sections::node_threats(input_diagram, threats);
+            let flows = input_diagram
+                .nodes
+                .iter()
+                .filter(|node| node.type_node == TypeNode::Flow)
+                .count();
+            by_diagram
+                .links
+                .insert((by_diagram.rows.len(), 0), format!("#diagram-{}", index));
+            by_diagram.rows.push(vec![
+                input_diagram.title.clone(),
+                input_diagram.methodology().to_string(),
+                (input_diagram.nodes.len() - flows).to_string(),
+                flows.to_string(),
+                node_threats.len().to_string(),
+                node_threats
+                    .iter()
+                    .filter(|(_, threat)| threat.status == Status::Open)
+                    .count()
+                    .to_string(),
+            ]);
+        });
+    html.push_str(&table(&by_diagram));
+    html
+}
+
+fn table(table: &Table) -> String {
+    if table.rows.is_empty() {
+        return "<p><em>None</em></p>\n".to_string();
+    }
+    let mut html = String::from("<table>\n<thead><tr>");
+    table
+        .headers
+        .iter()
+        .for_each(|header| html.push_str(&format!("<th>{}</th>", escape(header))));
+    html.push_str("</tr></thead>\n<tbody>\n");
+    table.rows.iter().enumerate().for_each(|(row_index, row)| {
+        html.push_str("<tr>");
+        row.iter().enumerate().for_each(|(col_index, cell)| {
+            match table.links.get(&(row_index, col_index)) {
+                Some(url) => html.push_str(&format!(
+                    "<td><a href=\"{}\">{}</a></td>",
+                    escape(url),
+                    escape(cell)
+                )),
+                None => html.push_str(&format!("<td>{}</td>", escape(cell))),
+            }
+        });
+        html.push_str("</tr>\n");
+    });
+    html.push_str("</tbody>\n</table>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+    use crate::project::project::Project;
+
+    const CONFIG: &str = r#"threatDragonVersion: "2.2.0"
+title: "Shop & Co"
+owner: "Team"
+description: "Online <shop>"
+trustBoundaries: []
+assets: []
+diagrams: []
+"#;
+
+    const THREATS: &str = r#"- title: "Spoofing of the user"
+  status: "Open"
+  severity: "High"
+  type: "Spoofing"
+  description: "Stolen session </script>"
+  mitigation: "Multi-factor authentication"
+  vector: ""
+- title: "Tampering of the orders"
+  status: "Mitigated"
+  severity: "Medium"
+  type: "Tampering"
+  description: "Orders changed in transit"
+  mitigation: "TLS"
+  vector: ""
+"#;
+
+    const DIAGRAM: &str = r#"title: "Orders"
+description: ""
+nodes:
+  - name: User
+    type: actor
+    description: ""
+    threats:
+      - "Spoofing of the user"
+  - name: Web
+    type: process
+    description: ""
+    threats: []
+  - name: Order
+    type: flow
+    description: ""
+    source: User
+    destination: Web
+    threats:
+      - "Tampering of the orders"
+"#;
+
+    fn report() -> String {
+        let project = Project::parse(
+            (Path::new("config.yaml"), CONFIG),
+            (Path::new("threats.yaml"), THREATS),
+            &[(PathBuf::from("orders.yaml"), DIAGRAM.to_string())],
+            None,
+        )
+        .unwrap();
+        let mut html = Vec::new();
+        write_report(
+            &mut html,
+            &project.threat_modeling(),
+            &project.diagrams_with_children(),
+            &project.config,
+            &project.threats,
+        )
+        .unwrap();
+        String::from_utf8(html).unwrap()
+    }
+
+    #[test]
+    fn summary_of_the_project() {
+        let html = report();
+        assert!(html.contains("<title>Shop &amp; Co</title>"));
+        assert!(html.contains("<p>Online &lt;shop&gt;</p>"));
+        for (value, label) in [
+            (1, "Diagrams"),
+            (2, "Components"),
+            (1, "Flows"),
+            (2, "Threats"),
+            (1, "Open threats"),
+        ] {
+            assert!(
+                html.contains(&format!(
+                    "<div class=\"stat\"><strong>{}</strong>{}</div>",
+                    value, label
+                )),
+                "{}",
+                label
+            );
+        }
+        assert!(html.contains("<tr><td>High</td><td>1</td><td>0</td><td>0</td><td>1</td></tr>"));
+        assert!(html.contains(
+            "<tr><td><a href=\"#diagram-0\">Orders</a></td><td>STRIDE</td><td>2</td><td>1</td><td>2</td><td>1</td></tr>"
+        ));
+    }
+
+    #[test]
+    fn diagrams_with_their_image_and_threats() {
+        let html = report();
+        let section = &html[html
+            .find("<section class=\"diagram\" id=\"diagram-0\"")
+            .unwrap()..];
+        assert!(section.contains("<h2>Orders</h2>"));
+        assert!(section.contains("<svg xmlns=\"http://www.w3.org/2000/svg\" class=\"td-diagram\""));
+        assert!(section.contains(
+            "<td>User</td><td>actor</td><td>Spoofing</td><td>Stolen session &lt;/script&gt;</td>"
+        ));
+        assert!(section.contains("<td>Order</td><td>flow</td><td>Tampering</td>"));
+        assert!(section.contains("<summary>Entry points (1)</summary>"));
+    }
+
+    #[test]
+    fn threats_of_the_nodes_are_embedded_for_the_clicks() {
+        let html = report();
+        let model = html
+            .split("const MODEL = ")
+            .nth(1)
+            .and_then(|rest| rest.split(";\n").next())
+            .unwrap();
+        // The description may not close the script element.
+        assert!(!model.contains("</script>"));
+        let model: Value = serde_json::from_str(&model.replace("<\\/", "</")).unwrap();
+        assert_eq!(model["Orders"]["User"][0]["title"], "Spoofing of the user");
+        assert_eq!(
+            model["Orders"]["User"][0]["description"],
+            "Stolen session </script>"
+        );
+        assert_eq!(model["Orders"]["Order"][0]["status"], "Mitigated");
+        assert_eq!(model["Orders"]["Web"], json!([]));
+    }
+}
@@ -0,0 +1,2 @@
+pub mod html_error;
+pub mod html_reports;
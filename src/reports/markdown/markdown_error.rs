@@ -0,0 +1,14 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone)]
+pub enum MarkdownError {
+    Save(String),
+}
+
+impl Display for MarkdownError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MarkdownError::Save(e) => write!(f, "cannot save markdown report: {}", e),
+        }
+    }
+}
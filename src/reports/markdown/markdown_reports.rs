@@ -0,0 +1,387 @@
+use std::{fs, io::Write, path::Path};
+
+use crate::{
+    config::config::Config,
+    diff::diff_reports::kind_label,
+    history::history::{self, History},
+    input::{
+        input_diagram::{InputDiagram, TypeNode},
+        threat::{Severity, Status, Threat},
+    },
+    reports::sections::{self, Table},
+};
+
+use super::markdown_error::MarkdownError;
+
+pub const INDEX_FILE_NAME: &str = "index.md";
+pub const HISTORY_FILE_NAME: &str = "history.md";
+
+/// Write the document of the diagram to `writer`, with a link to
+/// `<title>.png` when `image` is set.
+pub fn write_report<W: Write>(
+    mut writer: W,
+    input_diagram: &InputDiagram,
+    config: &Config,
+    threats: &[Threat],
+    image: bool,
+) -> Result<(), MarkdownError> {
+    writer
+        .write_all(render_report(input_diagram, config, threats, image).as_bytes())
+        .map_err(|e| MarkdownError::Save(format!("{}", e)))
+}
+
+fn render_report(
+    input_diagram: &InputDiagram,
+    config: &Config,
+    threats: &[Threat],
+    image: bool,
+) -> String {
+    let mut document = format!("# {}\n\n", input_diagram.title);
+    if !input_diagram.description.is_empty() {
+        document.push_str(&format!("{}\n\n", input_diagram.description));
+    }
+    document.push_str(&format!(
+        "Methodology: {} | [Index]({})\n\n",
+        input_diagram.methodology(),
+        INDEX_FILE_NAME
+    ));
+
+    if image {
+        let image_name = format!("{}.png", input_diagram.title);
+        document.push_str(&format!(
+            "![{}]({})\n\n",
+            input_diagram.title,
+            link_target(&image_name)
+        ));
+    }
+
+    push_section(
+        &mut document,
+        "Software components",
+        &sections::software_components(input_diagram),
+    );
+    push_section(
+        &mut document,
+        "Entry points",
+        &sections::entry_points(input_diagram),
+    );
+    push_section(
+        &mut document,
+        "Trust boundaries",
+        &sections::trust_boundaries(input_diagram, config),
+    );
+    push_section(
+        &mut document,
+        "Assets",
+        &sections::assets(input_diagram, config),
+    );
+
+    document.push_str("## Threats\n\n");
+    let node_threats = sections::node_threats(input_diagram, threats);
+    if node_threats.is_empty() {
+        document.push_str("_None_\n\n");
+    }
+    [Severity::High, Severity::Medium, Severity::Low]
+        .iter()
+        .for_each(|severity| {
+            let selection: Vec<_> = node_threats
+                .iter()
+                .filter(|(_, threat)| threat.severity == *severity)
+                .cloned()
+                .collect();
+            if !selection.is_empty() {
+                document.push_str(&format!("### {} ({})\n\n", severity, selection.len()));
+                push_table(
+                    &mut document,
+                    &sections::threats_of(input_diagram, &selection),
+                );
+            }
+        });
+
+    push_section(
+        &mut document,
+        "Vectors",
+        &sections::vectors(input_diagram, threats),
+    );
+    push_section(
+        &mut document,
+        "References",
+        &sections::references(input_diagram, threats),
+    );
+    document
+}
+
+/// Write the index of the diagram documents to `writer`.
+pub fn write_index<W: Write>(
+    mut writer: W,
+    input_diagrams: &[InputDiagram],
+    config: &Config,
+    threats: &[Threat],
+) -> Result<(), MarkdownError> {
+    writer
+        .write_all(render_index(input_diagrams, config, threats).as_bytes())
+        .map_err(|e| MarkdownError::Save(format!("{}", e)))
+}
+
+fn render_index(input_diagrams: &[InputDiagram], config: &Config, threats: &[Threat]) -> String {
+    let mut document = format!("# {}\n\n", config.title);
+    if !config.description.is_empty() {
+        document.push_str(&format!("{}\n\n", config.description));
+    }
+    document.push_str(&format!("Owner: {}\n\n", config.owner));
+
+    let mut table = Table {
+        headers: [
+            "Diagram",
+            "Methodology",
+            "Components",
+            "Flows",
+            "Threats",
+            "Open",
+            "Open High",
+        ]
+        .iter()
+        .map(|header| header.to_string())
+        .collect(),
+        ..Default::default()
+    };
+    let mut sorted_diagrams: Vec<&InputDiagram> = input_diagrams.iter().collect();
+    sorted_diagrams.sort_by(|a, b| a.title.cmp(&b.title));
+    sorted_diagrams.iter().for_each(|input_diagram| {
+        let node_threats = sections::node_threats(input_diagram, threats);
+        let open = node_threats
+            .iter()
+            .filter(|(_, threat)| threat.status == Status::Open);
+        let flows = input_diagram
+            .nodes
+            .iter()
+            .filter(|node| node.type_node == TypeNode::Flow)
+            .count();
+        table
+            .links
+            .insert((table.rows.len(), 0), format!("{}.md", input_diagram.title));
+        table.rows.push(vec![
+            input_diagram.title.clone(),
+            input_diagram.methodology().to_string(),
+            (input_diagram.nodes.len() - flows).to_string(),
+            flows.to_string(),
+            node_threats.len().to_string(),
+            open.clone().count().to_string(),
+            open.filter(|(_, threat)| threat.severity == Severity::High)
+                .count()
+                .to_string(),
+        ]);
+    });
+    push_section(&mut document, "Diagrams", &table);
+    document
+}
+
+/// Changelog of the threat model, newest commit first, followed by the time
+/// to mitigate of each severity.
+pub fn create_history(
+    output_folder: &Path,
+    config: &Config,
+    history: &History,
+) -> Result<(), MarkdownError> {
+    let mut document = format!("# {} history\n\n", config.title);
+    push_section(
+        &mut document,
+        "Time to mitigate",
+        &history::time_to_mitigate_table(history),
+    );
+    push_section(
+        &mut document,
+        "Mitigated threats",
+        &history::mitigations_table(history),
+    );
+
+    document.push_str("## Changelog\n\n");
+    let mut commits: Vec<&String> = Vec::new();
+    history.events.iter().rev().for_each(|event| {
+        if !commits.contains(&&event.commit.hash) {
+            commits.push(&event.commit.hash);
+        }
+    });
+    for hash in commits {
+        let events: Vec<_> = history
+            .events
+            .iter()
+            .filter(|event| event.commit.hash == *hash)
+            .collect();
+        let commit = &events[0].commit;
+        document.push_str(&format!(
+            "### {} {} ({})\n\n{}\n\n",
+            commit.date,
+            history::short_hash(&commit.hash),
+            commit.author,
+            commit.subject
+        ));
+        events.iter().for_each(|event| {
+            let detail = if event.detail.is_empty() {
+                String::new()
+            } else {
+                format!(": {}", event.detail)
+            };
+            document.push_str(&format!(
+                "- **{}** {} {}{}\n",
+                kind_label(event.kind),
+                event.element_type.to_lowercase(),
+                event.element,
+                detail
+            ));
+        });
+        document.push('\n');
+    }
+
+    fs::write(output_folder.join(HISTORY_FILE_NAME), document)
+        .map_err(|e| MarkdownError::Save(format!("{}", e)))
+}
+
+fn push_section(document: &mut String, title: &str, table: &Table) {
+    document.push_str(&format!("## {}\n\n", title));
+    push_table(document, table);
+}
+
+/// GitHub-flavoured table, or a placeholder when the table has no row.
+fn push_table(document: &mut String, table: &Table) {
+    if table.rows.is_empty() {
+        document.push_str("_None_\n\n");
+        return;
+    }
+    document.push_str(&format!("| {} |\n", table.headers.join(" | ")));
+    document.push_str(&format!("|{}\n", "---|".repeat(table.headers.len())));
+    table.rows.iter().enumerate().for_each(|(row_index, row)| {
+        let cells: Vec<String> = row
+            .iter()
+            .enumerate()
+            .map(
+                |(col_index, cell)| match table.links.get(&(row_index, col_index)) {
+                    Some(url) => format!("[{}]({})", escape(cell), link_target(url)),
+                    None => escape(cell),
+                },
+            )
+            .collect();
+        document.push_str(&format!("| {} |\n", cells.join(" | ")));
+    });
+    document.push('\n');
+}
+
+fn escape(cell: &str) -> String {
+    cell.replace('|', "\\|").replace('\n', "<br>")
+}
+
+/// Link target usable in markdown, spaces of relative paths are encoded.
+fn link_target(target: &str) -> String {
+    target.replace(' ', "%20")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+    use crate::project::project::Project;
+
+    const CONFIG: &str = r#"threatDragonVersion: "2.2.0"
+title: "Shop"
+owner: "Team"
+description: ""
+trustBoundaries: []
+assets: []
+diagrams: []
+"#;
+
+    const THREATS: &str = r#"- title: "Spoofing of the user"
+  status: "Open"
+  severity: "High"
+  type: "Spoofing"
+  description: "Stolen session | replayed\nlater"
+  mitigation: ""
+  vector: ""
+- title: "Tampering of the orders"
+  status: "Mitigated"
+  severity: "Low"
+  type: "Tampering"
+  description: "Orders changed in transit"
+  mitigation: "TLS"
+  vector: ""
+"#;
+
+    const DIAGRAM: &str = r#"title: "Order flow"
+description: "Orders of the shop"
+nodes:
+  - name: User
+    type: actor
+    description: ""
+    threats:
+      - "Spoofing of the user"
+  - name: Web
+    type: process
+    description: ""
+    threats: []
+  - name: Order
+    type: flow
+    description: ""
+    source: User
+    destination: Web
+    threats:
+      - "Tampering of the orders"
+"#;
+
+    fn project() -> Project {
+        Project::parse(
+            (Path::new("config.yaml"), CONFIG),
+            (Path::new("threats.yaml"), THREATS),
+            &[(PathBuf::from("orders.yaml"), DIAGRAM.to_string())],
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn report_of_a_diagram() {
+        let project = project();
+        let report = render_report(
+            &project.diagrams[0],
+            &project.config,
+            &project.threats,
+            true,
+        );
+        assert!(report.starts_with(
+            "# Order flow\n\nOrders of the shop\n\nMethodology: STRIDE | [Index](index.md)\n\n![Order flow](Order%20flow.png)\n\n"
+        ));
+        assert!(report.contains("## Software components\n\n| "));
+        assert!(report.contains("## Trust boundaries\n\n_None_\n\n"));
+
+        // Threats are grouped by severity, the cells escaped.
+        let high = report.find("### High (1)").unwrap();
+        let low = report.find("### Low (1)").unwrap();
+        assert!(high < low);
+        assert!(!report.contains("### Medium"));
+        assert!(report[high..low]
+            .contains("| User | actor | Spoofing | Stolen session \\| replayed<br>later |"));
+        assert!(report[low..].contains("| Order | flow | Tampering | Orders changed in transit |"));
+    }
+
+    #[test]
+    fn report_without_image() {
+        let project = project();
+        let report = render_report(
+            &project.diagrams[0],
+            &project.config,
+            &project.threats,
+            false,
+        );
+        assert!(!report.contains("!["));
+    }
+
+    #[test]
+    fn index_of_the_diagrams() {
+        let project = project();
+        let index = render_index(&project.diagrams, &project.config, &project.threats);
+        assert!(index.starts_with("# Shop\n\nOwner: Team\n\n## Diagrams\n\n"));
+        assert!(index.contains(
+            "| Diagram | Methodology | Components | Flows | Threats | Open | Open High |\n|---|---|---|---|---|---|---|\n| [Order flow](Order%20flow.md) | STRIDE | 2 | 1 | 2 | 1 | 1 |\n"
+        ));
+    }
+}
@@ -0,0 +1,2 @@
+pub mod markdown_error;
+pub mod markdown_reports;
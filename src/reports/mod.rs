@@ -1 +1,8 @@
+pub mod generators;
+pub mod html;
+pub mod markdown;
+pub mod output_sink;
+pub mod report_error;
+pub mod report_generator;
+pub mod sections;
 pub mod xls;
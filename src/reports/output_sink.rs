@@ -0,0 +1,61 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use super::report_error::ReportError;
+
+/// Destination of the generated files, named relative to the output folder.
+pub trait OutputSink {
+    fn write(&mut self, name: &str, content: &[u8]) -> Result<(), ReportError>;
+
+    /// Whether the file is available, from this run or from a previous one,
+    /// so that a report can link it.
+    fn contains(&self, name: &str) -> bool;
+}
+
+/// Files written in a folder of the file system.
+pub struct FolderSink {
+    folder: PathBuf,
+}
+
+impl FolderSink {
+    /// Create the folder when it does not exist.
+    pub fn new(folder: &Path) -> Result<Self, ReportError> {
+        fs::create_dir_all(folder)
+            .map_err(|e| ReportError::Save(format!("{}: {}", folder.display(), e)))?;
+        Ok(Self {
+            folder: folder.to_path_buf(),
+        })
+    }
+}
+
+impl OutputSink for FolderSink {
+    fn write(&mut self, name: &str, content: &[u8]) -> Result<(), ReportError> {
+        let path = self.folder.join(name);
+        fs::write(&path, content)
+            .map_err(|e| ReportError::Save(format!("{}: {}", path.display(), e)))
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.folder.join(name).is_file()
+    }
+}
+
+/// Files kept in memory, by name.
+#[derive(Debug, Clone, Default)]
+pub struct MemorySink {
+    pub files: BTreeMap<String, Vec<u8>>,
+}
+
+impl OutputSink for MemorySink {
+    fn write(&mut self, name: &str, content: &[u8]) -> Result<(), ReportError> {
+        self.files.insert(name.to_string(), content.to_vec());
+        Ok(())
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.files.contains_key(name)
+    }
+}
@@ -0,0 +1,34 @@
+use std::fmt::Display;
+
+use crate::{
+    exports::export_error::ExportError, render::render_error::RenderError,
+    reports::html::html_error::HtmlError, reports::markdown::markdown_error::MarkdownError,
+    reports::xls::excel_error::ExcelError,
+};
+
+#[derive(Debug, Clone)]
+pub enum ReportError {
+    UnknownFormat(String),
+    Excel(ExcelError),
+    Html(HtmlError),
+    Markdown(MarkdownError),
+    Export(ExportError),
+    Render(RenderError),
+    Serialize(String),
+    Save(String),
+}
+
+impl Display for ReportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReportError::UnknownFormat(e) => write!(f, "unknown report format: {}", e),
+            ReportError::Excel(e) => write!(f, "{}", e),
+            ReportError::Html(e) => write!(f, "{}", e),
+            ReportError::Markdown(e) => write!(f, "{}", e),
+            ReportError::Export(e) => write!(f, "{}", e),
+            ReportError::Render(e) => write!(f, "{}", e),
+            ReportError::Serialize(e) => write!(f, "cannot serialize report: {}", e),
+            ReportError::Save(e) => write!(f, "cannot save report: {}", e),
+        }
+    }
+}
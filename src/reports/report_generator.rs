@@ -0,0 +1,291 @@
+use std::{
+    cell::OnceCell,
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+};
+
+use crate::{
+    input::input_diagram::InputDiagram,
+    project::project::Project,
+    render::{png::render_png, svg::render_svg},
+    threat_dragon_modeling::{diagram::Diagram, general::ThreatModeling},
+    validation::validator::ValidationIssue,
+};
+
+use super::{
+    generators::{
+        DotExport, HtmlReport, JsonModel, MarkdownReport, MermaidExport, OtmExport, PlantUmlExport,
+        PngImages, SarifExport, SvgImages, XlsxReport,
+    },
+    output_sink::OutputSink,
+    report_error::ReportError,
+};
+
+/// Output format of the `generate` command, selected by its name.
+pub trait ReportGenerator {
+    /// Name given to `--format`.
+    fn name(&self) -> &'static str;
+
+    fn description(&self) -> &'static str;
+
+    /// Write the files of the format to the sink.
+    fn generate(&self, model: &ResolvedModel, sink: &mut dyn OutputSink)
+        -> Result<(), ReportError>;
+}
+
+/// Every built-in format, in the order they are generated: the images come
+/// before the reports linking them.
+pub fn generators() -> Vec<Box<dyn ReportGenerator>> {
+    vec![
+        Box::new(JsonModel),
+        Box::new(OtmExport),
+        Box::new(SarifExport),
+        Box::new(HtmlReport),
+        Box::new(SvgImages),
+        Box::new(PngImages),
+        Box::new(XlsxReport),
+        Box::new(MarkdownReport),
+        Box::new(DotExport),
+        Box::new(MermaidExport),
+        Box::new(PlantUmlExport),
+    ]
+}
+
+pub fn names() -> Vec<&'static str> {
+    generators()
+        .iter()
+        .map(|generator| generator.name())
+        .collect()
+}
+
+/// Generators of the formats, in the order of [`generators`] whatever the
+/// order of `formats`.
+pub fn select(formats: &[String]) -> Result<Vec<Box<dyn ReportGenerator>>, ReportError> {
+    if let Some(unknown) = formats
+        .iter()
+        .find(|format| !names().contains(&format.as_str()))
+    {
+        return Err(ReportError::UnknownFormat(format!(
+            "{} (available: {})",
+            unknown,
+            names().join(", ")
+        )));
+    }
+    Ok(generators()
+        .into_iter()
+        .filter(|generator| formats.iter().any(|format| format == generator.name()))
+        .collect())
+}
+
+/// Project with its child diagrams and its Threat Dragon model, shared by the
+/// generators so that every output uses the same cell identifiers.
+pub struct ResolvedModel<'a> {
+    pub project: &'a Project,
+    /// Name of the files covering the whole project, such as `<name>.json`.
+    pub name: String,
+    pub diagrams: Vec<InputDiagram>,
+    pub threat_modeling: ThreatModeling,
+    pub findings: Vec<ValidationIssue>,
+    /// Diagrams whose own files are written, all of them when `None`.
+    pub affected: Option<&'a HashSet<String>>,
+    images: HashMap<String, OnceCell<Vec<u8>>>,
+}
+
+impl<'a> ResolvedModel<'a> {
+    pub fn new(project: &'a Project, name: &str) -> Self {
+        let threat_modeling = project.threat_modeling();
+        let images = threat_modeling
+            .detail
+            .diagrams
+            .iter()
+            .map(|diagram| (diagram.title.clone(), OnceCell::new()))
+            .collect();
+        Self {
+            project,
+            name: name.to_string(),
+            diagrams: project.diagrams_with_children(),
+            threat_modeling,
+            findings: project.findings(),
+            affected: None,
+            images,
+        }
+    }
+
+    pub fn with_affected(mut self, affected: Option<&'a HashSet<String>>) -> Self {
+        self.affected = affected;
+        self
+    }
+
+    pub fn is_affected(&self, title: &str) -> bool {
+        self.affected
+            .is_none_or(|affected| affected.contains(title))
+    }
+
+    /// Input diagrams whose own files are written.
+    pub fn affected_diagrams(&self) -> impl Iterator<Item = &InputDiagram> {
+        self.diagrams
+            .iter()
+            .filter(|diagram| self.is_affected(&diagram.title))
+    }
+
+    /// Threat Dragon diagrams whose own files are written.
+    pub fn affected_cells(&self) -> impl Iterator<Item = &Diagram> {
+        self.threat_modeling
+            .detail
+            .diagrams
+            .iter()
+            .filter(|diagram| self.is_affected(&diagram.title))
+    }
+
+    /// PNG image of a diagram, rendered once for all the generators.
+    pub fn png(&self, title: &str) -> Result<Option<&[u8]>, ReportError> {
+        let Some(image) = self.images.get(title) else {
+            return Ok(None);
+        };
+        if image.get().is_none() {
+            let diagram = self
+                .threat_modeling
+                .detail
+                .diagrams
+                .iter()
+                .find(|diagram| diagram.title == title);
+            if let Some(diagram) = diagram {
+                let png = render_png(&render_svg(diagram)).map_err(ReportError::Render)?;
+                let _ = image.set(png);
+            }
+        }
+        Ok(image.get().map(Vec::as_slice))
+    }
+}
+
+/// `<title>.<extension>`, the extension replacing the one of the title as
+/// with the files of the output folder.
+pub fn file_name(title: &str, extension: &str) -> String {
+    let mut path = PathBuf::from(title);
+    path.set_extension(extension);
+    path.to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::reports::output_sink::MemorySink;
+
+    const CONFIG: &str = r#"threatDragonVersion: "2.2.0"
+title: "Shop"
+owner: "Team"
+description: ""
+trustBoundaries: []
+assets: []
+diagrams: []
+"#;
+
+    fn diagram(title: &str) -> (PathBuf, String) {
+        (
+            PathBuf::from(format!("{}.yaml", title)),
+            format!(
+                r#"title: "{}"
+description: ""
+nodes:
+  - name: User
+    type: actor
+    description: ""
+    threats: []
+  - name: Web
+    type: process
+    description: ""
+    threats: []
+  - name: Login
+    type: flow
+    description: ""
+    source: User
+    destination: Web
+    threats: []
+"#,
+                title
+            ),
+        )
+    }
+
+    fn project() -> Project {
+        Project::parse(
+            (Path::new("config.yaml"), CONFIG),
+            (Path::new("threats.yaml"), "[]\n"),
+            &[diagram("Orders"), diagram("Payments")],
+            None,
+        )
+        .unwrap()
+    }
+
+    fn formats(formats: &[&str]) -> Vec<String> {
+        formats.iter().map(|format| format.to_string()).collect()
+    }
+
+    #[test]
+    fn selected_formats_keep_the_generation_order() {
+        let selected: Vec<&str> = select(&formats(&["md", "json", "png"]))
+            .unwrap()
+            .iter()
+            .map(|generator| generator.name())
+            .collect();
+        assert_eq!(selected, vec!["json", "png", "md"]);
+        assert!(select(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn unknown_formats_are_errors() {
+        match select(&formats(&["json", "pdf"])) {
+            Err(ReportError::UnknownFormat(message)) => {
+                assert_eq!(message, format!("pdf (available: {})", names().join(", ")))
+            }
+            _ => panic!("pdf is not a format"),
+        }
+    }
+
+    #[test]
+    fn every_format_is_written_to_memory() {
+        let project = project();
+        let model = ResolvedModel::new(&project, "shop");
+        let mut sink = MemorySink::default();
+        for generator in generators() {
+            generator.generate(&model, &mut sink).unwrap();
+        }
+
+        let mut expected = vec![
+            "shop.json".to_string(),
+            "shop.otm.json".to_string(),
+            "shop.sarif".to_string(),
+            "shop.html".to_string(),
+            "index.md".to_string(),
+        ];
+        for title in ["Orders", "Payments"] {
+            for extension in ["svg", "png", "xlsx", "md", "dot", "mmd", "puml"] {
+                expected.push(file_name(title, extension));
+            }
+        }
+        expected.sort();
+        assert_eq!(sink.files.keys().cloned().collect::<Vec<_>>(), expected);
+        assert!(sink.files["shop.json"].starts_with(b"{"));
+        assert!(sink.files["Orders.png"].starts_with(b"\x89PNG"));
+        // The document links the image written before it.
+        let markdown = String::from_utf8(sink.files["Orders.md"].clone()).unwrap();
+        assert!(markdown.contains("![Orders](Orders.png)"));
+    }
+
+    #[test]
+    fn only_the_affected_diagrams_are_written_again() {
+        let project = project();
+        let affected = HashSet::from(["Payments".to_string()]);
+        let model = ResolvedModel::new(&project, "shop").with_affected(Some(&affected));
+        let mut sink = MemorySink::default();
+        for generator in select(&formats(&["json", "md", "dot"])).unwrap() {
+            generator.generate(&model, &mut sink).unwrap();
+        }
+        assert_eq!(
+            sink.files.keys().cloned().collect::<Vec<_>>(),
+            vec!["Payments.dot", "Payments.md", "index.md", "shop.json"]
+        );
+    }
+}
@@ -0,0 +1,308 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::{
+    config::config::{Asset, Config, TrustBoundary},
+    input::{
+        input_diagram::{InputDiagram, Node, TypeNode},
+        threat::{ReferenceKind, Threat},
+    },
+};
+
+/// Content of a report section, shared by the report formats. `links` holds
+/// the hyperlink of a cell, indexed by row and column of `rows`.
+#[derive(Debug, Clone, Default)]
+pub struct Table {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    pub links: HashMap<(usize, usize), String>,
+}
+
+impl Table {
+    pub fn new(headers: Vec<&str>) -> Self {
+        Self {
+            headers: headers.iter().map(|header| header.to_string()).collect(),
+            rows: Vec::new(),
+            links: HashMap::new(),
+        }
+    }
+}
+
+/// Threats of the diagram with the node they are attached to, in the order of
+/// the diagram nodes. Unknown threat titles are skipped.
+pub fn node_threats<'a>(
+    input_diagram: &'a InputDiagram,
+    threats: &'a [Threat],
+) -> Vec<(&'a Node, &'a Threat)> {
+    input_diagram
+        .nodes
+        .iter()
+        .flat_map(|node| {
+            node.threat_titles().filter_map(move |threat_str| {
+                threats
+                    .iter()
+                    .rfind(|threat| threat.title == *threat_str)
+                    .map(|threat| (node, threat))
+            })
+        })
+        .collect()
+}
+
+/// Elements of the diagram (flows excluded) grouped by trust boundary, `None`
+/// holding the elements outside of any boundary.
+pub fn nodes_by_trust_boundary(
+    input_diagram: &InputDiagram,
+) -> BTreeMap<Option<&String>, Vec<&Node>> {
+    let mut boundaries: BTreeMap<Option<&String>, Vec<&Node>> = BTreeMap::new();
+    input_diagram
+        .nodes
+        .iter()
+        .filter(|node| node.type_node != TypeNode::Flow)
+        .for_each(|node| {
+            boundaries
+                .entry(node.trust_boundary.as_ref())
+                .or_default()
+                .push(node)
+        });
+    boundaries
+}
+
+pub fn software_components(input_diagram: &InputDiagram) -> Table {
+    let mut table = Table::new(vec![
+        "Name",
+        "Description",
+        "Trust Level",
+        "Out of scope",
+        "Type",
+    ]);
+    input_diagram
+        .nodes
+        .iter()
+        .filter(|node| node.type_node != TypeNode::Flow)
+        .for_each(|node_process| {
+            let out_of_scope = match node_process.out_of_scope {
+                Some(true) => "Yes".to_string(),
+                Some(false) => "No".to_string(),
+                None => "".to_string(),
+            };
+            table.rows.push(vec![
+                node_process.name.clone(),
+                node_process.description.clone(),
+                node_process.trust_level.clone().unwrap_or_default(),
+                out_of_scope,
+                node_process.type_node.to_string(),
+            ]);
+        });
+    table
+}
+
+pub fn entry_points(input_diagram: &InputDiagram) -> Table {
+    let mut table = Table::new(vec![
+        "ID",
+        "Direction",
+        "Description",
+        "Asset Name",
+        "Trust Level",
+        "Microservice",
+    ]);
+    let in_scope = |name: &Option<String>| {
+        input_diagram
+            .nodes
+            .iter()
+            .rfind(|node| Some(&node.name) == name.as_ref())
+            .filter(|node| !node.out_of_scope.unwrap_or(false))
+    };
+    input_diagram
+        .nodes
+        .iter()
+        .filter(|node| node.type_node == TypeNode::Flow)
+        .for_each(|node_flow| {
+            let (microservice, direction) = if let Some(node_source) = in_scope(&node_flow.source) {
+                (node_source.name.clone(), "Exit".to_string())
+            } else if let Some(node_dest) = in_scope(&node_flow.destination) {
+                (node_dest.name.clone(), "Entry".to_string())
+            } else {
+                ("Unknown".to_string(), "".to_string())
+            };
+            table.rows.push(vec![
+                node_flow.name.clone(),
+                direction,
+                node_flow.description.clone(),
+                node_flow.asset.clone().unwrap_or_default(),
+                node_flow
+                    .trust_level
+                    .clone()
+                    .unwrap_or("Unknown".to_string()),
+                microservice,
+            ]);
+        });
+    table
+}
+
+pub fn trust_boundaries(input_diagram: &InputDiagram, config: &Config) -> Table {
+    let mut table = Table::new(vec![
+        "ID",
+        "Description",
+        "Limit of Access",
+        "Level of Authorization",
+    ]);
+    let mut trust_boundaries_map: BTreeMap<String, TrustBoundary> = BTreeMap::new();
+    input_diagram
+        .nodes
+        .iter()
+        .filter_map(|node| node.trust_boundary.clone())
+        .for_each(|node_trust_boundary| {
+            if let Some(tb) = config
+                .trust_boundaries
+                .iter()
+                .rfind(|tb| tb.name == node_trust_boundary)
+            {
+                trust_boundaries_map.insert(node_trust_boundary, tb.clone());
+            }
+        });
+    trust_boundaries_map.iter().for_each(|(tb_name, tb)| {
+        table.rows.push(vec![
+            tb_name.clone(),
+            tb.description.clone(),
+            tb.limit_of_access.clone(),
+            tb.level_of_authorization.clone(),
+        ]);
+    });
+    table
+}
+
+pub fn assets(input_diagram: &InputDiagram, config: &Config) -> Table {
+    let mut table = Table::new(vec!["Name", "Description"]);
+    let mut asset_map: BTreeMap<String, Asset> = BTreeMap::new();
+    input_diagram
+        .nodes
+        .iter()
+        .filter(|node| node.type_node == TypeNode::Flow)
+        .filter_map(|node_flow| node_flow.asset.clone())
+        .for_each(|node_flow_asset| {
+            if let Some(asset) = config
+                .assets
+                .iter()
+                .rfind(|asset_config| asset_config.name == node_flow_asset)
+            {
+                asset_map.insert(node_flow_asset, asset.clone());
+            }
+        });
+    asset_map.values().for_each(|asset| {
+        table
+            .rows
+            .push(vec![asset.name.clone(), asset.description.clone()]);
+    });
+    table
+}
+
+/// Reference kinds displayed as columns of the threats table.
+const REFERENCE_COLUMNS: [ReferenceKind; 4] = [
+    ReferenceKind::Cwe,
+    ReferenceKind::Capec,
+    ReferenceKind::Asvs,
+    ReferenceKind::Url,
+];
+
+pub fn threats(input_diagram: &InputDiagram, threats: &[Threat]) -> Table {
+    threats_of(input_diagram, &node_threats(input_diagram, threats))
+}
+
+/// Threats table for a selection of the diagram threats.
+pub fn threats_of(input_diagram: &InputDiagram, node_threats: &[(&Node, &Threat)]) -> Table {
+    let methodology = input_diagram.methodology().to_string();
+    let mut table = Table::new(vec![
+        "ID",
+        "Type",
+        &methodology,
+        "Description",
+        "Vector",
+        "Status",
+        "Severity",
+        "Mitigations",
+        "CWE",
+        "CAPEC",
+        "ASVS",
+        "References",
+    ]);
+    let first_reference_column = table.headers.len() - REFERENCE_COLUMNS.len();
+    node_threats.iter().for_each(|(node, threat)| {
+        let mut row = vec![
+            node.name.clone(),
+            node.type_node.to_string(),
+            threat.type_field.to_string(),
+            threat.description.clone(),
+            threat.vector.clone(),
+            threat.status.to_string(),
+            threat.severity.to_string(),
+            threat.mitigation.clone(),
+        ];
+        let references = threat.references.clone().unwrap_or_default();
+        REFERENCE_COLUMNS
+            .iter()
+            .enumerate()
+            .for_each(|(index, kind)| {
+                let entries = references.entries_of_kind(kind.clone());
+                // A cell holds a single hyperlink, it points to the first reference.
+                if let Some(url) = entries.iter().find_map(|reference| reference.url.clone()) {
+                    table
+                        .links
+                        .insert((table.rows.len(), first_reference_column + index), url);
+                }
+                row.push(
+                    entries
+                        .iter()
+                        .map(|reference| reference.label.clone())
+                        .collect::<Vec<String>>()
+                        .join(", "),
+                );
+            });
+        table.rows.push(row);
+    });
+    table
+}
+
+pub fn vectors(input_diagram: &InputDiagram, threats: &[Threat]) -> Table {
+    let mut table = Table::new(vec!["Name"]);
+    let vectors: BTreeSet<String> = node_threats(input_diagram, threats)
+        .iter()
+        .map(|(_, threat)| threat.vector.clone())
+        .collect();
+    vectors
+        .into_iter()
+        .for_each(|vector| table.rows.push(vec![vector]));
+    table
+}
+
+/// One row per threat and reference, so the table can be filtered and grouped
+/// on the reference columns.
+pub fn references(input_diagram: &InputDiagram, threats: &[Threat]) -> Table {
+    let mut table = Table::new(vec!["Threat", "Kind", "Reference", "Nodes"]);
+    let mut nodes_by_threat: BTreeMap<String, (&Threat, Vec<String>)> = BTreeMap::new();
+    node_threats(input_diagram, threats)
+        .into_iter()
+        .for_each(|(node, threat)| {
+            nodes_by_threat
+                .entry(threat.title.clone())
+                .or_insert((threat, Vec::new()))
+                .1
+                .push(node.name.clone());
+        });
+    nodes_by_threat
+        .iter()
+        .for_each(|(threat_str, (threat, nodes))| {
+            if let Some(references) = &threat.references {
+                references.entries().into_iter().for_each(|reference| {
+                    if let Some(url) = reference.url.clone() {
+                        table.links.insert((table.rows.len(), 2), url);
+                    }
+                    table.rows.push(vec![
+                        threat_str.clone(),
+                        reference.kind.to_string(),
+                        reference.label,
+                        nodes.join(", "),
+                    ]);
+                });
+            }
+        });
+    table
+}
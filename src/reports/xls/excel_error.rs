@@ -1,4 +1,7 @@
+use std::fmt::Display;
+
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 pub enum ExcelError {
     SetColumnWidth(String),
     SetName(String),
@@ -8,5 +11,23 @@ pub enum ExcelError {
     MergeRange(String),
     NewImage(String),
     InsertImage(String),
+    AddTable(String),
     Save(String),
 }
+
+impl Display for ExcelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExcelError::SetColumnWidth(e) => write!(f, "cannot set column width: {}", e),
+            ExcelError::SetName(e) => write!(f, "cannot set worksheet name: {}", e),
+            ExcelError::Write(e) => write!(f, "cannot write cell: {}", e),
+            ExcelError::WriteWithFormat(e) => write!(f, "cannot write formatted cell: {}", e),
+            ExcelError::ExcelDateTime(e) => write!(f, "invalid date time: {}", e),
+            ExcelError::MergeRange(e) => write!(f, "cannot merge range: {}", e),
+            ExcelError::NewImage(e) => write!(f, "cannot load image: {}", e),
+            ExcelError::InsertImage(e) => write!(f, "cannot insert image: {}", e),
+            ExcelError::AddTable(e) => write!(f, "cannot add table: {}", e),
+            ExcelError::Save(e) => write!(f, "cannot save workbook: {}", e),
+        }
+    }
+}
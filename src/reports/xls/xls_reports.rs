@@ -1,49 +1,106 @@
-use std::{collections::HashMap, path::Path};
+use std::{io::Write, path::Path};
 
-use rust_xlsxwriter::{Format, Table, TableColumn, TableStyle, Workbook, Worksheet};
+use rust_xlsxwriter::{
+    Color, Format, FormatBorder, Image, Table, TableColumn, TableStyle, Url, Workbook, Worksheet,
+};
 
 use crate::{
-    config::config::{Asset, Config, TrustBoundary},
-    input::{
-        input_diagram::{InputDiagram, TypeNode},
-        threat::Threat,
-    },
+    config::config::Config,
+    history::history::{self, History},
+    input::{input_diagram::InputDiagram, threat::Threat},
+    reports::sections::{self, Table as SectionTable},
 };
 
 use super::excel_error::ExcelError;
 
-pub fn create_reports(
-    output_folder: &Path,
+/// Write the workbook of the diagram to `writer`, such as a `Vec<u8>` to
+/// keep it in memory.
+pub fn write_report<W: Write>(
+    mut writer: W,
     input_diagram: &InputDiagram,
     config: &Config,
-    threats: &Vec<Threat>,
+    threats: &[Threat],
+    diagram_image: Option<&[u8]>,
 ) -> Result<(), ExcelError> {
+    let mut workbook = diagram_workbook(input_diagram, config, threats, diagram_image)?;
+    let buffer = workbook
+        .save_to_buffer()
+        .map_err(|e| ExcelError::Save(format!("{}", e)))?;
+    writer
+        .write_all(&buffer)
+        .map_err(|e| ExcelError::Save(format!("{}", e)))
+}
+
+fn diagram_workbook(
+    input_diagram: &InputDiagram,
+    config: &Config,
+    threats: &[Threat],
+    diagram_image: Option<&[u8]>,
+) -> Result<Workbook, ExcelError> {
     let mut workbook = Workbook::new();
 
-    create_software_worksheet(&input_diagram, &mut workbook)?;
-    create_entry_points_worksheet(&input_diagram, &mut workbook)?;
-    create_trust_boundary_worksheet(&input_diagram, &config, &mut workbook)?;
-    create_assets_worksheet(&input_diagram, &config, &mut workbook)?;
-    create_threats_worksheet(&input_diagram, &threats, &mut workbook)?;
-    create_vectors_worksheet(&input_diagram, &threats, &mut workbook)?;
-    let mut workbook_save_path = output_folder.join(&input_diagram.title);
-    workbook_save_path.set_extension("xlsx");
-    // Save the file to disk.
-    workbook
-        .save(workbook_save_path)
-        .map_err(|e| ExcelError::Save(format!("{}", e)))?;
+    if let Some(diagram_image) = diagram_image {
+        create_diagram_worksheet(input_diagram, diagram_image, &mut workbook)?;
+    }
+    create_software_worksheet(input_diagram, &mut workbook)?;
+    create_entry_points_worksheet(input_diagram, &mut workbook)?;
+    create_trust_boundary_worksheet(input_diagram, config, &mut workbook)?;
+    create_assets_worksheet(input_diagram, config, &mut workbook)?;
+    create_threats_worksheet(input_diagram, threats, &mut workbook)?;
+    create_vectors_worksheet(input_diagram, threats, &mut workbook)?;
+    create_references_worksheet(input_diagram, threats, &mut workbook)?;
+    Ok(workbook)
+}
 
-    Ok(())
+pub const HISTORY_FILE_NAME: &str = "history.xlsx";
+
+/// Workbook of the history: a "History" worksheet with every change, a "Time
+/// to mitigate" worksheet with the statistics of each severity and the dates
+/// of each mitigated threat.
+pub fn create_history_report(output_folder: &Path, history: &History) -> Result<(), ExcelError> {
+    let mut workbook = Workbook::new();
+
+    let history_worksheet = workbook.add_worksheet();
+    history_worksheet
+        .set_name("History")
+        .map_err(|e| ExcelError::SetName(format!("{}", e)))?;
+    create_table(&history::history_table(history), history_worksheet)?;
+    history_worksheet.autofit();
+    wrap_column(history_worksheet, 6)?;
+
+    let statistics_worksheet = workbook.add_worksheet();
+    statistics_worksheet
+        .set_name("Time to mitigate")
+        .map_err(|e| ExcelError::SetName(format!("{}", e)))?;
+    create_table(
+        &history::time_to_mitigate_table(history),
+        statistics_worksheet,
+    )?;
+    statistics_worksheet.autofit();
+
+    let mitigations_worksheet = workbook.add_worksheet();
+    mitigations_worksheet
+        .set_name("Mitigated threats")
+        .map_err(|e| ExcelError::SetName(format!("{}", e)))?;
+    create_table(&history::mitigations_table(history), mitigations_worksheet)?;
+    mitigations_worksheet.autofit();
+
+    workbook
+        .save(output_folder.join(HISTORY_FILE_NAME))
+        .map_err(|e| ExcelError::Save(format!("{}", e)))
 }
 
-fn create_table(headers: &Vec<String>, data: &Vec<Vec<String>>, worksheet: &mut Worksheet) {
+fn create_table(section: &SectionTable, worksheet: &mut Worksheet) -> Result<(), ExcelError> {
     // Create a new table and configure it.
-    let data_size = data.len();
-    let titles: Vec<TableColumn> = headers
+    let data_size = section.rows.len();
+    let titles: Vec<TableColumn> = section
+        .headers
         .iter()
         .map(|header| TableColumn::new().set_header(header))
         .collect();
-    worksheet.write_row_matrix(1, 0, data).unwrap();
+    worksheet
+        .write_row_matrix(1, 0, &section.rows)
+        .map_err(|e| ExcelError::Write(format!("{}", e)))?;
     let table = Table::new()
         .set_banded_rows(true)
         .set_style(TableStyle::Medium23)
@@ -52,7 +109,93 @@ fn create_table(headers: &Vec<String>, data: &Vec<Vec<String>>, worksheet: &mut
 
     worksheet
         .add_table(0, 0, (data_size) as u32, (titles.len() - 1) as u16, &table)
-        .unwrap();
+        .map_err(|e| ExcelError::AddTable(format!("{}", e)))?;
+
+    for ((row, col), url) in section.links.iter() {
+        worksheet
+            .write_url_with_text(
+                (row + 1) as u32,
+                *col as u16,
+                Url::new(url),
+                &section.rows[*row][*col],
+            )
+            .map_err(|e| ExcelError::Write(format!("{}", e)))?;
+    }
+    Ok(())
+}
+
+fn wrap_column(worksheet: &mut Worksheet, column: u16) -> Result<(), ExcelError> {
+    let format_text_wrap = Format::new().set_text_wrap();
+    worksheet
+        .set_column_width(column, 40)
+        .map_err(|e| ExcelError::SetColumnWidth(format!("{}", e)))?;
+    worksheet
+        .set_column_format(column, &format_text_wrap)
+        .map_err(|e| ExcelError::WriteWithFormat(format!("{}", e)))?;
+    Ok(())
+}
+
+fn create_diagram_worksheet(
+    input_diagram: &InputDiagram,
+    diagram_image: &[u8],
+    workbook: &mut Workbook,
+) -> Result<(), ExcelError> {
+    // Add a worksheet to the workbook.
+    let diagram_worksheet = workbook.add_worksheet();
+
+    diagram_worksheet
+        .set_name("Diagram")
+        .map_err(|e| ExcelError::SetName(format!("{}", e)))?;
+
+    let title_format = Format::new().set_bold().set_font_size(14);
+    diagram_worksheet
+        .merge_range(0, 0, 0, 5, &input_diagram.title, &title_format)
+        .map_err(|e| ExcelError::MergeRange(format!("{}", e)))?;
+
+    // Legend of the rendering, one sample cell per kind of element.
+    let legend = [
+        (
+            Format::new()
+                .set_border(FormatBorder::Thin)
+                .set_border_color(Color::Red),
+            "Element with threats",
+        ),
+        (
+            Format::new().set_border(FormatBorder::Dotted),
+            "Out of scope element",
+        ),
+        (
+            Format::new()
+                .set_border(FormatBorder::Dashed)
+                .set_border_color(Color::Gray),
+            "Trust boundary",
+        ),
+        (
+            Format::new().set_border_bottom(FormatBorder::Medium),
+            "Data flow",
+        ),
+    ];
+    for (index, (format, label)) in legend.iter().enumerate() {
+        let row = (index + 2) as u32;
+        diagram_worksheet
+            .write_blank(row, 0, format)
+            .map_err(|e| ExcelError::WriteWithFormat(format!("{}", e)))?;
+        diagram_worksheet
+            .write_string(row, 1, *label)
+            .map_err(|e| ExcelError::Write(format!("{}", e)))?;
+    }
+    diagram_worksheet
+        .set_column_width(1, 24)
+        .map_err(|e| ExcelError::SetColumnWidth(format!("{}", e)))?;
+
+    let mut image = Image::new_from_buffer(diagram_image)
+        .map_err(|e| ExcelError::NewImage(format!("{}", e)))?;
+    image.set_alt_text(format!("Data flow diagram {}", input_diagram.title));
+    diagram_worksheet
+        .insert_image((legend.len() + 3) as u32, 0, &image)
+        .map_err(|e| ExcelError::InsertImage(format!("{}", e)))?;
+
+    Ok(())
 }
 
 fn create_entry_points_worksheet(
@@ -66,61 +209,12 @@ fn create_entry_points_worksheet(
         .set_name("EntryPoint")
         .map_err(|e| ExcelError::SetName(format!("{}", e)))?;
 
-    let column_titles = vec![
-        "ID".to_string(),
-        "Direction".to_string(),
-        "Description".to_string(),
-        "Asset Name".to_string(),
-        "Trust Level".to_string(),
-        "Microservice".to_string(),
-    ];
-
-    let mut data: Vec<Vec<String>> = Vec::new();
-
-    input_diagram
-        .nodes
-        .iter()
-        .filter(|node| node.type_node == TypeNode::Flow)
-        .for_each(|node_flow| {
-            let node_source = input_diagram
-                .nodes
-                .iter()
-                .filter(|node| node.name == node_flow.source.clone().unwrap())
-                .last();
-            let node_dest = input_diagram
-                .nodes
-                .iter()
-                .filter(|node| node.name == node_flow.destination.clone().unwrap())
-                .last();
-            let (microservice, direction) = if !node_source.unwrap().out_of_scope.unwrap() {
-                (node_source.unwrap().name.clone(), "Exit".to_string())
-            } else if !node_dest.unwrap().out_of_scope.unwrap() {
-                (node_dest.unwrap().name.clone(), "Entry".to_string())
-            } else {
-                ("Unknown".to_string(), "".to_string())
-            };
-            let trust_level = match &node_flow.trust_level {
-                Some(trust_level) => trust_level.clone(),
-                None => "Unknown".to_string(),
-            };
-            data.push(vec![
-                node_flow.name.clone(),
-                direction,
-                node_flow.description.clone(),
-                node_flow.asset.clone().unwrap_or("".to_string()),
-                trust_level,
-                microservice,
-            ]);
-        });
-    create_table(&column_titles, &data, entry_point_worksheet);
+    create_table(
+        &sections::entry_points(input_diagram),
+        entry_point_worksheet,
+    )?;
     entry_point_worksheet.autofit();
-    let format_text_wrap = Format::new().set_text_wrap();
-    entry_point_worksheet
-        .set_column_width(2, 40)
-        .map_err(|e| ExcelError::SetColumnWidth(format!("{}", e)))?;
-    entry_point_worksheet
-        .set_column_format(2, &format_text_wrap)
-        .map_err(|e| ExcelError::WriteWithFormat(format!("{}", e)))?;
+    wrap_column(entry_point_worksheet, 2)?;
 
     Ok(())
 }
@@ -131,67 +225,25 @@ fn create_trust_boundary_worksheet(
     workbook: &mut Workbook,
 ) -> Result<(), ExcelError> {
     // Add a worksheet to the workbook.
-    let entry_point_worksheet = workbook.add_worksheet();
+    let trust_boundary_worksheet = workbook.add_worksheet();
 
-    entry_point_worksheet
+    trust_boundary_worksheet
         .set_name("Trust Boundaries")
         .map_err(|e| ExcelError::SetName(format!("{}", e)))?;
 
-    let mut trust_boundaries_map: HashMap<String, TrustBoundary> = HashMap::new();
-
-    input_diagram
-        .nodes
-        .iter()
-        .filter(|node| node.trust_boundary.is_some())
-        .for_each(|node_with_trust_boundary| {
-            config
-                .trust_boundaries
-                .iter()
-                .filter(|tb| tb.name == node_with_trust_boundary.trust_boundary.clone().unwrap())
-                .for_each(|tb| {
-                    trust_boundaries_map.insert(
-                        node_with_trust_boundary.trust_boundary.clone().unwrap(),
-                        tb.clone(),
-                    );
-                });
-        });
-
-    let mut data: Vec<Vec<String>> = Vec::new();
-    let mut data_size = 0;
-    let headers = vec![
-        "ID".to_string(),
-        "Description".to_string(),
-        "Limit of Access".to_string(),
-        "Level of Authorization".to_string(),
-    ];
-    trust_boundaries_map
-        .iter()
-        .enumerate()
-        .for_each(|(index, (tb_name, tb))| {
-            data.push(vec![
-                tb_name.clone().to_string(),
-                tb.description.clone().to_string(),
-                tb.limit_of_access.clone().to_string(),
-                tb.level_of_authorization.clone().to_string(),
-            ]);
-            data_size = index + 1;
-        });
-    create_table(&headers, &data, entry_point_worksheet);
-    entry_point_worksheet.autofit();
-    let format_text_wrap = Format::new().set_text_wrap();
-    entry_point_worksheet
-        .set_column_width(1, 40)
-        .map_err(|e| ExcelError::SetColumnWidth(format!("{}", e)))?;
-    entry_point_worksheet
-        .set_column_format(1, &format_text_wrap)
-        .map_err(|e| ExcelError::WriteWithFormat(format!("{}", e)))?;
+    create_table(
+        &sections::trust_boundaries(input_diagram, config),
+        trust_boundary_worksheet,
+    )?;
+    trust_boundary_worksheet.autofit();
+    wrap_column(trust_boundary_worksheet, 1)?;
 
     Ok(())
 }
 
 fn create_threats_worksheet(
     input_diagram: &InputDiagram,
-    threats: &Vec<Threat>,
+    threats: &[Threat],
     workbook: &mut Workbook,
 ) -> Result<(), ExcelError> {
     // Add a worksheet to the workbook.
@@ -201,53 +253,33 @@ fn create_threats_worksheet(
         .set_name("Threats")
         .map_err(|e| ExcelError::SetName(format!("{}", e)))?;
 
-    let column_titles = vec![
-        "ID".to_string(),
-        "Type".to_string(),
-        "STRIDE".to_string(),
-        "Description".to_string(),
-        "Vector".to_string(),
-        "Status".to_string(),
-        "Mitigations".to_string(),
-    ];
+    create_table(
+        &sections::threats(input_diagram, threats),
+        threats_worksheet,
+    )?;
+    threats_worksheet.autofit();
+    wrap_column(threats_worksheet, 3)?;
+    wrap_column(threats_worksheet, 7)?;
+    Ok(())
+}
+
+fn create_references_worksheet(
+    input_diagram: &InputDiagram,
+    threats: &[Threat],
+    workbook: &mut Workbook,
+) -> Result<(), ExcelError> {
+    let references_worksheet = workbook.add_worksheet();
 
-    let mut data: Vec<Vec<String>> = Vec::new();
-
-    input_diagram.nodes.iter().for_each(|node| {
-        node.threats.iter().for_each(|threat_str| {
-            let threat = threats
-                .iter()
-                .filter(|threat| threat.title == *threat_str)
-                .last();
-            if let Some(threat) = threat {
-                data.push(vec![
-                    node.name.clone().to_string(),
-                    node.type_node.clone().to_string(),
-                    threat.type_field.clone().to_string(),
-                    threat.description.clone().to_string(),
-                    threat.vector.clone().to_string(),
-                    threat.status.clone().to_string(),
-                    threat.mitigation.clone().to_string(),
-                ]);
-            }
-        });
-    });
+    references_worksheet
+        .set_name("References")
+        .map_err(|e| ExcelError::SetName(format!("{}", e)))?;
+
+    create_table(
+        &sections::references(input_diagram, threats),
+        references_worksheet,
+    )?;
+    references_worksheet.autofit();
 
-    let format_text_wrap = Format::new().set_text_wrap();
-    create_table(&column_titles, &data, threats_worksheet);
-    threats_worksheet.autofit();
-    threats_worksheet
-        .set_column_width(3, 40)
-        .map_err(|e| ExcelError::SetColumnWidth(format!("{}", e)))?;
-    threats_worksheet
-        .set_column_format(3, &format_text_wrap)
-        .map_err(|e| ExcelError::WriteWithFormat(format!("{}", e)))?;
-    threats_worksheet
-        .set_column_width(6, 40)
-        .map_err(|e| ExcelError::SetColumnWidth(format!("{}", e)))?;
-    threats_worksheet
-        .set_column_format(6, &format_text_wrap)
-        .map_err(|e| ExcelError::WriteWithFormat(format!("{}", e)))?;
     Ok(())
 }
 
@@ -262,45 +294,12 @@ fn create_software_worksheet(
         .set_name("Software Component")
         .map_err(|e| ExcelError::SetName(format!("{}", e)))?;
 
-    let column_titles = vec![
-        "Name".to_string(),
-        "Description".to_string(),
-        "Trust Level".to_string(),
-        "Out of scope".to_string(),
-    ];
-
-    let mut data: Vec<Vec<String>> = Vec::new();
-
-    input_diagram
-        .nodes
-        .iter()
-        .filter(|node| node.type_node == TypeNode::Process)
-        .for_each(|node_process| {
-            let mut row: Vec<String> = Vec::new();
-            let out_of_scope = if let Some(out_of_scope) = node_process.out_of_scope.clone() {
-                if out_of_scope {
-                    "Yes".to_string()
-                } else {
-                    "No".to_string()
-                }
-            } else {
-                "".to_string()
-            };
-            row.push(node_process.name.clone());
-            row.push(node_process.description.clone());
-            row.push(node_process.trust_level.clone().unwrap_or("".to_string()));
-            row.push(out_of_scope);
-            data.push(row);
-        });
-    create_table(&column_titles, &data, software_worksheet);
+    create_table(
+        &sections::software_components(input_diagram),
+        software_worksheet,
+    )?;
     software_worksheet.autofit();
-    let format_text_wrap = Format::new().set_text_wrap();
-    software_worksheet
-        .set_column_width(1, 40)
-        .map_err(|e| ExcelError::SetColumnWidth(format!("{}", e)))?;
-    software_worksheet
-        .set_column_format(1, &format_text_wrap)
-        .map_err(|e| ExcelError::WriteWithFormat(format!("{}", e)))?;
+    wrap_column(software_worksheet, 1)?;
     Ok(())
 }
 
@@ -316,51 +315,15 @@ fn create_assets_worksheet(
         .set_name("Asset worksheet")
         .map_err(|e| ExcelError::SetName(format!("{}", e)))?;
 
-    let column_titles = vec!["Name".to_string(), "Description".to_string()];
-
-    let mut asset_map: HashMap<String, Asset> = HashMap::new();
-
-    input_diagram
-        .nodes
-        .iter()
-        .filter(|node| node.type_node == TypeNode::Flow)
-        .for_each(|node_flow| {
-            if let Some(node_flow_asset) = node_flow.asset.clone() {
-                let asset = config
-                    .assets
-                    .iter()
-                    .filter(|asset_config| asset_config.name == node_flow_asset)
-                    .last();
-
-                if let Some(asset) = asset {
-                    asset_map.insert(node_flow_asset, asset.clone());
-                }
-            }
-        });
-    let mut data: Vec<Vec<String>> = Vec::new();
-
-    asset_map.iter().for_each(|(_key, value)| {
-        let mut row: Vec<String> = Vec::new();
-        row.push(value.name.clone());
-        row.push(value.description.clone());
-        data.push(row);
-    });
-
-    create_table(&column_titles, &data, asset_worksheet);
+    create_table(&sections::assets(input_diagram, config), asset_worksheet)?;
     asset_worksheet.autofit();
-    let format_text_wrap = Format::new().set_text_wrap();
-    asset_worksheet
-        .set_column_width(1, 40)
-        .map_err(|e| ExcelError::SetColumnWidth(format!("{}", e)))?;
-    asset_worksheet
-        .set_column_format(1, &format_text_wrap)
-        .map_err(|e| ExcelError::WriteWithFormat(format!("{}", e)))?;
+    wrap_column(asset_worksheet, 1)?;
     Ok(())
 }
 
 fn create_vectors_worksheet(
     input_diagram: &InputDiagram,
-    threats: &Vec<Threat>,
+    threats: &[Threat],
     workbook: &mut Workbook,
 ) -> Result<(), ExcelError> {
     let vectors_worksheet = workbook.add_worksheet();
@@ -369,29 +332,130 @@ fn create_vectors_worksheet(
         .set_name("Vectors")
         .map_err(|e| ExcelError::SetName(format!("{}", e)))?;
 
-    let column_titles = vec!["Name".to_string()];
-
-    let mut vector_map: HashMap<String, String> = HashMap::new();
-
-    input_diagram.nodes.iter().for_each(|node| {
-        node.threats.iter().for_each(|threat_str| {
-            let threat = threats
-                .iter()
-                .filter(|threat| threat.title == *threat_str)
-                .last();
-            if let Some(threat) = threat {
-                vector_map.insert(threat.vector.clone(), threat.vector.clone());
-            }
-        });
-    });
-
-    let vectorl_list: Vec<Vec<String>> = vector_map
-        .iter()
-        .map(|(index, _value)| vec![index.clone()])
-        .collect();
-
-    create_table(&column_titles, &vectorl_list, vectors_worksheet);
+    create_table(
+        &sections::vectors(input_diagram, threats),
+        vectors_worksheet,
+    )?;
     vectors_worksheet.autofit();
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Cursor, Read},
+        path::{Path, PathBuf},
+    };
+
+    use zip::ZipArchive;
+
+    use super::*;
+    use crate::{
+        project::project::Project,
+        render::{png::render_png, svg::render_svg},
+    };
+
+    const CONFIG: &str = r#"threatDragonVersion: "2.2.0"
+title: "Shop"
+owner: "Team"
+description: ""
+trustBoundaries: []
+assets: []
+diagrams: []
+"#;
+
+    const DIAGRAM: &str = r#"title: "Orders"
+description: ""
+nodes:
+  - name: User
+    type: actor
+    description: ""
+    threats: []
+  - name: Web
+    type: process
+    description: ""
+    threats: []
+  - name: Order
+    type: flow
+    description: ""
+    source: User
+    destination: Web
+    threats: []
+"#;
+
+    /// Files of the workbook written with or without the diagram image.
+    fn workbook(with_image: bool) -> ZipArchive<Cursor<Vec<u8>>> {
+        let project = Project::parse(
+            (Path::new("config.yaml"), CONFIG),
+            (Path::new("threats.yaml"), "[]\n"),
+            &[(PathBuf::from("orders.yaml"), DIAGRAM.to_string())],
+            None,
+        )
+        .unwrap();
+        let png = render_png(&render_svg(&project.threat_modeling().detail.diagrams[0])).unwrap();
+        let mut xlsx = Vec::new();
+        write_report(
+            &mut xlsx,
+            &project.diagrams[0],
+            &project.config,
+            &project.threats,
+            with_image.then_some(png.as_slice()),
+        )
+        .unwrap();
+        ZipArchive::new(Cursor::new(xlsx)).unwrap()
+    }
+
+    fn file(archive: &mut ZipArchive<Cursor<Vec<u8>>>, name: &str) -> String {
+        let mut content = String::new();
+        archive
+            .by_name(name)
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        content
+    }
+
+    fn sheet_names(archive: &mut ZipArchive<Cursor<Vec<u8>>>) -> Vec<String> {
+        let workbook = file(archive, "xl/workbook.xml");
+        let document = roxmltree::Document::parse(&workbook).unwrap();
+        document
+            .descendants()
+            .filter(|node| node.tag_name().name() == "sheet")
+            .filter_map(|node| node.attribute("name"))
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn diagram_sheet_comes_first_with_its_legend() {
+        let mut archive = workbook(true);
+        let names = sheet_names(&mut archive);
+        assert_eq!(names[0], "Diagram");
+        assert!(names.contains(&"Threats".to_string()));
+
+        let strings = file(&mut archive, "xl/sharedStrings.xml");
+        for label in [
+            "Orders",
+            "Element with threats",
+            "Out of scope element",
+            "Trust boundary",
+            "Data flow",
+        ] {
+            assert!(strings.contains(&format!(">{}<", label)), "{}", label);
+        }
+        let sheet = file(&mut archive, "xl/worksheets/sheet1.xml");
+        assert!(sheet.contains("<mergeCell ref=\"A1:F1\"/>"));
+
+        assert!(archive.by_name("xl/media/image1.png").is_ok());
+        let drawing = file(&mut archive, "xl/drawings/drawing1.xml");
+        assert!(drawing.contains("descr=\"Data flow diagram Orders\""));
+    }
+
+    #[test]
+    fn no_diagram_sheet_without_image() {
+        let mut archive = workbook(false);
+        assert!(!sheet_names(&mut archive).contains(&"Diagram".to_string()));
+        assert!(archive.by_name("xl/media/image1.png").is_err());
+    }
+}
@@ -0,0 +1,2 @@
+pub mod scaffold;
+pub mod scaffold_error;
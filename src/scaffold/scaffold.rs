@@ -0,0 +1,274 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{
+    config::config::Config,
+    input::{
+        input_diagram::{InputDiagram, Node, TypeNode},
+        threat::Threat,
+    },
+    project::project::{Project, CONFIG_FILE_NAME, DIAGRAMS_DIRECTORY_NAME, THREATS_FILE_NAME},
+    schema::schema::{
+        self, CONFIG_SCHEMA_FILE_NAME, DIAGRAM_SCHEMA_FILE_NAME, THREATS_SCHEMA_FILE_NAME,
+    },
+};
+
+use super::scaffold_error::ScaffoldError;
+
+const BUILTIN_PROJECTS: &str = include_str!("projects.yaml");
+
+pub const SCHEMAS_DIRECTORY_NAME: &str = "schemas";
+pub const OUTPUT_DIRECTORY_NAME: &str = "output";
+
+const GITIGNORE: &str = "# Generated by threat-dragon-generator\n/output/\n";
+
+pub const GITHUB_WORKFLOW_PATH: &str = ".github/workflows/threat-model.yml";
+const GITHUB_WORKFLOW: &str = r#"name: Threat model
+
+on:
+  pull_request:
+  push:
+    branches: [main]
+
+jobs:
+  threat-model:
+    runs-on: ubuntu-latest
+    permissions:
+      contents: read
+      security-events: write
+    steps:
+      - uses: actions/checkout@v4
+      - name: Generate the reports
+        run: >
+          docker run --rm -v "$PWD:/workdir" -w /workdir
+          threatdragongenerator/threat-dragon-generator:latest
+          generate --config config.yaml --threats threats.yaml --diagrams diagrams --output output
+      - uses: github/codeql-action/upload-sarif@v3
+        with:
+          sarif_file: output/output.sarif
+      - uses: actions/upload-artifact@v4
+        with:
+          name: threat-model
+          path: output
+      - name: Check the thresholds
+        run: >
+          docker run --rm -v "$PWD:/workdir" -w /workdir
+          threatdragongenerator/threat-dragon-generator:latest
+          gate --config config.yaml --threats threats.yaml --diagrams diagrams --junit output/threat-gates.xml
+"#;
+
+pub const GITLAB_CI_PATH: &str = ".gitlab-ci.yml";
+const GITLAB_CI: &str = r#"threat-model:
+  image: rust:latest
+  script:
+    - cargo install --git https://github.com/dedal-lab/threat-dragon-generator
+    - threat-dragon-generator generate --config config.yaml --threats threats.yaml --diagrams diagrams --output output
+    - threat-dragon-generator gate --config config.yaml --threats threats.yaml --diagrams diagrams --junit output/threat-gates.xml
+  artifacts:
+    when: always
+    paths:
+      - output
+    reports:
+      junit: output/threat-gates.xml
+"#;
+
+/// Starting point of a new project.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectTemplate {
+    pub id: String,
+    pub description: String,
+    pub config: Config,
+    pub threats: Vec<Threat>,
+    pub diagrams: Vec<InputDiagram>,
+}
+
+impl ProjectTemplate {
+    pub fn builtin() -> Vec<Self> {
+        serde_yaml::from_str(BUILTIN_PROJECTS).expect("built-in project templates are valid")
+    }
+
+    pub fn find(id: &str) -> Result<Self, ScaffoldError> {
+        let templates = Self::builtin();
+        let available: Vec<String> = templates
+            .iter()
+            .map(|template| format!("  {}: {}", template.id, template.description))
+            .collect();
+        templates
+            .into_iter()
+            .find(|template| template.id == id)
+            .ok_or_else(|| {
+                ScaffoldError::UnknownTemplate(format!(
+                    "{}, available templates:\n{}",
+                    id,
+                    available.join("\n")
+                ))
+            })
+    }
+
+    /// Project of the template, with the components added as processes of
+    /// the first diagram.
+    pub fn to_project(&self, title: &str, owner: &str, components: &[String]) -> Project {
+        let mut config = self.config.clone();
+        config.title = title.to_string();
+        config.owner = owner.to_string();
+        let mut diagrams = self.diagrams.clone();
+        if let Some(diagram) = diagrams.first_mut() {
+            diagram
+                .nodes
+                .extend(components.iter().map(|component| Node {
+                    name: component.clone(),
+                    type_node: TypeNode::Process,
+                    description: String::new(),
+                    out_of_scope: None,
+                    trust_boundary: None,
+                    trust_level: None,
+                    source: None,
+                    destination: None,
+                    asset: None,
+                    protocol: None,
+                    is_encrypted: None,
+                    is_public_network: None,
+                    threats: Vec::new(),
+                }));
+        }
+        Project {
+            config,
+            threats: self.threats.clone(),
+            diagrams,
+            diagram_paths: HashMap::new(),
+            node_paths: HashMap::new(),
+            component_overrides: HashMap::new(),
+            included_paths: Vec::new(),
+        }
+    }
+}
+
+/// Create the project files in `folder`: the YAML files with a schema
+/// modeline, the schemas, a `.gitignore`, the output folder and the `extra`
+/// files. Nothing is written when one of the files already exists.
+pub fn create_project(
+    folder: &Path,
+    project: &Project,
+    extra: Vec<(PathBuf, String)>,
+) -> Result<Vec<PathBuf>, ScaffoldError> {
+    let mut files: Vec<(PathBuf, String)> = project
+        .files(folder)
+        .map_err(|e| ScaffoldError::Save(format!("{}", e)))?
+        .into_iter()
+        .map(|(path, content)| {
+            let schema = if path == folder.join(CONFIG_FILE_NAME) {
+                format!("{}/{}", SCHEMAS_DIRECTORY_NAME, CONFIG_SCHEMA_FILE_NAME)
+            } else if path == folder.join(THREATS_FILE_NAME) {
+                format!("{}/{}", SCHEMAS_DIRECTORY_NAME, THREATS_SCHEMA_FILE_NAME)
+            } else {
+                format!("../{}/{}", SCHEMAS_DIRECTORY_NAME, DIAGRAM_SCHEMA_FILE_NAME)
+            };
+            let modeline = format!("# yaml-language-server: $schema={}\n", schema);
+            (path, modeline + &content)
+        })
+        .collect();
+    for (file_name, root_schema) in schema::schemas() {
+        let content = serde_json::to_string_pretty(&root_schema)
+            .map_err(|e| ScaffoldError::Save(format!("{}", e)))?;
+        files.push((
+            folder.join(SCHEMAS_DIRECTORY_NAME).join(file_name),
+            content + "\n",
+        ));
+    }
+    files.push((folder.join(".gitignore"), GITIGNORE.to_string()));
+    files.extend(extra);
+
+    if let Some((path, _)) = files.iter().find(|(path, _)| path.exists()) {
+        return Err(ScaffoldError::AlreadyExists(format!("{}", path.display())));
+    }
+    for folder in [
+        folder.join(DIAGRAMS_DIRECTORY_NAME),
+        folder.join(OUTPUT_DIRECTORY_NAME),
+    ] {
+        fs::create_dir_all(&folder)
+            .map_err(|e| ScaffoldError::Save(format!("{}: {}", folder.display(), e)))?;
+    }
+    for (path, content) in files.iter() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| ScaffoldError::Save(format!("{}: {}", parent.display(), e)))?;
+        }
+        fs::write(path, content)
+            .map_err(|e| ScaffoldError::Save(format!("{}: {}", path.display(), e)))?;
+    }
+    Ok(files.into_iter().map(|(path, _)| path).collect())
+}
+
+pub fn github_workflow() -> (PathBuf, String) {
+    (
+        PathBuf::from(GITHUB_WORKFLOW_PATH),
+        GITHUB_WORKFLOW.to_string(),
+    )
+}
+
+pub fn gitlab_ci() -> (PathBuf, String) {
+    (PathBuf::from(GITLAB_CI_PATH), GITLAB_CI.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use crate::validation::validator::IssueLevel;
+
+    use super::*;
+
+    #[test]
+    fn builtin_templates_parse() {
+        let ids: Vec<String> = ProjectTemplate::builtin()
+            .into_iter()
+            .map(|template| template.id)
+            .collect();
+        assert_eq!(
+            ids,
+            vec!["web-app", "microservice-broker", "mobile-backend"]
+        );
+        assert!(ProjectTemplate::find("web-app").is_ok());
+        assert!(matches!(
+            ProjectTemplate::find("mainframe"),
+            Err(ScaffoldError::UnknownTemplate(_))
+        ));
+    }
+
+    #[test]
+    fn scaffolded_templates_load_without_errors() {
+        for template in ProjectTemplate::builtin() {
+            let folder =
+                std::env::temp_dir().join(format!("threat-dragon-scaffold-{}", Uuid::new_v4()));
+            let project = template.to_project("Shop", "Team", &["Billing".to_string()]);
+            let (workflow_path, workflow) = github_workflow();
+            let extra = vec![(folder.join(workflow_path), workflow)];
+            let files = create_project(&folder, &project, extra);
+            let loaded = Project::load_folder(&folder, None);
+            fs::remove_dir_all(&folder).unwrap();
+
+            let files = files.unwrap_or_else(|e| panic!("{}: {}", template.id, e));
+            assert!(files.contains(&folder.join(GITHUB_WORKFLOW_PATH)));
+            let loaded = loaded.unwrap_or_else(|e| panic!("{}: {}", template.id, e));
+            assert_eq!(loaded.config.title, "Shop");
+            assert_eq!(loaded.diagrams.len(), template.diagrams.len());
+            assert!(loaded.diagrams[0]
+                .nodes
+                .iter()
+                .any(|node| node.name == "Billing"));
+            let errors: Vec<String> = loaded
+                .validate()
+                .iter()
+                .filter(|issue| issue.level == IssueLevel::Error)
+                .map(|issue| issue.to_string())
+                .collect();
+            assert!(errors.is_empty(), "{}: {:?}", template.id, errors);
+        }
+    }
+}
@@ -0,0 +1,18 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone)]
+pub enum ScaffoldError {
+    UnknownTemplate(String),
+    AlreadyExists(String),
+    Save(String),
+}
+
+impl Display for ScaffoldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScaffoldError::UnknownTemplate(e) => write!(f, "unknown project template: {}", e),
+            ScaffoldError::AlreadyExists(e) => write!(f, "file already exists: {}", e),
+            ScaffoldError::Save(e) => write!(f, "cannot create project: {}", e),
+        }
+    }
+}
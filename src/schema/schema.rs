@@ -0,0 +1,184 @@
+use std::{fs, path::Path};
+
+use schemars::{
+    schema::{RootSchema, Schema, SchemaObject, SingleOrVec, SubschemaValidation},
+    schema_for,
+};
+use serde_json::json;
+
+use crate::{
+    config::config::Config,
+    input::{input_diagram::InputDiagram, threat::Threat},
+    project::{component::COMPONENT_KEY, include::INCLUDE_KEY},
+};
+
+use super::schema_error::SchemaError;
+
+pub const CONFIG_SCHEMA_FILE_NAME: &str = "config.schema.json";
+pub const THREATS_SCHEMA_FILE_NAME: &str = "threats.schema.json";
+pub const DIAGRAM_SCHEMA_FILE_NAME: &str = "diagram.schema.json";
+
+/// JSON Schemas of the YAML inputs, derived from the types they are read
+/// into, with their file name.
+pub fn schemas() -> Vec<(&'static str, RootSchema)> {
+    vec![
+        (CONFIG_SCHEMA_FILE_NAME, schema_for!(Config)),
+        (THREATS_SCHEMA_FILE_NAME, schema_for!(Vec<Threat>)),
+        (DIAGRAM_SCHEMA_FILE_NAME, schema_for!(InputDiagram)),
+    ]
+    .into_iter()
+    .map(|(file_name, mut root)| {
+        allow_includes(&mut root);
+        if file_name == DIAGRAM_SCHEMA_FILE_NAME {
+            allow_component_references(&mut root);
+        }
+        (file_name, root)
+    })
+    .collect()
+}
+
+/// Accept the `include` key of the root mapping and `include` items in the
+/// lists, resolved when the project is loaded.
+fn allow_includes(root: &mut RootSchema) {
+    let files: Schema = serde_json::from_value(json!({
+        "description": "File or list of files included, relative to this file",
+        "anyOf": [
+            { "type": "string" },
+            { "type": "array", "items": { "type": "string" } }
+        ]
+    }))
+    .expect("include schema is valid");
+    let item: Schema = serde_json::from_value(json!({
+        "type": "object",
+        "required": [INCLUDE_KEY],
+        "properties": { INCLUDE_KEY: files },
+        "additionalProperties": false
+    }))
+    .expect("include schema is valid");
+    root.definitions.insert("Include".to_string(), item);
+    let include_item = Schema::new_ref("#/definitions/Include".to_string());
+
+    if let Some(object) = root.schema.object.as_mut() {
+        object.properties.insert(INCLUDE_KEY.to_string(), files);
+        // The required keys may be written in the included files.
+        let required = std::mem::take(&mut object.required);
+        let condition: Schema = serde_json::from_value(json!({ "required": [INCLUDE_KEY] }))
+            .expect("include schema is valid");
+        let otherwise: Schema = serde_json::from_value(json!({ "required": required }))
+            .expect("include schema is valid");
+        root.schema.subschemas().if_schema = Some(Box::new(condition));
+        root.schema.subschemas().else_schema = Some(Box::new(otherwise));
+    }
+    accept_include_items(&mut root.schema, &include_item);
+    for definition in root.definitions.values_mut() {
+        if let Schema::Object(schema) = definition {
+            accept_include_items(schema, &include_item);
+        }
+    }
+}
+
+/// Let the lists of `schema` and of its properties hold include items.
+fn accept_include_items(schema: &mut SchemaObject, include_item: &Schema) {
+    if let Some(SingleOrVec::Single(items)) =
+        schema.array.as_mut().and_then(|array| array.items.as_mut())
+    {
+        let item = std::mem::replace(items.as_mut(), Schema::Bool(true));
+        **items = Schema::Object(SchemaObject {
+            subschemas: Some(Box::new(SubschemaValidation {
+                any_of: Some(vec![item, include_item.clone()]),
+                ..Default::default()
+            })),
+            ..Default::default()
+        });
+    }
+    if let Some(object) = schema.object.as_mut() {
+        for property in object.properties.values_mut() {
+            if let Schema::Object(property) = property {
+                accept_include_items(property, include_item);
+            }
+        }
+    }
+}
+
+/// Accept nodes referencing a component of the configuration, with any field
+/// of a node overriding the one of the component.
+fn allow_component_references(root: &mut RootSchema) {
+    let Some(Schema::Object(node)) = root.definitions.get("Node") else {
+        return;
+    };
+    let mut properties = node
+        .object
+        .as_ref()
+        .map(|object| object.properties.clone())
+        .unwrap_or_default();
+    properties.insert(
+        COMPONENT_KEY.to_string(),
+        serde_json::from_value(json!({
+            "description": "Name of a component of the configuration",
+            "type": "string"
+        }))
+        .expect("component schema is valid"),
+    );
+    let reference: Schema = serde_json::from_value(json!({
+        "type": "object",
+        "required": [COMPONENT_KEY],
+        "properties": properties
+    }))
+    .expect("component schema is valid");
+    root.definitions
+        .insert("ComponentReference".to_string(), reference);
+
+    let nodes = root
+        .schema
+        .object
+        .as_mut()
+        .and_then(|object| object.properties.get_mut("nodes"));
+    if let Some(Schema::Object(nodes)) = nodes {
+        if let Some(SingleOrVec::Single(items)) =
+            nodes.array.as_mut().and_then(|array| array.items.as_mut())
+        {
+            if let Schema::Object(items) = items.as_mut() {
+                items
+                    .subschemas()
+                    .any_of
+                    .get_or_insert_with(Vec::new)
+                    .push(Schema::new_ref(
+                        "#/definitions/ComponentReference".to_string(),
+                    ));
+            }
+        }
+    }
+}
+
+/// Write the schemas of the YAML inputs in `output_folder`.
+pub fn create_schemas(output_folder: &Path) -> Result<(), SchemaError> {
+    fs::create_dir_all(output_folder).map_err(|e| SchemaError::Save(format!("{}", e)))?;
+    for (file_name, schema) in schemas() {
+        let content = serde_json::to_string_pretty(&schema)
+            .map_err(|e| SchemaError::Save(format!("{}", e)))?;
+        fs::write(output_folder.join(file_name), content + "\n")
+            .map_err(|e| SchemaError::Save(format!("{}: {}", file_name, e)))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The schemas of the repository must be regenerated with
+    /// `threat-dragon-generator schema` when the inputs change.
+    #[test]
+    fn committed_schemas_are_up_to_date() {
+        let folder = Path::new(env!("CARGO_MANIFEST_DIR")).join("schemas");
+        for (file_name, schema) in schemas() {
+            let expected = serde_json::to_string_pretty(&schema).unwrap() + "\n";
+            let committed = fs::read_to_string(folder.join(file_name)).unwrap();
+            assert!(
+                committed == expected,
+                "schemas/{} is out of date, regenerate the schemas",
+                file_name
+            );
+        }
+    }
+}
@@ -0,0 +1,14 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone)]
+pub enum SchemaError {
+    Save(String),
+}
+
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaError::Save(e) => write!(f, "cannot save schema: {}", e),
+        }
+    }
+}
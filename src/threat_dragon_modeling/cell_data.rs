@@ -4,9 +4,7 @@ use uuid::Uuid;
 use crate::{
     config::config::Config,
     input::{input_diagram::Node, threat},
-    process::process::{
-        MappingFromInputNode, MappingFromInputNodeAndThreats, MappingFromInputThreat,
-    },
+    process::process::{MappingFromInputNodeAndThreats, MappingFromInputThreat},
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -41,11 +39,7 @@ pub struct CellData {
 }
 
 impl MappingFromInputNodeAndThreats for CellData {
-    fn from_input_diagram(
-        input_node: &Node,
-        config: &Config,
-        threats: &Vec<threat::Threat>,
-    ) -> Self {
+    fn from_input_diagram(input_node: &Node, config: &Config, threats: &[threat::Threat]) -> Self {
         Self {
             type_field: format!(
                 "tm.{}",
@@ -56,32 +50,30 @@ impl MappingFromInputNodeAndThreats for CellData {
                     .next()
                     .map(|c| c.to_uppercase().collect::<String>()
                         + &input_node.type_node.to_string()[c.len_utf8()..])
-                    .unwrap_or_else(String::new)
+                    .unwrap_or_default()
             ),
             name: input_node.name.clone(),
             description: input_node.description.clone(),
-            out_of_scope: input_node.out_of_scope.clone(),
+            out_of_scope: input_node.out_of_scope,
             reason_out_of_scope: Some("".to_string()),
             has_open_threats: false,
             is_bidirectional: None,
-            is_encrypted: None,
-            is_public_network: None,
-            protocol: None,
+            is_encrypted: input_node.is_encrypted,
+            is_public_network: input_node.is_public_network,
+            protocol: input_node.protocol.clone(),
             handles_card_payment: None,
             handles_goods_or_services: None,
             is_web_application: None,
             privilege_level: None,
             threats: input_node
-                .threats
-                .iter()
+                .threat_titles()
                 .map(|input_threat_name| {
                     threats
                         .iter()
-                        .filter(|threat| threat.title == *input_threat_name)
-                        .last()
+                        .rfind(|threat| threat.title == *input_threat_name)
                 })
                 .filter(|input_threat| input_threat.is_some())
-                .map(|input_threat| Threat::from_input_diagram(&input_threat.unwrap(), &config))
+                .map(|input_threat| Threat::from_input_diagram(input_threat.unwrap(), config))
                 .collect(),
         }
     }
@@ -105,16 +97,16 @@ pub struct Threat {
 }
 
 impl MappingFromInputThreat for Threat {
-    fn from_input_diagram(input_threat: &threat::Threat, config: &Config) -> Self {
+    fn from_input_diagram(input_threat: &threat::Threat, _config: &Config) -> Self {
         Self {
             id: Uuid::new_v4().to_string(),
             title: input_threat.title.clone(),
             status: input_threat.status.to_string(),
             severity: input_threat.severity.to_string(),
             type_field: input_threat.type_field.to_string(),
-            description: input_threat.description.clone(),
+            description: input_threat.description_with_references(),
             mitigation: input_threat.mitigation.clone(),
-            model_type: "STRIDE".to_string(),
+            model_type: input_threat.type_field.methodology().to_string(),
             new: false,
             number: 1,
             score: "".to_string(),
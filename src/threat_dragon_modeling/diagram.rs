@@ -35,23 +35,23 @@ impl MappingFromInputDiagram for Diagram {
         index: usize,
         input_diagram: &InputDiagram,
         config: &Config,
-        threats: &Vec<Threat>,
+        threats: &[Threat],
     ) -> Self {
-        let mut cells = input_diagram
+        let mut cells: Vec<Cell> = input_diagram
             .nodes
             .iter()
-            .map(|input_node| Cell::from_input_diagram(&input_node, &config, threats))
+            .map(|input_node| Cell::from_input_diagram(input_node, config, threats))
             .collect();
-        Self::update_source_and_destination(&mut cells, &input_diagram);
-        Self::update_cells_position(&mut cells, &input_diagram);
+        Self::update_source_and_destination(&mut cells, input_diagram);
+        Self::update_cells_position(&mut cells, input_diagram);
         Self::add_trust_boundaries(&mut cells, input_diagram);
 
         Self {
             id: index as u32,
             title: input_diagram.title.clone(),
-            diagram_type: "STRIDE".to_string(),
+            diagram_type: input_diagram.methodology().to_string(),
             placeholder: input_diagram.description.clone(),
-            thumbnail: "./public/content/images/thumbnail.stride.jpg".to_string(),
+            thumbnail: input_diagram.methodology().thumbnail(),
             version: config.threat_dragon_version.clone(),
             cells,
         }
@@ -59,7 +59,7 @@ impl MappingFromInputDiagram for Diagram {
 }
 
 impl Diagram {
-    fn update_source_and_destination(cells: &mut Vec<Cell>, input_diagram: &InputDiagram) {
+    fn update_source_and_destination(cells: &mut [Cell], input_diagram: &InputDiagram) {
         let mut node_ids_map = HashMap::new();
         cells.iter().for_each(|cell| {
             node_ids_map.insert(cell.data.name.clone(), cell.id.clone());
@@ -88,7 +88,7 @@ impl Diagram {
             });
     }
 
-    fn update_cells_position(cells: &mut Vec<Cell>, input_diagram: &InputDiagram) {
+    fn update_cells_position(cells: &mut [Cell], input_diagram: &InputDiagram) {
         let mut node_region = HashMap::new();
         let mut regions_positions: HashMap<String, (Position, Vec<String>)> = HashMap::new();
         input_diagram.nodes.iter().for_each(|node| {
@@ -107,7 +107,7 @@ impl Diagram {
             } else {
                 default_region
             };
-            if let Some((pos, node_list)) = regions_positions.get(&region) {
+            if let Some((_pos, node_list)) = regions_positions.get(&region) {
                 let mut new_node_list_in_region = node_list.clone();
                 new_node_list_in_region.push(node.name.clone());
                 let node_list_size_in_region = new_node_list_in_region.len();
@@ -132,15 +132,13 @@ impl Diagram {
         let region_radius = 120.;
         let center_x = 500.0;
         let center_y = 500.0;
-        // Initialiser un générateur de nombres aléatoires
-        let mut rng = rand::thread_rng();
         // Calculer les positions des régions en cercle
         let region_count = regions_positions.len();
         regions_positions
             .iter_mut()
             .filter(|(region, _)| region.ne(&&"Center".to_string()))
             .enumerate()
-            .for_each(|(index, (region, (pos, _)))| {
+            .for_each(|(index, (_region, (pos, _)))| {
                 let angle = 2.0 * PI * (index as f64) / (region_count as f64);
                 let region_center_x = center_x + radius * angle.cos();
                 let region_center_y = center_y + radius * angle.sin();
@@ -156,12 +154,11 @@ impl Diagram {
                     node_region.get(&cell.data.name).unwrap().to_owned();
                 let (region_position, nodes) = regions_positions.get(&region).unwrap().to_owned();
                 let angle = 2.0 * PI * (index_in_region as f64) / (nodes.len() as f64);
-                let test = if index_in_region > 2 { 0 } else { 0 };
                 let dx = region_radius * angle.cos();
                 let dy = region_radius * angle.sin();
                 cell.position = Some(Position {
-                    x: region_position.x + dx + test as f64,
-                    y: region_position.y + dy + test as f64,
+                    x: region_position.x + dx,
+                    y: region_position.y + dy,
                 });
                 cell.size = Some(Size {
                     width: 140,
@@ -200,8 +197,7 @@ impl Diagram {
             .for_each(|(trust_boundary, node_list)| {
                 let cell_last_node = cells
                     .iter()
-                    .filter(|cell| cell.data.name == node_list.get(0).unwrap().name)
-                    .last();
+                    .rfind(|cell| cell.data.name == node_list.first().unwrap().name);
 
                 let cells_for_node_list = cells.iter().filter(|cell| {
                     node_list
@@ -287,7 +283,7 @@ impl Diagram {
                     .filter(|size_x| *size_x > 0)
                     .max();
                 let margin: u32 = 40;
-                if let Some(cell_last_node) = cell_last_node {
+                if cell_last_node.is_some() {
                     cells.push(Cell {
                         position: Some(Position {
                             x: min_pos_x.unwrap() - margin as f64,
@@ -308,6 +304,8 @@ impl Diagram {
                             text: None,
                             body: None,
                             line: None,
+                            top_line: None,
+                            bottom_line: None,
                         }),
                         visible: None,
                         shape: "trust-boundary-box".to_string(),
@@ -366,17 +364,17 @@ pub struct Cell {
 }
 
 impl MappingFromInputNodeAndThreats for Cell {
-    fn from_input_diagram(input_node: &Node, config: &Config, threats: &Vec<Threat>) -> Self {
+    fn from_input_diagram(input_node: &Node, config: &Config, threats: &[Threat]) -> Self {
         Self {
             position: None,
             size: None,
-            attrs: Some(Attrs::from_input_diagram(&input_node, &config)),
+            attrs: Some(Attrs::from_input_diagram(input_node, config)),
             visible: None,
             shape: input_node.type_node.to_string(),
             id: Uuid::new_v4().to_string(),
             z_index: 1,
             connector: None,
-            data: CellData::from_input_diagram(&input_node, &config, threats),
+            data: CellData::from_input_diagram(input_node, config, threats),
             source: None,
             target: None,
             labels: None,
@@ -386,65 +384,72 @@ impl MappingFromInputNodeAndThreats for Cell {
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
-struct Position {
-    x: f64,
-    y: f64,
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
-struct Size {
-    width: u32,
-    height: u32,
+pub struct Size {
+    pub width: u32,
+    pub height: u32,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
-struct Attrs {
+pub struct Attrs {
     #[serde(skip_serializing_if = "Option::is_none")]
-    header_text: Option<Text>,
+    pub header_text: Option<Text>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    text: Option<Text>,
+    pub text: Option<Text>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    body: Option<Body>,
+    pub body: Option<Body>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    line: Option<Line>,
+    pub line: Option<Line>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_line: Option<Body>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bottom_line: Option<Body>,
 }
 
 impl MappingFromInputNode for Attrs {
     fn from_input_diagram(input_node: &Node, config: &Config) -> Self {
-        let (header_text, text, body, line) = match input_node.type_node {
-            TypeNode::Process => (
-                None,
-                Some(Text::from_input_diagram(&input_node, &config)),
-                Some(Body::from_input_diagram(&input_node, &config)),
-                None,
-            ),
-            TypeNode::Flow => (
-                None,
-                None,
-                None,
-                Some(Line::from_input_diagram(&input_node, &config)),
-            ),
+        let mut attrs = Self {
+            header_text: None,
+            text: None,
+            body: None,
+            line: None,
+            top_line: None,
+            bottom_line: None,
         };
-
-        Self {
-            header_text,
-            text,
-            body,
-            line,
-        }
+        match input_node.type_node {
+            TypeNode::Actor | TypeNode::Process => {
+                attrs.text = Some(Text::from_input_diagram(input_node, config));
+                attrs.body = Some(Body::from_input_diagram(input_node, config));
+            }
+            // Threat Dragon draws a store with a top and a bottom line instead of a body
+            TypeNode::Store => {
+                attrs.text = Some(Text::from_input_diagram(input_node, config));
+                attrs.top_line = Some(Body::from_input_diagram(input_node, config));
+                attrs.bottom_line = Some(Body::from_input_diagram(input_node, config));
+            }
+            TypeNode::Flow => {
+                attrs.line = Some(Line::from_input_diagram(input_node, config));
+            }
+        };
+        attrs
     }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
-struct Text {
-    text: String,
+pub struct Text {
+    pub text: String,
 }
 
 impl MappingFromInputNode for Text {
-    fn from_input_diagram(input_node: &Node, config: &Config) -> Self {
+    fn from_input_diagram(input_node: &Node, _config: &Config) -> Self {
         Self {
             text: input_node.name.clone(),
         }
@@ -453,15 +458,15 @@ impl MappingFromInputNode for Text {
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
-struct Body {
-    stroke: String,
-    stroke_width: f64,
-    stroke_dasharray: Option<String>,
+pub struct Body {
+    pub stroke: String,
+    pub stroke_width: f64,
+    pub stroke_dasharray: Option<String>,
 }
 
 impl MappingFromInputNode for Body {
-    fn from_input_diagram(input_node: &Node, config: &Config) -> Self {
-        let (stroke, stroke_width) = if input_node.threats.len() > 0 {
+    fn from_input_diagram(input_node: &Node, _config: &Config) -> Self {
+        let (stroke, stroke_width) = if !input_node.threats.is_empty() {
             ("red".to_string(), 1.5)
         } else {
             ("#333333".to_string(), 3.)
@@ -486,17 +491,17 @@ impl MappingFromInputNode for Body {
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
-struct Line {
-    stroke: String,
-    stroke_width: f64,
-    source_marker: SourceMarker,
-    target_marker: TargetMarker,
-    stroke_dasharray: Option<String>,
+pub struct Line {
+    pub stroke: String,
+    pub stroke_width: f64,
+    pub source_marker: SourceMarker,
+    pub target_marker: TargetMarker,
+    pub stroke_dasharray: Option<String>,
 }
 
 impl MappingFromInputNode for Line {
-    fn from_input_diagram(input_node: &Node, config: &Config) -> Self {
-        let (stroke, stroke_width) = if input_node.threats.len() > 0 {
+    fn from_input_diagram(input_node: &Node, _config: &Config) -> Self {
+        let (stroke, stroke_width) = if !input_node.threats.is_empty() {
             ("red".to_string(), 1.5)
         } else {
             ("#333333".to_string(), 3.)
@@ -517,18 +522,18 @@ impl MappingFromInputNode for Line {
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
-struct SourceMarker {
-    name: String,
+pub struct SourceMarker {
+    pub name: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
-struct TargetMarker {
-    name: String,
+pub struct TargetMarker {
+    pub name: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
-struct Endpoint {
-    cell: String,
+pub struct Endpoint {
+    pub cell: String,
 }
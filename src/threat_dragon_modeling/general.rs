@@ -3,7 +3,7 @@ use std::collections::BTreeMap;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    config::config::{Config, ConfigError, ConfigYaml},
+    config::config::Config,
     input::{input_diagram::InputDiagram, threat::Threat},
     process::process::{MappingFromInputDiagram, MappingFromVecInputDiagram},
 };
@@ -19,18 +19,18 @@ pub struct ThreatModeling {
 }
 
 impl ThreatModeling {
-    pub fn new(input_diagram: &Vec<InputDiagram>, config: &Config, threats: &Vec<Threat>) -> Self {
+    pub fn new(input_diagram: &[InputDiagram], config: &Config, threats: &[Threat]) -> Self {
         Self {
             version: config.threat_dragon_version.clone(),
             summary: Summary::new(config),
-            detail: Detail::from_input_diagram(&input_diagram, &config, threats),
+            detail: Detail::from_input_diagram(input_diagram, config, threats),
         }
     }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
-struct Summary {
+pub struct Summary {
     pub title: String,
     pub owner: String,
     pub description: String,
@@ -48,13 +48,6 @@ impl Summary {
     }
 }
 
-impl ConfigYaml for ThreatModeling {
-    fn from_config(&mut self, config: &Config) -> Result<(), ConfigError> {
-        self.version = config.threat_dragon_version.clone();
-        Ok(())
-    }
-}
-
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Detail {
@@ -67,9 +60,9 @@ pub struct Detail {
 
 impl MappingFromVecInputDiagram for Detail {
     fn from_input_diagram(
-        input_diagram: &Vec<InputDiagram>,
+        input_diagram: &[InputDiagram],
         config: &Config,
-        threats: &Vec<Threat>,
+        threats: &[Threat],
     ) -> Self {
         let json_diagram: BTreeMap<String, Diagram> = input_diagram
             .iter()
@@ -77,7 +70,7 @@ impl MappingFromVecInputDiagram for Detail {
             .map(|(index, input_diagram)| {
                 (
                     input_diagram.title.clone(),
-                    Diagram::from_input_diagram(index, &input_diagram, &config, threats),
+                    Diagram::from_input_diagram(index, input_diagram, config, threats),
                 )
             })
             .collect();
@@ -0,0 +1,79 @@
+use crate::input::{
+    input_diagram::{InputDiagram, TypeNode},
+    threat::{Methodology, StrideThreat, Threat, TypeThreat},
+};
+
+use super::validator::{IssueLevel, ValidationIssue};
+
+/// STRIDE categories applicable to each element type, following STRIDE per
+/// element.
+fn expected_categories(type_node: &TypeNode) -> Vec<StrideThreat> {
+    match type_node {
+        TypeNode::Actor => vec![StrideThreat::Spoofing, StrideThreat::Repudiation],
+        TypeNode::Process => vec![
+            StrideThreat::Spoofing,
+            StrideThreat::Tampering,
+            StrideThreat::Repudiation,
+            StrideThreat::InformationDisclosure,
+            StrideThreat::DenialOfService,
+            StrideThreat::ElevationOfPrivilege,
+        ],
+        TypeNode::Store => vec![
+            StrideThreat::Tampering,
+            StrideThreat::Repudiation,
+            StrideThreat::InformationDisclosure,
+            StrideThreat::DenialOfService,
+        ],
+        TypeNode::Flow => vec![
+            StrideThreat::Tampering,
+            StrideThreat::InformationDisclosure,
+            StrideThreat::DenialOfService,
+        ],
+    }
+}
+
+/// The in-scope elements of a STRIDE diagram should have a threat for each
+/// category applicable to their type.
+pub fn check_stride_coverage(
+    input_diagram: &InputDiagram,
+    threats: &[Threat],
+) -> Vec<ValidationIssue> {
+    if input_diagram.methodology() != Methodology::Stride {
+        return Vec::new();
+    }
+    input_diagram
+        .nodes
+        .iter()
+        .filter(|node| !node.out_of_scope.unwrap_or(false))
+        .filter_map(|node| {
+            let covered: Vec<StrideThreat> = node
+                .threat_titles()
+                .filter_map(|title| threats.iter().rfind(|threat| threat.title == *title))
+                .filter_map(|threat| match &threat.type_field {
+                    TypeThreat::Stride(category) => Some(category.clone()),
+                    _ => None,
+                })
+                .collect();
+            let missing: Vec<String> = expected_categories(&node.type_node)
+                .into_iter()
+                .filter(|category| !covered.contains(category))
+                .map(|category| category.to_string())
+                .collect();
+            if missing.is_empty() {
+                return None;
+            }
+            Some(ValidationIssue {
+                level: IssueLevel::Warning,
+                rule: "stride-coverage".to_string(),
+                diagram: input_diagram.title.clone(),
+                node: Some(node.name.clone()),
+                message: format!(
+                    "{} {} has no threat for: {}",
+                    node.type_node,
+                    node.name,
+                    missing.join(", ")
+                ),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,244 @@
+use std::fmt::Display;
+
+use crate::{
+    input::{
+        input_diagram::{InputDiagram, Node, TypeNode},
+        threat::{Severity, Status, Threat},
+    },
+    reports::sections,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GateRule {
+    /// Open threats of a severity.
+    OpenThreats(Severity),
+    /// Open threats on flows going through a public network.
+    UnmitigatedPublicFlows,
+}
+
+impl GateRule {
+    pub fn name(&self) -> String {
+        match self {
+            GateRule::OpenThreats(severity) => {
+                format!("open-{}-threats", severity.to_string().to_lowercase())
+            }
+            GateRule::UnmitigatedPublicFlows => "unmitigated-public-flows".to_string(),
+        }
+    }
+
+    fn matches(&self, node: &Node, threat: &Threat) -> bool {
+        match self {
+            GateRule::OpenThreats(severity) => {
+                threat.status == Status::Open && threat.severity == *severity
+            }
+            GateRule::UnmitigatedPublicFlows => {
+                threat.status == Status::Open
+                    && node.type_node == TypeNode::Flow
+                    && node.is_public_network == Some(true)
+            }
+        }
+    }
+}
+
+impl Display for GateRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GateRule::OpenThreats(severity) => write!(f, "Open {} threats", severity),
+            GateRule::UnmitigatedPublicFlows => {
+                write!(f, "Open threats on public network flows")
+            }
+        }
+    }
+}
+
+/// Threshold of a rule: at most `max` matching threats in each diagram.
+#[derive(Debug, Clone)]
+pub struct Gate {
+    pub rule: GateRule,
+    pub max: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct GateResult {
+    pub diagram: String,
+    pub gate: Gate,
+    /// Matching threats, each counted once whatever the number of nodes it
+    /// is on, as `<threat title> (<nodes>)`.
+    pub threats: Vec<String>,
+}
+
+impl GateResult {
+    pub fn passed(&self) -> bool {
+        self.threats.len() <= self.gate.max
+    }
+}
+
+impl Display for GateResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} {} found, at most {} allowed",
+            self.diagram,
+            self.threats.len(),
+            self.gate.rule,
+            self.gate.max
+        )
+    }
+}
+
+/// Result of each gate on each diagram, in the order of the diagrams. A
+/// threat matching on several nodes of a diagram counts once.
+pub fn evaluate(
+    input_diagrams: &[InputDiagram],
+    threats: &[Threat],
+    gates: &[Gate],
+) -> Vec<GateResult> {
+    input_diagrams
+        .iter()
+        .flat_map(|input_diagram| {
+            let node_threats = sections::node_threats(input_diagram, threats);
+            gates.iter().map(move |gate| {
+                let mut matching: Vec<(&String, Vec<&str>)> = Vec::new();
+                node_threats
+                    .iter()
+                    .filter(|(node, threat)| gate.rule.matches(node, threat))
+                    .for_each(|(node, threat)| {
+                        match matching
+                            .iter_mut()
+                            .find(|(title, _)| **title == threat.title)
+                        {
+                            Some((_, nodes)) => nodes.push(&node.name),
+                            None => matching.push((&threat.title, vec![&node.name])),
+                        }
+                    });
+                GateResult {
+                    diagram: input_diagram.title.clone(),
+                    gate: gate.clone(),
+                    threats: matching
+                        .iter()
+                        .map(|(title, nodes)| format!("{} ({})", title, nodes.join(", ")))
+                        .collect(),
+                }
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+    use crate::project::project::Project;
+
+    const CONFIG: &str = r#"threatDragonVersion: "2.2.0"
+title: "Shop"
+owner: "Team"
+description: ""
+trustBoundaries: []
+assets: []
+diagrams: []
+"#;
+
+    const THREATS: &str = r#"- title: "Flooding"
+  status: "Open"
+  severity: "High"
+  type: "DenialOfService"
+  description: ""
+  mitigation: ""
+  vector: ""
+- title: "Sniffing"
+  status: "Open"
+  severity: "Medium"
+  type: "InformationDisclosure"
+  description: ""
+  mitigation: ""
+  vector: ""
+- title: "Replay"
+  status: "Mitigated"
+  severity: "High"
+  type: "Spoofing"
+  description: ""
+  mitigation: "Nonces"
+  vector: ""
+"#;
+
+    const DIAGRAM: &str = r#"title: "Orders"
+description: ""
+nodes:
+  - name: User
+    type: actor
+    description: ""
+    threats: []
+  - name: Web
+    type: process
+    description: ""
+    threats:
+      - "Flooding"
+  - name: Api
+    type: process
+    description: ""
+    threats:
+      - "Flooding"
+      - "Replay"
+  - name: Login
+    type: flow
+    description: ""
+    source: User
+    destination: Web
+    isPublicNetwork: true
+    threats:
+      - "Sniffing"
+      - "Replay"
+  - name: Call
+    type: flow
+    description: ""
+    source: Web
+    destination: Api
+    threats:
+      - "Sniffing"
+"#;
+
+    fn results(gates: &[Gate]) -> Vec<GateResult> {
+        let project = Project::parse(
+            (Path::new("config.yaml"), CONFIG),
+            (Path::new("threats.yaml"), THREATS),
+            &[(PathBuf::from("orders.yaml"), DIAGRAM.to_string())],
+            None,
+        )
+        .unwrap();
+        evaluate(&project.diagrams, &project.threats, gates)
+    }
+
+    #[test]
+    fn threats_on_several_nodes_count_once() {
+        let results = results(&[
+            Gate {
+                rule: GateRule::OpenThreats(Severity::High),
+                max: 1,
+            },
+            Gate {
+                rule: GateRule::OpenThreats(Severity::Medium),
+                max: 0,
+            },
+        ]);
+        assert_eq!(results[0].threats, vec!["Flooding (Web, Api)"]);
+        assert!(results[0].passed());
+        assert_eq!(results[1].threats, vec!["Sniffing (Login, Call)"]);
+        assert!(!results[1].passed());
+        assert_eq!(
+            results[1].to_string(),
+            "Orders: 1 Open Medium threats found, at most 0 allowed"
+        );
+    }
+
+    #[test]
+    fn public_flows_with_open_threats() {
+        let results = results(&[Gate {
+            rule: GateRule::UnmitigatedPublicFlows,
+            max: 0,
+        }]);
+        assert_eq!(results[0].threats, vec!["Sniffing (Login)"]);
+        assert_eq!(results[0].gate.rule.name(), "unmitigated-public-flows");
+    }
+}
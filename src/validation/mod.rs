@@ -0,0 +1,4 @@
+pub mod coverage;
+pub mod gate;
+pub mod policy;
+pub mod validator;
@@ -0,0 +1,101 @@
+use crate::{
+    config::config::Config,
+    input::{
+        input_diagram::{InputDiagram, TypeNode},
+        threat::{Severity, Status, Threat},
+    },
+};
+
+use super::validator::{IssueLevel, ValidationIssue};
+
+/// Security policy of the threat model: open High threats, unencrypted flows
+/// on public networks, threats closed without explanation and elements using
+/// trust boundaries or assets missing from the configuration.
+pub fn check_policies(
+    input_diagram: &InputDiagram,
+    config: &Config,
+    threats: &[Threat],
+) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let mut push = |level: IssueLevel, rule: &str, node: &str, message: String| {
+        issues.push(ValidationIssue {
+            level,
+            rule: rule.to_string(),
+            diagram: input_diagram.title.clone(),
+            node: Some(node.to_string()),
+            message,
+        })
+    };
+
+    for node in input_diagram.nodes.iter() {
+        for threat in node
+            .threat_titles()
+            .filter_map(|title| threats.iter().rfind(|threat| threat.title == *title))
+        {
+            if threat.status == Status::Open && threat.severity == Severity::High {
+                push(
+                    IssueLevel::Error,
+                    "open-high-threat",
+                    &node.name,
+                    format!("threat \"{}\" is Open with a High severity", threat.title),
+                );
+            }
+            if threat.status != Status::Open && threat.mitigation.trim().is_empty() {
+                push(
+                    IssueLevel::Warning,
+                    "missing-mitigation",
+                    &node.name,
+                    format!(
+                        "threat \"{}\" is {} without mitigation or reason",
+                        threat.title, threat.status
+                    ),
+                );
+            }
+        }
+
+        if node.type_node == TypeNode::Flow
+            && node.is_public_network == Some(true)
+            && node.is_encrypted != Some(true)
+        {
+            push(
+                IssueLevel::Warning,
+                "unencrypted-public-flow",
+                &node.name,
+                format!(
+                    "flow {} goes through a public network without encryption",
+                    node.name
+                ),
+            );
+        }
+
+        if let Some(trust_boundary) = &node.trust_boundary {
+            if !config
+                .trust_boundaries
+                .iter()
+                .any(|known| known.name == *trust_boundary)
+            {
+                push(
+                    IssueLevel::Warning,
+                    "undeclared-trust-boundary",
+                    &node.name,
+                    format!(
+                        "trust boundary {} is not declared in the configuration",
+                        trust_boundary
+                    ),
+                );
+            }
+        }
+
+        if let Some(asset) = &node.asset {
+            if !config.assets.iter().any(|known| known.name == *asset) {
+                push(
+                    IssueLevel::Warning,
+                    "undeclared-asset",
+                    &node.name,
+                    format!("asset {} is not declared in the configuration", asset),
+                );
+            }
+        }
+    }
+    issues
+}
@@ -0,0 +1,407 @@
+use std::fmt::Display;
+
+use crate::{
+    config::config::Config,
+    input::{
+        input_diagram::{InputDiagram, Node, TypeNode},
+        threat::Threat,
+    },
+    json::json::changed_fields,
+    project::component::ComponentOverrides,
+};
+
+use super::{coverage, policy};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IssueLevel {
+    Error,
+    Warning,
+}
+
+impl Display for IssueLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            IssueLevel::Error => write!(f, "error"),
+            IssueLevel::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ValidationIssue {
+    pub level: IssueLevel,
+    pub rule: String,
+    pub diagram: String,
+    pub node: Option<String>,
+    pub message: String,
+}
+
+impl Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.node {
+            Some(node) => write!(
+                f,
+                "{}[{}]: {} > {}: {}",
+                self.level, self.rule, self.diagram, node, self.message
+            ),
+            None => write!(
+                f,
+                "{}[{}]: {}: {}",
+                self.level, self.rule, self.diagram, self.message
+            ),
+        }
+    }
+}
+
+pub fn validate(
+    input_diagrams: &[InputDiagram],
+    config: &Config,
+    threats: &[Threat],
+    component_overrides: &ComponentOverrides,
+) -> Vec<ValidationIssue> {
+    let mut issues: Vec<ValidationIssue> = written_diagrams(input_diagrams, config)
+        .flat_map(|input_diagram| {
+            let mut issues = check_flows(input_diagram);
+            issues.extend(check_threats(input_diagram, threats));
+            issues
+        })
+        .collect();
+    issues.extend(check_components(
+        input_diagrams,
+        config,
+        component_overrides,
+    ));
+    issues
+}
+
+/// Validation issues, STRIDE coverage gaps and policy violations of the
+/// diagrams, reported by code scanning. Only the validation issues stop the
+/// generation. The nodes of child diagrams are copies of their parent's and
+/// are only reported once, in the parent.
+pub fn findings(
+    input_diagrams: &[InputDiagram],
+    config: &Config,
+    threats: &[Threat],
+    component_overrides: &ComponentOverrides,
+) -> Vec<ValidationIssue> {
+    let mut issues = validate(input_diagrams, config, threats, component_overrides);
+    written_diagrams(input_diagrams, config).for_each(|input_diagram| {
+        issues.extend(coverage::check_stride_coverage(input_diagram, threats));
+        issues.extend(policy::check_policies(input_diagram, config, threats));
+    });
+    issues
+}
+
+/// Diagrams written in the project, without the child diagrams of the
+/// configuration.
+fn written_diagrams<'a>(
+    input_diagrams: &'a [InputDiagram],
+    config: &'a Config,
+) -> impl Iterator<Item = &'a InputDiagram> {
+    input_diagrams.iter().filter(|input_diagram| {
+        !config
+            .diagrams
+            .iter()
+            .any(|child| child.name == input_diagram.title)
+    })
+}
+
+pub fn has_errors(issues: &[ValidationIssue]) -> bool {
+    issues.iter().any(|issue| issue.level == IssueLevel::Error)
+}
+
+/// A flow goes from a node of its diagram to another one, flows excluded.
+fn check_flows(input_diagram: &InputDiagram) -> Vec<ValidationIssue> {
+    let is_node = |name: &str| {
+        input_diagram
+            .nodes
+            .iter()
+            .any(|node| node.type_node != TypeNode::Flow && node.name == name)
+    };
+    let mut issues = Vec::new();
+    input_diagram
+        .nodes
+        .iter()
+        .filter(|node| node.type_node == TypeNode::Flow)
+        .for_each(|flow| {
+            [("source", &flow.source), ("destination", &flow.destination)]
+                .into_iter()
+                .for_each(|(end, name)| {
+                    let message = match name {
+                        Some(name) if is_node(name) => return,
+                        Some(name) => format!("{} {} is not a node of the diagram", end, name),
+                        None => format!("flow has no {}", end),
+                    };
+                    issues.push(ValidationIssue {
+                        level: IssueLevel::Error,
+                        rule: "unknown-endpoint".to_string(),
+                        diagram: input_diagram.title.clone(),
+                        node: Some(flow.name.clone()),
+                        message,
+                    });
+                });
+        });
+    issues
+}
+
+/// The threats of a diagram must be defined in the threat list and use the
+/// threat types of the diagram methodology.
+fn check_threats(input_diagram: &InputDiagram, threats: &[Threat]) -> Vec<ValidationIssue> {
+    let methodology = input_diagram.methodology();
+    let mut issues = Vec::new();
+    input_diagram.nodes.iter().for_each(|node| {
+        node.threat_titles().for_each(|threat_str| {
+            let threat = threats.iter().rfind(|threat| threat.title == *threat_str);
+            if threat.is_none() {
+                issues.push(ValidationIssue {
+                    level: IssueLevel::Warning,
+                    rule: "unknown-threat".to_string(),
+                    diagram: input_diagram.title.clone(),
+                    node: Some(node.name.clone()),
+                    message: format!("threat \"{}\" is not defined, it is ignored", threat_str),
+                });
+            }
+            if let Some(threat) = threat {
+                if threat.type_field.methodology() != methodology {
+                    issues.push(ValidationIssue {
+                        level: IssueLevel::Error,
+                        rule: "methodology".to_string(),
+                        diagram: input_diagram.title.clone(),
+                        node: Some(node.name.clone()),
+                        message: format!(
+                            "threat \"{}\" of type {} ({}) does not fit the {} methodology",
+                            threat.title,
+                            threat.type_field,
+                            threat.type_field.methodology(),
+                            methodology
+                        ),
+                    });
+                }
+            }
+        });
+    });
+    issues
+}
+
+/// A component written in several diagrams, under the same name, should be
+/// the same in each of them, apart from its threats which belong to each
+/// diagram. Nodes are compared with the catalog, or with their first copy, a
+/// reference to the catalog ignoring the fields written next to it. Child
+/// diagrams copy the nodes of their parent and are not compared.
+fn check_components(
+    input_diagrams: &[InputDiagram],
+    config: &Config,
+    component_overrides: &ComponentOverrides,
+) -> Vec<ValidationIssue> {
+    let value = |node: &Node, skipped: &[&str]| {
+        let mut value = serde_json::to_value(node).unwrap_or_default();
+        if let Some(fields) = value.as_object_mut() {
+            fields.retain(|field, _| !skipped.contains(&field.as_str()));
+        }
+        value
+    };
+    let mut first: Vec<(&str, &str, &Node)> = config
+        .components
+        .iter()
+        .map(|component| (component.name.as_str(), "the catalog", component))
+        .collect();
+    let mut issues = Vec::new();
+    written_diagrams(input_diagrams, config).for_each(|input_diagram| {
+        input_diagram
+            .nodes
+            .iter()
+            .filter(|node| node.type_node != TypeNode::Flow)
+            .for_each(|node| {
+                let Some((_, copy_of, first_node)) =
+                    first.iter().find(|(name, _, _)| *name == node.name)
+                else {
+                    first.push((&node.name, &input_diagram.title, node));
+                    return;
+                };
+                let mut skipped = vec!["threats"];
+                skipped.extend(
+                    component_overrides
+                        .get(&(input_diagram.title.clone(), node.name.clone()))
+                        .into_iter()
+                        .flatten()
+                        .map(String::as_str),
+                );
+                let fields = changed_fields(&value(first_node, &skipped), &value(node, &skipped));
+                if !fields.is_empty() {
+                    issues.push(ValidationIssue {
+                        level: IssueLevel::Warning,
+                        rule: "diverging-component".to_string(),
+                        diagram: input_diagram.title.clone(),
+                        node: Some(node.name.clone()),
+                        message: format!(
+                            "component differs from its copy in {}: {}",
+                            copy_of,
+                            fields.join(", ")
+                        ),
+                    });
+                }
+            });
+    });
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use crate::project::project::Project;
+
+    const CONFIG: &str = r#"threatDragonVersion: "2.2.0"
+title: "Shop"
+owner: "Team"
+description: ""
+trustBoundaries: []
+assets: []
+components:
+  - name: Broker
+    type: process
+    description: "Message broker"
+    trustLevel: "Operational"
+    threats: []
+diagrams: []
+"#;
+
+    const THREATS: &str = r#"- title: "Flooding of the broker"
+  status: "Open"
+  severity: "Medium"
+  type: "DenialOfService"
+  description: ""
+  mitigation: ""
+  vector: ""
+"#;
+
+    const FRONT: &str = r#"title: "Front"
+description: ""
+nodes:
+  - component: Broker
+    description: "Broker of the orders"
+  - name: Web
+    type: process
+    description: "Shop front"
+    threats: []
+"#;
+
+    const BACK: &str = r#"title: "Back"
+description: ""
+nodes:
+  - component: Broker
+    threats:
+      - "Flooding of the broker"
+  - name: Web
+    type: process
+    description: "Shop front"
+    threats:
+      - "Flooding of the broker"
+"#;
+
+    fn diverging(diagrams: &[(&str, &str)]) -> Vec<String> {
+        let diagrams: Vec<(PathBuf, String)> = diagrams
+            .iter()
+            .map(|(name, content)| (PathBuf::from(name), content.to_string()))
+            .collect();
+        let project = Project::parse(
+            (Path::new("config.yaml"), CONFIG),
+            (Path::new("threats.yaml"), THREATS),
+            &diagrams,
+            None,
+        )
+        .unwrap();
+        project
+            .validate()
+            .into_iter()
+            .filter(|issue| issue.rule == "diverging-component")
+            .map(|issue| issue.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn flows_need_nodes_at_both_ends() {
+        let diagram = r#"title: "Orders"
+description: ""
+nodes:
+  - name: Web
+    type: process
+    description: "Shop front"
+    threats: []
+  - name: Publish
+    type: flow
+    description: "Orders"
+    source: Web
+    destination: Nowhere
+    threats: []
+  - name: Notify
+    type: flow
+    description: "Notifications"
+    destination: Publish
+    threats: []
+"#;
+        let project = Project::parse(
+            (Path::new("config.yaml"), CONFIG),
+            (Path::new("threats.yaml"), THREATS),
+            &[(PathBuf::from("orders.yaml"), diagram.to_string())],
+            None,
+        )
+        .unwrap();
+        let issues: Vec<String> = project
+            .validate()
+            .into_iter()
+            .filter(|issue| issue.rule == "unknown-endpoint")
+            .map(|issue| issue.to_string())
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                "error[unknown-endpoint]: Orders > Publish: destination Nowhere is not a node of the diagram",
+                "error[unknown-endpoint]: Orders > Notify: flow has no source",
+                "error[unknown-endpoint]: Orders > Notify: destination Publish is not a node of the diagram",
+            ]
+        );
+    }
+
+    #[test]
+    fn example_components_do_not_diverge() {
+        let folder = Path::new(env!("CARGO_MANIFEST_DIR")).join("exemples");
+        let project = Project::load_folder(&folder, None).unwrap();
+        let diverging: Vec<String> = project
+            .validate()
+            .into_iter()
+            .filter(|issue| issue.rule == "diverging-component")
+            .map(|issue| issue.to_string())
+            .collect();
+        assert!(diverging.is_empty(), "{:?}", diverging);
+    }
+
+    #[test]
+    fn overrides_and_threats_do_not_diverge() {
+        assert!(diverging(&[("front.yaml", FRONT), ("back.yaml", BACK)]).is_empty());
+    }
+
+    #[test]
+    fn copies_are_compared_with_the_catalog() {
+        let copy = r#"title: "Ops"
+description: ""
+nodes:
+  - name: Broker
+    type: process
+    description: "Queue"
+    trustLevel: "Operational"
+    threats: []
+  - name: Web
+    type: process
+    description: "Shop"
+    threats: []
+"#;
+        assert_eq!(
+            diverging(&[("front.yaml", FRONT), ("ops.yaml", copy)]),
+            vec![
+                "warning[diverging-component]: Ops > Broker: component differs from its copy in the catalog: description",
+                "warning[diverging-component]: Ops > Web: component differs from its copy in Front: description",
+            ]
+        );
+    }
+}